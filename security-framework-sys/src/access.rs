@@ -1,5 +1,13 @@
-use core_foundation_sys::base::CFTypeID;
+use crate::base::SecTrustedApplicationRef;
+use core_foundation_sys::base::{CFTypeID, OSStatus};
+use std::os::raw::c_char;
 
 extern "C" {
     pub fn SecAccessGetTypeID() -> CFTypeID;
+
+    pub fn SecTrustedApplicationGetTypeID() -> CFTypeID;
+    pub fn SecTrustedApplicationCreateFromPath(
+        path: *const c_char,
+        app: *mut SecTrustedApplicationRef,
+    ) -> OSStatus;
 }
@@ -32,6 +32,9 @@ pub type SecCertificateRef = *mut OpaqueSecCertificateRef;
 pub enum OpaqueSecAccessRef {}
 pub type SecAccessRef = *mut OpaqueSecAccessRef;
 
+pub enum OpaqueSecTrustedApplicationRef {}
+pub type SecTrustedApplicationRef = *mut OpaqueSecTrustedApplicationRef;
+
 pub enum OpaqueSecAccessControlRef {}
 pub type SecAccessControlRef = *mut OpaqueSecAccessControlRef;
 
@@ -44,12 +47,19 @@ pub type SecIdentityRef = *mut OpaqueSecIdentityRef;
 pub enum OpaqueSecPolicyRef {}
 pub type SecPolicyRef = *mut OpaqueSecPolicyRef;
 
+// dispatch_queue_t and dispatch_data_t from libdispatch, used by the asynchronous APIs
+pub enum dispatch_queue_s {}
+pub type dispatch_queue_t = *mut dispatch_queue_s;
+pub enum dispatch_data_s {}
+pub type dispatch_data_t = *mut dispatch_data_s;
+
 pub const errSecSuccess: OSStatus = 0;
 pub const errSecUnimplemented: OSStatus = -4;
 pub const errSecIO: OSStatus = -36;
 pub const errSecParam: OSStatus = -50;
 pub const errSecBadReq: OSStatus = -909;
 pub const errSecNoTrustSettings: OSStatus = -25263;
+pub const errSecDecode: OSStatus = -26275;
 pub const errSecAuthFailed: OSStatus = -25293;
 pub const errSecDuplicateItem: OSStatus = -25299;
 pub const errSecItemNotFound: OSStatus = -25300;
@@ -57,7 +67,10 @@ pub const errSecCreateChainFailed: OSStatus = -25318;
 pub const errSecConversionError: OSStatus = -67594;
 pub const errSecHostNameMismatch: OSStatus = -67602;
 pub const errSecInvalidExtendedKeyUsage: OSStatus = -67609;
+pub const errSecIncompleteCertRevocationCheck: OSStatus = -67635;
 pub const errSecTrustSettingDeny: OSStatus = -67654;
+pub const errSecCertificateExpired: OSStatus = -67818;
+pub const errSecCertificateNotValidYet: OSStatus = -67819;
 pub const errSecCertificateRevoked: OSStatus = -67820;
 pub const errSecNotTrusted: OSStatus = -67843;
 pub const errSecInternalComponent: OSStatus = -2070;
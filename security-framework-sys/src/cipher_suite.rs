@@ -207,6 +207,17 @@ pub const TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384: SSLCipherSuite = 0xC030;
 pub const TLS_ECDH_RSA_WITH_AES_128_GCM_SHA256: SSLCipherSuite = 0xC031;
 pub const TLS_ECDH_RSA_WITH_AES_256_GCM_SHA384: SSLCipherSuite = 0xC032;
 
+// RFC 7905 - ChaCha20-Poly1305 Cipher Suites for TLS
+pub const TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256: SSLCipherSuite = 0xCCA8;
+pub const TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256: SSLCipherSuite = 0xCCA9;
+
+// TLS 1.3 standard cipher suites
+pub const TLS_AES_128_GCM_SHA256: SSLCipherSuite = 0x1301;
+pub const TLS_AES_256_GCM_SHA384: SSLCipherSuite = 0x1302;
+pub const TLS_CHACHA20_POLY1305_SHA256: SSLCipherSuite = 0x1303;
+pub const TLS_AES_128_CCM_SHA256: SSLCipherSuite = 0x1304;
+pub const TLS_AES_128_CCM_8_SHA256: SSLCipherSuite = 0x1305;
+
 // RFC 5746 - Secure Renegotiation
 pub const TLS_EMPTY_RENEGOTIATION_INFO_SCSV: SSLCipherSuite = 0x00FF;
 // Tags for SSL 2 cipher kinds which are not specified
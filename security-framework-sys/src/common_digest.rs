@@ -0,0 +1,43 @@
+use std::os::raw::{c_int, c_uchar, c_void};
+
+pub type CC_LONG = u32;
+pub type CC_LONG64 = u64;
+
+pub const CC_SHA224_DIGEST_LENGTH: usize = 28;
+pub const CC_SHA256_DIGEST_LENGTH: usize = 32;
+pub const CC_SHA384_DIGEST_LENGTH: usize = 48;
+pub const CC_SHA512_DIGEST_LENGTH: usize = 64;
+
+#[repr(C)]
+pub struct CC_SHA256_CTX {
+    pub count: [CC_LONG; 2],
+    pub hash: [CC_LONG; 8],
+    pub wbuf: [CC_LONG; 16],
+}
+
+#[repr(C)]
+pub struct CC_SHA512_CTX {
+    pub count: [CC_LONG64; 2],
+    pub hash: [CC_LONG64; 8],
+    pub wbuf: [CC_LONG64; 16],
+}
+
+extern "C" {
+    pub fn CC_SHA224(data: *const c_void, len: CC_LONG, md: *mut c_uchar) -> *mut c_uchar;
+    pub fn CC_SHA256(data: *const c_void, len: CC_LONG, md: *mut c_uchar) -> *mut c_uchar;
+    pub fn CC_SHA384(data: *const c_void, len: CC_LONG, md: *mut c_uchar) -> *mut c_uchar;
+    pub fn CC_SHA512(data: *const c_void, len: CC_LONG, md: *mut c_uchar) -> *mut c_uchar;
+
+    pub fn CC_SHA224_Init(c: *mut CC_SHA256_CTX) -> c_int;
+    pub fn CC_SHA224_Update(c: *mut CC_SHA256_CTX, data: *const c_void, len: CC_LONG) -> c_int;
+    pub fn CC_SHA224_Final(md: *mut c_uchar, c: *mut CC_SHA256_CTX) -> c_int;
+    pub fn CC_SHA256_Init(c: *mut CC_SHA256_CTX) -> c_int;
+    pub fn CC_SHA256_Update(c: *mut CC_SHA256_CTX, data: *const c_void, len: CC_LONG) -> c_int;
+    pub fn CC_SHA256_Final(md: *mut c_uchar, c: *mut CC_SHA256_CTX) -> c_int;
+    pub fn CC_SHA384_Init(c: *mut CC_SHA512_CTX) -> c_int;
+    pub fn CC_SHA384_Update(c: *mut CC_SHA512_CTX, data: *const c_void, len: CC_LONG) -> c_int;
+    pub fn CC_SHA384_Final(md: *mut c_uchar, c: *mut CC_SHA512_CTX) -> c_int;
+    pub fn CC_SHA512_Init(c: *mut CC_SHA512_CTX) -> c_int;
+    pub fn CC_SHA512_Update(c: *mut CC_SHA512_CTX, data: *const c_void, len: CC_LONG) -> c_int;
+    pub fn CC_SHA512_Final(md: *mut c_uchar, c: *mut CC_SHA512_CTX) -> c_int;
+}
@@ -19,6 +19,7 @@ extern "C" {
     pub static kSecReturnPersistentRef: CFStringRef;
 
     pub static kSecMatchSearchList: CFStringRef;
+    pub static kSecMatchIssuers: CFStringRef;
 
     pub static kSecAttrApplicationLabel: CFStringRef;
     pub static kSecAttrKeyType: CFStringRef;
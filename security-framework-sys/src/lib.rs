@@ -19,6 +19,7 @@ pub mod certificate_oids;
 pub mod cipher_suite;
 #[cfg(target_os = "macos")]
 pub mod code_signing;
+pub mod common_digest;
 #[cfg(target_os = "macos")]
 pub mod digest_transform;
 #[cfg(target_os = "macos")]
@@ -29,8 +30,12 @@ pub mod item;
 pub mod key;
 pub mod keychain;
 pub mod keychain_item;
+#[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+pub mod network;
 pub mod policy;
 pub mod random;
+#[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+pub mod sec_protocol;
 pub mod secure_transport;
 #[cfg(target_os = "macos")]
 pub mod transform;
@@ -0,0 +1,14 @@
+use crate::sec_protocol::sec_protocol_options_t;
+use std::os::raw::c_void;
+
+pub type nw_protocol_options_t = *mut c_void;
+
+#[cfg_attr(
+    any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"),
+    link(name = "Network", kind = "framework")
+)]
+extern "C" {
+    pub fn nw_tls_create_options() -> nw_protocol_options_t;
+    pub fn nw_tls_copy_sec_protocol_options(options: nw_protocol_options_t) -> sec_protocol_options_t;
+    pub fn nw_release(obj: *mut c_void);
+}
@@ -1,6 +1,9 @@
 #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
 use core_foundation_sys::base::CFOptionFlags;
 use core_foundation_sys::base::{Boolean, CFTypeID};
+#[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use core_foundation_sys::base::CFTypeRef;
+use core_foundation_sys::dictionary::CFDictionaryRef;
 use core_foundation_sys::string::CFStringRef;
 
 use crate::base::SecPolicyRef;
@@ -21,9 +24,35 @@ mod revocation_flags {
 pub use revocation_flags::*;
 
 extern "C" {
+    pub static kSecPolicyAppleX509Basic: CFStringRef;
+    pub static kSecPolicyAppleSSL: CFStringRef;
+    pub static kSecPolicyAppleSMIME: CFStringRef;
+    pub static kSecPolicyAppleEAP: CFStringRef;
+    pub static kSecPolicyAppleIPsec: CFStringRef;
+    #[cfg(target_os = "macos")]
+    pub static kSecPolicyAppleCodeSigning: CFStringRef;
+    pub static kSecPolicyAppleIDValidation: CFStringRef;
+    pub static kSecPolicyAppleTimeStamping: CFStringRef;
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub static kSecPolicyAppleRevocation: CFStringRef;
+
+    pub static kSecPolicyOid: CFStringRef;
+    pub static kSecPolicyName: CFStringRef;
+    pub static kSecPolicyClient: CFStringRef;
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub static kSecPolicyRevocationFlags: CFStringRef;
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub static kSecPolicyTeamIdentifier: CFStringRef;
+
     pub fn SecPolicyCreateSSL(server: Boolean, hostname: CFStringRef) -> SecPolicyRef;
     #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
     pub fn SecPolicyCreateRevocation(revocationFlags: CFOptionFlags) -> SecPolicyRef;
     pub fn SecPolicyGetTypeID() -> CFTypeID;
     pub fn SecPolicyCreateBasicX509() -> SecPolicyRef;
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub fn SecPolicyCreateWithProperties(
+        policyIdentifier: CFTypeRef,
+        properties: CFDictionaryRef,
+    ) -> SecPolicyRef;
+    pub fn SecPolicyCopyProperties(policyRef: SecPolicyRef) -> CFDictionaryRef;
 }
@@ -0,0 +1,135 @@
+use crate::base::{dispatch_data_t, dispatch_queue_t, SecCertificateRef, SecIdentityRef};
+use crate::trust::SecTrustRef;
+use core_foundation_sys::array::CFArrayRef;
+use std::os::raw::{c_char, c_void};
+
+pub enum sec_object {}
+
+pub type sec_protocol_options_t = *mut sec_object;
+pub type sec_protocol_metadata_t = *mut sec_object;
+pub type sec_trust_t = *mut sec_object;
+pub type sec_identity_t = *mut sec_object;
+pub type sec_certificate_t = *mut sec_object;
+
+pub type tls_protocol_version_t = u16;
+
+pub const tls_protocol_version_TLSv10: tls_protocol_version_t = 0x0301;
+pub const tls_protocol_version_TLSv11: tls_protocol_version_t = 0x0302;
+pub const tls_protocol_version_TLSv12: tls_protocol_version_t = 0x0303;
+pub const tls_protocol_version_TLSv13: tls_protocol_version_t = 0x0304;
+pub const tls_protocol_version_DTLSv10: tls_protocol_version_t = 0xfeff;
+pub const tls_protocol_version_DTLSv12: tls_protocol_version_t = 0xfefd;
+
+// the IANA cipher suite values, as in `CipherSuite.h`
+pub type tls_ciphersuite_t = u16;
+
+// An Objective-C block of type `void (^)(bool result)`.
+pub type sec_protocol_verify_complete_t = *const c_void;
+// An Objective-C block of type
+// `void (^)(sec_protocol_metadata_t metadata, sec_trust_t trust, sec_protocol_verify_complete_t complete)`.
+pub type sec_protocol_verify_t = *const c_void;
+// An Objective-C block of type `void (^)(sec_certificate_t certificate)`.
+pub type sec_protocol_metadata_certificate_handler_t = *const c_void;
+
+extern "C" {
+    pub fn sec_retain(obj: *mut c_void) -> *mut c_void;
+    pub fn sec_release(obj: *mut c_void);
+
+    pub fn sec_trust_create(trust: SecTrustRef) -> sec_trust_t;
+    pub fn sec_trust_copy_ref(trust: sec_trust_t) -> SecTrustRef;
+    pub fn sec_identity_create(identity: SecIdentityRef) -> sec_identity_t;
+    pub fn sec_identity_create_with_certificates(
+        identity: SecIdentityRef,
+        certificates: CFArrayRef,
+    ) -> sec_identity_t;
+    pub fn sec_certificate_create(certificate: SecCertificateRef) -> sec_certificate_t;
+    pub fn sec_certificate_copy_ref(certificate: sec_certificate_t) -> SecCertificateRef;
+
+    pub fn sec_protocol_options_set_local_identity(
+        options: sec_protocol_options_t,
+        identity: sec_identity_t,
+    );
+    pub fn sec_protocol_options_append_tls_ciphersuite(
+        options: sec_protocol_options_t,
+        ciphersuite: tls_ciphersuite_t,
+    );
+    pub fn sec_protocol_options_set_min_tls_protocol_version(
+        options: sec_protocol_options_t,
+        version: tls_protocol_version_t,
+    );
+    pub fn sec_protocol_options_set_max_tls_protocol_version(
+        options: sec_protocol_options_t,
+        version: tls_protocol_version_t,
+    );
+    pub fn sec_protocol_options_add_tls_application_protocol(
+        options: sec_protocol_options_t,
+        application_protocol: *const c_char,
+    );
+    pub fn sec_protocol_options_set_tls_server_name(
+        options: sec_protocol_options_t,
+        server_name: *const c_char,
+    );
+    pub fn sec_protocol_options_add_pre_shared_key(
+        options: sec_protocol_options_t,
+        psk: dispatch_data_t,
+        psk_identity: dispatch_data_t,
+    );
+    pub fn sec_protocol_options_set_tls_pre_shared_key_identity_hint(
+        options: sec_protocol_options_t,
+        psk_identity_hint: dispatch_data_t,
+    );
+    pub fn sec_protocol_options_set_peer_authentication_required(
+        options: sec_protocol_options_t,
+        peer_authentication_required: bool,
+    );
+    pub fn sec_protocol_options_set_tls_tickets_enabled(
+        options: sec_protocol_options_t,
+        tickets_enabled: bool,
+    );
+    pub fn sec_protocol_options_set_tls_resumption_enabled(
+        options: sec_protocol_options_t,
+        resumption_enabled: bool,
+    );
+    pub fn sec_protocol_options_set_tls_false_start_enabled(
+        options: sec_protocol_options_t,
+        false_start_enabled: bool,
+    );
+    pub fn sec_protocol_options_set_tls_ocsp_enabled(
+        options: sec_protocol_options_t,
+        ocsp_enabled: bool,
+    );
+    pub fn sec_protocol_options_set_tls_sct_enabled(
+        options: sec_protocol_options_t,
+        sct_enabled: bool,
+    );
+    pub fn sec_protocol_options_set_verify_block(
+        options: sec_protocol_options_t,
+        verify_block: sec_protocol_verify_t,
+        verify_block_queue: dispatch_queue_t,
+    );
+
+    pub fn sec_protocol_metadata_get_negotiated_protocol(
+        metadata: sec_protocol_metadata_t,
+    ) -> *const c_char;
+    pub fn sec_protocol_metadata_get_negotiated_tls_protocol_version(
+        metadata: sec_protocol_metadata_t,
+    ) -> tls_protocol_version_t;
+    pub fn sec_protocol_metadata_get_negotiated_tls_ciphersuite(
+        metadata: sec_protocol_metadata_t,
+    ) -> tls_ciphersuite_t;
+    pub fn sec_protocol_metadata_get_server_name(metadata: sec_protocol_metadata_t) -> *const c_char;
+    pub fn sec_protocol_metadata_get_early_data_accepted(metadata: sec_protocol_metadata_t) -> bool;
+    pub fn sec_protocol_metadata_access_peer_certificate_chain(
+        metadata: sec_protocol_metadata_t,
+        handler: sec_protocol_metadata_certificate_handler_t,
+    ) -> bool;
+    pub fn sec_protocol_metadata_copy_peer_public_key(
+        metadata: sec_protocol_metadata_t,
+    ) -> dispatch_data_t;
+    pub fn sec_protocol_metadata_create_secret(
+        metadata: sec_protocol_metadata_t,
+        label_len: usize,
+        label: *const c_char,
+        exporter_length: usize,
+    ) -> dispatch_data_t;
+}
@@ -173,6 +173,17 @@ extern "C" {
         peerName: *mut c_char,
         peerNameLen: *mut usize,
     ) -> OSStatus;
+    #[cfg(any(feature = "OSX_10_11", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub fn SSLCopyRequestedPeerNameLength(
+        context: SSLContextRef,
+        peerNameLen: *mut usize,
+    ) -> OSStatus;
+    #[cfg(any(feature = "OSX_10_11", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub fn SSLCopyRequestedPeerName(
+        context: SSLContextRef,
+        peerName: *mut c_char,
+        peerNameLen: *mut usize,
+    ) -> OSStatus;
     pub fn SSLSetCertificate(context: SSLContextRef, certRefs: CFArrayRef) -> OSStatus;
     #[cfg(target_os = "macos")]
     pub fn SSLSetCertificateAuthorities(
@@ -180,6 +191,7 @@ extern "C" {
         certificateOrArray: CFTypeRef,
         replaceExisting: Boolean,
     ) -> OSStatus;
+    pub fn SSLCopyDistinguishedNames(context: SSLContextRef, names: *mut CFArrayRef) -> OSStatus;
     #[cfg(target_os = "macos")]
     pub fn SSLCopyCertificateAuthorities(
         context: SSLContextRef,
@@ -1,10 +1,18 @@
 use crate::base::SecCertificateRef;
 use crate::base::SecKeyRef;
+#[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use crate::base::dispatch_queue_t;
 use core_foundation_sys::array::CFArrayRef;
 use core_foundation_sys::base::{Boolean, CFIndex, CFTypeID, CFTypeRef, OSStatus};
 use core_foundation_sys::date::CFDateRef;
+#[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use core_foundation_sys::dictionary::CFDictionaryRef;
+#[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use core_foundation_sys::string::CFStringRef;
 #[cfg(any(feature = "OSX_10_13", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
 use core_foundation_sys::error::CFErrorRef;
+#[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use std::os::raw::c_void;
 
 pub type SecTrustResultType = u32;
 
@@ -36,7 +44,26 @@ pub enum __SecTrust {}
 
 pub type SecTrustRef = *mut __SecTrust;
 
+// An Objective-C block of type `void (^)(SecTrustRef trust, bool result, CFErrorRef error)`.
+#[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+pub type SecTrustWithErrorCallback = *const c_void;
+
 extern "C" {
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub static kSecTrustEvaluationDate: CFStringRef;
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub static kSecTrustExtendedValidation: CFStringRef;
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub static kSecTrustOrganizationName: CFStringRef;
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub static kSecTrustResultValue: CFStringRef;
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub static kSecTrustRevocationChecked: CFStringRef;
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub static kSecTrustRevocationValidUntilDate: CFStringRef;
+    #[cfg(any(feature = "OSX_10_11", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub static kSecTrustCertificateTransparency: CFStringRef;
+
     pub fn SecTrustGetTypeID() -> CFTypeID;
     pub fn SecTrustGetCertificateCount(trust: SecTrustRef) -> CFIndex;
     #[deprecated(note = "deprecated by Apple")]
@@ -57,6 +84,12 @@ extern "C" {
     // it should have been OSX_10_14, but due to back-compat it can't rely on the newer feature flag
     #[cfg(any(feature = "OSX_10_13", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
     pub fn SecTrustEvaluateWithError(trust: SecTrustRef, error: *mut CFErrorRef) -> bool;
+    #[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub fn SecTrustEvaluateAsyncWithError(
+        trust: SecTrustRef,
+        queue: dispatch_queue_t,
+        result: SecTrustWithErrorCallback,
+    ) -> OSStatus;
     pub fn SecTrustCreateWithCertificates(
         certificates: CFTypeRef,
         policies: CFTypeRef,
@@ -77,4 +110,6 @@ extern "C" {
         sctArray: CFArrayRef,
     ) -> OSStatus;
     pub fn SecTrustCopyPublicKey(trust: SecTrustRef) -> SecKeyRef;
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub fn SecTrustCopyResult(trust: SecTrustRef) -> CFDictionaryRef;
 }
@@ -17,6 +17,16 @@ pub const kSecTrustSettingsResultTrustAsRoot: SecTrustSettingsResult = 2;
 pub const kSecTrustSettingsResultDeny: SecTrustSettingsResult = 3;
 pub const kSecTrustSettingsResultUnspecified: SecTrustSettingsResult = 4;
 
+pub type SecTrustSettingsKeyUsage = u32;
+
+pub const kSecTrustSettingsKeyUseSignature: SecTrustSettingsKeyUsage = 0x0000_0001;
+pub const kSecTrustSettingsKeyUseEnDecryptData: SecTrustSettingsKeyUsage = 0x0000_0002;
+pub const kSecTrustSettingsKeyUseEnDecryptKey: SecTrustSettingsKeyUsage = 0x0000_0004;
+pub const kSecTrustSettingsKeyUseSignCert: SecTrustSettingsKeyUsage = 0x0000_0008;
+pub const kSecTrustSettingsKeyUseSignRevocation: SecTrustSettingsKeyUsage = 0x0000_0010;
+pub const kSecTrustSettingsKeyUseKeyExchange: SecTrustSettingsKeyUsage = 0x0000_0020;
+pub const kSecTrustSettingsKeyUseAny: SecTrustSettingsKeyUsage = 0xffff_ffff;
+
 extern "C" {
     pub fn SecTrustSettingsCopyCertificates(
         domain: SecTrustSettingsDomain,
@@ -32,4 +42,8 @@ extern "C" {
         domain: SecTrustSettingsDomain,
         trustSettingsDictOrArray: CFTypeRef,
     ) -> OSStatus;
+    pub fn SecTrustSettingsRemoveTrustSettings(
+        certificateRef: SecCertificateRef,
+        domain: SecTrustSettingsDomain,
+    ) -> OSStatus;
 }
@@ -0,0 +1,171 @@
+//! Measures `SslStream` write throughput over an in-memory pipe.
+//!
+//! Run with `cargo bench --bench write`.
+
+#[cfg(target_os = "macos")]
+mod bench {
+    use security_framework::certificate::SecCertificate;
+    use security_framework::identity::SecIdentity;
+    use security_framework::item::{ItemClass, ItemSearchOptions, Reference, SearchResult};
+    use security_framework::os::macos::item::ItemSearchOptionsExt;
+    use security_framework::os::macos::keychain::SecKeychain;
+    use security_framework::secure_transport::{ClientBuilder, ServerBuilder, SslStream};
+    use std::collections::VecDeque;
+    use std::io::{self, IoSlice, Read, Write};
+    use std::path::Path;
+    use std::sync::{Arc, Condvar, Mutex};
+    use std::thread;
+    use std::time::Instant;
+
+    const TOTAL: usize = 64 << 20;
+
+    #[derive(Debug, Default)]
+    struct Channel {
+        data: Mutex<(VecDeque<u8>, bool)>,
+        ready: Condvar,
+    }
+
+    /// One end of a blocking in-memory duplex pipe.
+    #[derive(Debug)]
+    struct Pipe {
+        incoming: Arc<Channel>,
+        outgoing: Arc<Channel>,
+    }
+
+    fn pipe() -> (Pipe, Pipe) {
+        let a = Arc::new(Channel::default());
+        let b = Arc::new(Channel::default());
+        (
+            Pipe { incoming: a.clone(), outgoing: b.clone() },
+            Pipe { incoming: b, outgoing: a },
+        )
+    }
+
+    impl Read for Pipe {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let mut data = self.incoming.data.lock().unwrap();
+            while data.0.is_empty() && !data.1 {
+                data = self.incoming.ready.wait(data).unwrap();
+            }
+            let len = buf.len().min(data.0.len());
+            for (dst, src) in buf.iter_mut().zip(data.0.drain(..len)) {
+                *dst = src;
+            }
+            Ok(len)
+        }
+    }
+
+    impl Write for Pipe {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.outgoing.data.lock().unwrap().0.extend(buf);
+            self.outgoing.ready.notify_one();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Drop for Pipe {
+        fn drop(&mut self) {
+            self.outgoing.data.lock().unwrap().1 = true;
+            self.outgoing.ready.notify_one();
+        }
+    }
+
+    fn identity(dir: &Path) -> SecIdentity {
+        let path = dir.join("server.keychain");
+        std::fs::write(&path, include_bytes!("../test/server.keychain")).unwrap();
+        let mut keychain = SecKeychain::open(&path).unwrap();
+        keychain.unlock(Some("password123")).unwrap();
+        let mut items = ItemSearchOptions::new()
+            .class(ItemClass::identity())
+            .keychains(&[keychain])
+            .search()
+            .unwrap();
+        match items.pop() {
+            Some(SearchResult::Ref(Reference::Identity(identity))) => identity,
+            _ => panic!("expected identity"),
+        }
+    }
+
+    fn connect(identity: &SecIdentity) -> (SslStream<Pipe>, thread::JoinHandle<usize>) {
+        let (client, server) = pipe();
+        let builder = ServerBuilder::new(identity, &[]);
+        let handle = thread::spawn(move || {
+            let mut stream = builder.handshake(server).unwrap();
+            let mut buf = vec![0; 1 << 16];
+            let mut total = 0;
+            loop {
+                match stream.read(&mut buf).unwrap() {
+                    0 => return total,
+                    n => total += n,
+                }
+            }
+        });
+        let certificate = SecCertificate::from_der(include_bytes!("../test/server.der")).unwrap();
+        let stream = ClientBuilder::new()
+            .anchor_certificates(&[certificate])
+            .danger_accept_invalid_certs(true)
+            .handshake("foobar.com", client)
+            .unwrap();
+        (stream, handle)
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn run(name: &str, identity: &SecIdentity, write: impl Fn(&mut SslStream<Pipe>)) {
+        let (mut stream, handle) = connect(identity);
+        let start = Instant::now();
+        write(&mut stream);
+        stream.close().unwrap();
+        drop(stream);
+        assert_eq!(TOTAL, handle.join().unwrap());
+        let secs = start.elapsed().as_secs_f64();
+        println!("{name:<32} {:>8.1} MB/s", TOTAL as f64 / secs / 1e6);
+    }
+
+    pub fn main() {
+        let dir = tempfile::tempdir().unwrap();
+        let identity = identity(dir.path());
+        let chunk = [0x55; 16384];
+
+        for size in [64, 1024, 16384] {
+            run(&format!("write {size}"), &identity, |stream| {
+                for _ in 0..TOTAL / size {
+                    stream.write_all(&chunk[..size]).unwrap();
+                }
+            });
+        }
+        for size in [64, 1024] {
+            run(&format!("buffered write {size}"), &identity, |stream| {
+                stream.set_write_buffer_size(16384).unwrap();
+                for _ in 0..TOTAL / size {
+                    stream.write_all(&chunk[..size]).unwrap();
+                }
+                stream.flush().unwrap();
+            });
+        }
+        run("write_vectored 16 x 64", &identity, |stream| {
+            let bufs = [IoSlice::new(&chunk[..64]); 16];
+            for _ in 0..TOTAL / 1024 {
+                // the pipe never blocks, so all buffers fit in one record
+                assert_eq!(1024, stream.write_vectored(&bufs).unwrap());
+            }
+        });
+        run("write 16384, 1024 byte records", &identity, |stream| {
+            stream.set_max_record_size(1024).unwrap();
+            for _ in 0..TOTAL / 16384 {
+                stream.write_all(&chunk).unwrap();
+            }
+        });
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn main() {
+    bench::main();
+}
+
+#[cfg(not(target_os = "macos"))]
+fn main() {}
@@ -0,0 +1,239 @@
+//! Ordering of certificate chains received out of order.
+//!
+//! Peers often send their chain in the wrong order, with duplicates, or with
+//! certificates that have nothing to do with their own. `CertificateChain`
+//! rebuilds the path from the leaf certificate upwards by matching issuer and
+//! subject names and, where present, authority and subject key identifiers,
+//! so the result can be handed to `SecTrust::create_with_certificates`.
+//!
+//! Only the DER encoding is inspected; signatures are not verified. That is
+//! left to trust evaluation.
+
+use security_framework_sys::base::errSecDecode;
+
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use crate::certificate::SecCertificate;
+use crate::der::Certificate;
+use crate::{Error, Result};
+
+/// A certificate chain ordered from the leaf towards the root.
+#[derive(Debug, Clone)]
+pub struct CertificateChain<T> {
+    certificates: Vec<T>,
+    unused: Vec<T>,
+    missing_issuer: Option<Vec<u8>>,
+}
+
+impl<T: AsRef<[u8]>> CertificateChain<T> {
+    /// Orders a set of DER encoded certificates.
+    ///
+    /// The leaf is taken to be the certificate that issued none of the others
+    /// and that heads the longest path; ties go to the earliest certificate.
+    pub fn new(certificates: Vec<T>) -> Result<Self> {
+        Self::build(None, certificates, |cert| cert.as_ref().to_vec())
+    }
+
+    /// Orders a set of DER encoded certificates, starting from a known leaf.
+    pub fn with_leaf(leaf: T, certificates: Vec<T>) -> Result<Self> {
+        Self::build(Some(leaf), certificates, |cert| cert.as_ref().to_vec())
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+impl CertificateChain<SecCertificate> {
+    /// Orders a set of certificates.
+    ///
+    /// The leaf is chosen as in `CertificateChain::new`.
+    pub fn from_certificates(certificates: Vec<SecCertificate>) -> Result<Self> {
+        Self::build(None, certificates, SecCertificate::to_der)
+    }
+
+    /// Orders a set of certificates, starting from a known leaf.
+    pub fn from_certificates_with_leaf(leaf: SecCertificate, certificates: Vec<SecCertificate>) -> Result<Self> {
+        Self::build(Some(leaf), certificates, SecCertificate::to_der)
+    }
+}
+
+impl<T> CertificateChain<T> {
+    fn build<F>(leaf: Option<T>, certificates: Vec<T>, to_der: F) -> Result<Self>
+    where
+        F: Fn(&T) -> Vec<u8>,
+    {
+        let has_leaf = leaf.is_some();
+        let mut items = leaf.into_iter().chain(certificates).map(Some).collect::<Vec<_>>();
+        let ders = items.iter().flatten().map(&to_der).collect::<Vec<_>>();
+        let parsed = ders
+            .iter()
+            .map(|der| Certificate::parse(der))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| Error::from_code(errSecDecode))?;
+
+        // identical encodings are only considered once
+        let distinct = (0..ders.len())
+            .filter(|&i| !ders[..i].contains(&ders[i]))
+            .collect::<Vec<_>>();
+
+        let path = if has_leaf {
+            path_from(0, &parsed, &distinct)
+        } else {
+            distinct
+                .iter()
+                .filter(|&&i| !distinct.iter().any(|&j| j != i && parsed[j].is_issued_by(&parsed[i])))
+                .map(|&i| path_from(i, &parsed, &distinct))
+                .fold(None, |best: Option<Vec<usize>>, path| match best {
+                    Some(best) if best.len() >= path.len() => Some(best),
+                    _ => Some(path),
+                })
+                .or_else(|| distinct.first().map(|&i| path_from(i, &parsed, &distinct)))
+                .unwrap_or_default()
+        };
+
+        let missing_issuer = path
+            .last()
+            .map(|&last| &parsed[last])
+            .filter(|cert| !cert.is_self_issued())
+            .map(|cert| cert.issuer.to_vec());
+
+        let unused = distinct.iter().filter(|i| !path.contains(i)).copied().collect::<Vec<_>>();
+
+        Ok(Self {
+            certificates: path.iter().filter_map(|&i| items[i].take()).collect(),
+            unused: unused.iter().filter_map(|&i| items[i].take()).collect(),
+            missing_issuer,
+        })
+    }
+
+    /// Returns the ordered chain, leaf first.
+    #[inline(always)]
+    #[must_use]
+    pub fn certificates(&self) -> &[T] {
+        &self.certificates
+    }
+
+    /// Consumes the chain, returning the ordered certificates, leaf first.
+    #[inline(always)]
+    #[must_use]
+    pub fn into_certificates(self) -> Vec<T> {
+        self.certificates
+    }
+
+    /// Returns the certificates that are not part of the chain.
+    ///
+    /// Duplicates of certificates in the chain are dropped and not reported here.
+    #[inline(always)]
+    #[must_use]
+    pub fn unused(&self) -> &[T] {
+        &self.unused
+    }
+
+    /// Returns the DER encoded issuer name of the last certificate if the
+    /// chain does not end in a self-issued certificate.
+    ///
+    /// This is expected when the peer leaves out its root, which the trust
+    /// store is then supposed to provide.
+    #[inline(always)]
+    #[must_use]
+    pub fn missing_issuer(&self) -> Option<&[u8]> {
+        self.missing_issuer.as_deref()
+    }
+
+    /// Returns `true` if the chain ends in a self-issued certificate.
+    #[inline(always)]
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.missing_issuer.is_none()
+    }
+}
+
+/// Follows issuers from `leaf` until a self-issued certificate or a gap is reached.
+fn path_from(leaf: usize, parsed: &[Certificate<'_>], candidates: &[usize]) -> Vec<usize> {
+    let mut path = vec![leaf];
+    loop {
+        let current = &parsed[path[path.len() - 1]];
+        if current.is_self_issued() {
+            break;
+        }
+        let mut issuers = candidates
+            .iter()
+            .copied()
+            .filter(|i| !path.contains(i) && current.is_issued_by(&parsed[*i]));
+        let first = issuers.next();
+        // a match on key identifiers beats one on names alone
+        let issuer = if current.authority_key_identifier.is_some() {
+            first
+                .into_iter()
+                .chain(issuers)
+                .find(|&i| parsed[i].subject_key_identifier.is_some())
+                .or(first)
+        } else {
+            first
+        };
+        match issuer {
+            Some(issuer) => path.push(issuer),
+            None => break,
+        }
+    }
+    path
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::der::Reader;
+
+    const LEAF: &[u8] = include_bytes!("../test/chain/leaf.der");
+    const INTERMEDIATE: &[u8] = include_bytes!("../test/chain/intermediate.der");
+    const ROOT: &[u8] = include_bytes!("../test/chain/root.der");
+    const OTHER_ROOT: &[u8] = include_bytes!("../test/chain/root2.der");
+    const UNRELATED: &[u8] = include_bytes!("../test/chain/other.der");
+
+    #[test]
+    fn reorders() {
+        let chain = CertificateChain::new(vec![ROOT, UNRELATED, LEAF, INTERMEDIATE, LEAF]).unwrap();
+        assert_eq!(&[LEAF, INTERMEDIATE, ROOT][..], chain.certificates());
+        assert_eq!(&[UNRELATED][..], chain.unused());
+        assert!(chain.is_complete());
+    }
+
+    #[test]
+    fn matches_key_identifiers() {
+        let chain = CertificateChain::new(vec![OTHER_ROOT, INTERMEDIATE, LEAF, ROOT]).unwrap();
+        assert_eq!(&[LEAF, INTERMEDIATE, ROOT][..], chain.certificates());
+        assert_eq!(&[OTHER_ROOT][..], chain.unused());
+    }
+
+    #[test]
+    fn reports_gap() {
+        let chain = CertificateChain::with_leaf(LEAF, vec![ROOT]).unwrap();
+        assert_eq!(&[LEAF][..], chain.certificates());
+        assert_eq!(&[ROOT][..], chain.unused());
+        assert!(!chain.is_complete());
+
+        let intermediate = Certificate::parse(INTERMEDIATE).unwrap();
+        assert_eq!(Some(intermediate.subject), chain.missing_issuer());
+
+        let chain = CertificateChain::new(vec![INTERMEDIATE, LEAF]).unwrap();
+        assert_eq!(&[LEAF, INTERMEDIATE][..], chain.certificates());
+        let root = Certificate::parse(ROOT).unwrap();
+        assert_eq!(Some(root.subject), chain.missing_issuer());
+        assert!(Reader::new(chain.missing_issuer().unwrap()).read_sequence().is_some());
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert!(CertificateChain::new(vec![LEAF, &b"garbage"[..]]).is_err());
+        assert!(CertificateChain::<&[u8]>::new(vec![]).unwrap().certificates().is_empty());
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    #[test]
+    fn from_certificates() {
+        let certs = [INTERMEDIATE, LEAF, ROOT]
+            .iter()
+            .map(|der| SecCertificate::from_der(der).unwrap())
+            .collect();
+        let chain = CertificateChain::from_certificates(certs).unwrap();
+        let ders = chain.certificates().iter().map(SecCertificate::to_der).collect::<Vec<_>>();
+        assert_eq!(vec![LEAF.to_vec(), INTERMEDIATE.to_vec(), ROOT.to_vec()], ders);
+    }
+}
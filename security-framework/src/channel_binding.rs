@@ -0,0 +1,217 @@
+//! TLS channel bindings, as defined by RFC 5929.
+//!
+//! Channel bindings tie an authentication exchange run over TLS, such as
+//! SCRAM or Kerberos, to the TLS connection it is run over.
+
+use security_framework_sys::base::{errSecDecode, errSecUnimplemented};
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use security_framework_sys::common_digest::CC_LONG;
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use std::mem::MaybeUninit;
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use std::os::raw::{c_int, c_uchar, c_void};
+
+use crate::der::{self, Certificate, Reader};
+use crate::{Error, Result};
+
+/// The type of a channel binding.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ChannelBindingType {
+    /// `tls-server-end-point`, a hash of the server's certificate.
+    TlsServerEndPoint,
+    /// `tls-unique`, the first Finished message of the handshake.
+    TlsUnique,
+}
+
+impl ChannelBindingType {
+    /// The name of the binding type, as used by SCRAM and GSS-API.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::TlsServerEndPoint => "tls-server-end-point",
+            Self::TlsUnique => "tls-unique",
+        }
+    }
+}
+
+/// The hash function used for a `tls-server-end-point` binding.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum EndPointHash {
+    /// SHA-224.
+    Sha224,
+    /// SHA-256, also used for certificates signed with MD5 or SHA-1.
+    Sha256,
+    /// SHA-384.
+    Sha384,
+    /// SHA-512.
+    Sha512,
+}
+
+const OID_RSA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01];
+const OID_ECDSA: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04];
+const OID_DSA_WITH_SHA1: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x38, 0x04, 0x03];
+const OID_NIST_SIGNATURE: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x03];
+const OID_NIST_HASH: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02];
+const OID_SHA1: &[u8] = &[0x2b, 0x0e, 0x03, 0x02, 0x1a];
+
+impl EndPointHash {
+    /// Selects the hash function for a DER encoded certificate.
+    ///
+    /// This is the hash function of the certificate's signature algorithm,
+    /// with MD5 and SHA-1 upgraded to SHA-256. Signature algorithms without a
+    /// single hash function, such as Ed25519, are not covered by RFC 5929
+    /// and return `errSecUnimplemented`.
+    pub fn for_certificate(der: &[u8]) -> Result<Self> {
+        let cert = Certificate::parse(der).ok_or_else(|| Error::from_code(errSecDecode))?;
+        Self::for_signature_algorithm(cert.signature_algorithm, cert.signature_parameters)
+            .ok_or_else(|| Error::from_code(errSecUnimplemented))
+    }
+
+    fn for_signature_algorithm(oid: &[u8], parameters: Option<&[u8]>) -> Option<Self> {
+        if let Some(rest) = oid.strip_prefix(OID_RSA) {
+            return match rest {
+                // md5WithRSAEncryption, sha1WithRSAEncryption, sha256WithRSAEncryption
+                [4 | 5 | 11] => Some(Self::Sha256),
+                // RSASSA-PSS
+                [10] => Self::for_pss(parameters?),
+                [12] => Some(Self::Sha384),
+                [13] => Some(Self::Sha512),
+                [14] => Some(Self::Sha224),
+                _ => None,
+            };
+        }
+        if let Some(rest) = oid.strip_prefix(OID_ECDSA) {
+            return match rest {
+                // ecdsa-with-SHA1, ecdsa-with-SHA256
+                [1] | [3, 2] => Some(Self::Sha256),
+                [3, 1] => Some(Self::Sha224),
+                [3, 3] => Some(Self::Sha384),
+                [3, 4] => Some(Self::Sha512),
+                _ => None,
+            };
+        }
+        if oid == OID_DSA_WITH_SHA1 {
+            return Some(Self::Sha256);
+        }
+        match oid.strip_prefix(OID_NIST_SIGNATURE)? {
+            [1] => Some(Self::Sha224),
+            [2] => Some(Self::Sha256),
+            _ => None,
+        }
+    }
+
+    fn for_pss(parameters: &[u8]) -> Option<Self> {
+        let mut params = Reader::new(parameters).read_sequence()?;
+        let hash = match params.read_optional(der::explicit(0)) {
+            Some(hash) => Reader::new(hash.contents).read_sequence()?.read_contents(der::OID)?,
+            // the default is SHA-1
+            None => return Some(Self::Sha256),
+        };
+        if hash == OID_SHA1 {
+            return Some(Self::Sha256);
+        }
+        match hash.strip_prefix(OID_NIST_HASH)? {
+            [1] => Some(Self::Sha256),
+            [2] => Some(Self::Sha384),
+            [3] => Some(Self::Sha512),
+            [4] => Some(Self::Sha224),
+            _ => None,
+        }
+    }
+
+    /// Hashes `data`.
+    #[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    #[must_use]
+    pub fn digest(self, data: &[u8]) -> Vec<u8> {
+        use security_framework_sys::common_digest::*;
+
+        match self {
+            Self::Sha224 => hash(data, CC_SHA224_DIGEST_LENGTH, CC_SHA224_Init, CC_SHA224_Update, CC_SHA224_Final),
+            Self::Sha256 => hash(data, CC_SHA256_DIGEST_LENGTH, CC_SHA256_Init, CC_SHA256_Update, CC_SHA256_Final),
+            Self::Sha384 => hash(data, CC_SHA384_DIGEST_LENGTH, CC_SHA384_Init, CC_SHA384_Update, CC_SHA384_Final),
+            Self::Sha512 => hash(data, CC_SHA512_DIGEST_LENGTH, CC_SHA512_Init, CC_SHA512_Update, CC_SHA512_Final),
+        }
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+fn hash<C>(
+    data: &[u8],
+    len: usize,
+    init: unsafe extern "C" fn(*mut C) -> c_int,
+    update: unsafe extern "C" fn(*mut C, *const c_void, CC_LONG) -> c_int,
+    finish: unsafe extern "C" fn(*mut c_uchar, *mut C) -> c_int,
+) -> Vec<u8> {
+    let mut ctx = MaybeUninit::<C>::uninit();
+    let mut md = vec![0; len];
+    unsafe {
+        init(ctx.as_mut_ptr());
+        // lengths are 32 bits, so larger inputs are fed in pieces
+        for chunk in data.chunks(CC_LONG::MAX as usize) {
+            update(ctx.as_mut_ptr(), chunk.as_ptr().cast(), chunk.len() as CC_LONG);
+        }
+        finish(md.as_mut_ptr(), ctx.as_mut_ptr());
+    }
+    md
+}
+
+/// Computes the `tls-server-end-point` binding for the server's DER encoded
+/// certificate.
+///
+/// Servers pass their own certificate; clients the certificate the server
+/// presented.
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+pub fn tls_server_end_point(certificate: &[u8]) -> Result<Vec<u8>> {
+    Ok(EndPointHash::for_certificate(certificate)?.digest(certificate))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn selects_end_point_hash() {
+        // sha1WithRSAEncryption
+        let server = include_bytes!("../test/server.der");
+        assert_eq!(EndPointHash::Sha256, EndPointHash::for_certificate(server).unwrap());
+        // ecdsa-with-SHA256
+        let leaf = include_bytes!("../test/chain/leaf.der");
+        assert_eq!(EndPointHash::Sha256, EndPointHash::for_certificate(leaf).unwrap());
+        assert!(EndPointHash::for_certificate(&server[1..]).is_err());
+
+        let select = EndPointHash::for_signature_algorithm;
+        let rsa = |n| [OID_RSA, &[n]].concat();
+        assert_eq!(Some(EndPointHash::Sha256), select(&rsa(4), None));
+        assert_eq!(Some(EndPointHash::Sha384), select(&rsa(12), None));
+        assert_eq!(Some(EndPointHash::Sha512), select(&rsa(13), None));
+        assert_eq!(Some(EndPointHash::Sha224), select(&rsa(14), None));
+        assert_eq!(Some(EndPointHash::Sha384), select(&[OID_ECDSA, &[3, 3]].concat(), None));
+        assert_eq!(Some(EndPointHash::Sha256), select(OID_DSA_WITH_SHA1, None));
+        // Ed25519
+        assert_eq!(None, select(&[0x2b, 0x65, 0x70], None));
+    }
+
+    #[test]
+    fn selects_pss_hash() {
+        let select = EndPointHash::for_signature_algorithm;
+        let pss = [OID_RSA, &[10]].concat();
+        // hashAlgorithm [0] sha384
+        let sha384 = [
+            0x30, 0x11, 0xa0, 0x0f, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+            0x02, 0x05, 0x00,
+        ];
+        assert_eq!(Some(EndPointHash::Sha384), select(&pss, Some(&sha384)));
+        assert_eq!(Some(EndPointHash::Sha256), select(&pss, Some(&[0x30, 0x00])));
+        assert_eq!(None, select(&pss, None));
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    #[test]
+    fn server_end_point() {
+        let binding = tls_server_end_point(include_bytes!("../test/server.der")).unwrap();
+        assert_eq!(
+            "af9dd180a326ae08b37e6398f9262f8b9d4c55674a233a7c84975024f873655d",
+            hex::encode(binding)
+        );
+    }
+}
@@ -0,0 +1,371 @@
+//! OpenSSL style cipher strings.
+//!
+//! A cipher string such as `ECDHE+AESGCM:!aNULL:!MD5` is a list of rules,
+//! separated by `:`, `,`, `;` or spaces, that build an ordered list of cipher
+//! suites from the suites available:
+//!
+//! * `X` appends the suites matching `X` that are not yet listed.
+//! * `+X` moves the listed suites matching `X` to the end of the list.
+//! * `-X` removes the suites matching `X`; later rules may add them again.
+//! * `!X` removes the suites matching `X` for good.
+//! * `@STRENGTH` sorts the list by cipher key length, longest first.
+//! * `@SECLEVEL=n` drops suites below OpenSSL security level `n` once all
+//!   rules have been applied.
+//!
+//! `X` is a cipher suite name, in OpenSSL or IANA form, or one of OpenSSL's
+//! aliases, such as `HIGH`, `kECDHE`, `aRSA`, `AESGCM`, `SHA256` or
+//! `TLSv1.2`, and several of them may be joined with `+` to match the suites
+//! matching all of them. As in OpenSSL, unknown names match nothing, and TLS
+//! 1.3 suites, which are configured separately, are never selected.
+
+use std::cmp::Reverse;
+use std::str::FromStr;
+
+use security_framework_sys::base::errSecParam;
+
+use crate::cipher_suite::{Authentication, BulkCipher, CipherSuite, CipherSuiteInfo, KeyExchange, Mac, TlsVersion};
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use crate::secure_transport::SslContext;
+use crate::{Error, Result};
+
+/// A parsed cipher string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CipherString {
+    rules: Vec<Rule>,
+    security_level: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Rule {
+    Select(Op, Vec<String>),
+    Strength,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Op {
+    Add,
+    Move,
+    Remove,
+    Kill,
+}
+
+impl FromStr for CipherString {
+    type Err = Error;
+
+    /// Parses a cipher string, returning `errSecParam` for malformed rules.
+    fn from_str(s: &str) -> Result<Self> {
+        let mut rules = Vec::new();
+        let mut security_level = 0;
+        for element in s.split([':', ',', ';', ' ']).filter(|e| !e.is_empty()) {
+            let mut commands = element.split('@');
+            let selection = commands.next().unwrap_or_default();
+            if !selection.is_empty() {
+                rules.push(parse_selection(selection)?);
+            }
+            for command in commands {
+                if command == "STRENGTH" {
+                    rules.push(Rule::Strength);
+                } else if let Some(level) = command.strip_prefix("SECLEVEL=") {
+                    security_level = match level.parse() {
+                        Ok(level @ 0..=5) => level,
+                        _ => return Err(Error::from_code(errSecParam)),
+                    };
+                } else {
+                    return Err(Error::from_code(errSecParam));
+                }
+            }
+        }
+        Ok(Self { rules, security_level })
+    }
+}
+
+fn parse_selection(selection: &str) -> Result<Rule> {
+    let (op, terms) = match selection.as_bytes()[0] {
+        b'+' => (Op::Move, &selection[1..]),
+        b'-' => (Op::Remove, &selection[1..]),
+        b'!' => (Op::Kill, &selection[1..]),
+        _ => (Op::Add, selection),
+    };
+    let terms = terms.split('+').map(str::to_owned).collect::<Vec<_>>();
+    if terms.iter().any(String::is_empty) {
+        return Err(Error::from_code(errSecParam));
+    }
+    Ok(Rule::Select(op, terms))
+}
+
+impl CipherString {
+    /// Evaluates the cipher string against the available suites, such as
+    /// those returned by `SslContext::supported_ciphers`.
+    ///
+    /// Suites added by the same rule keep their order in `available`. The
+    /// result can be passed to `SslContext::set_enabled_ciphers` or
+    /// `ClientBuilder::whitelist_ciphers`.
+    #[must_use]
+    pub fn select(&self, available: &[CipherSuite]) -> Vec<CipherSuite> {
+        let mut candidates = Vec::<(CipherSuite, &CipherSuiteInfo)>::new();
+        for &suite in available {
+            match suite.info() {
+                Some(info) if info.min_version < TlsVersion::Tls13 && !candidates.iter().any(|&(s, _)| s == suite) => {
+                    candidates.push((suite, info));
+                }
+                _ => {}
+            }
+        }
+
+        let mut list = Vec::<(CipherSuite, &CipherSuiteInfo)>::new();
+        let mut killed = Vec::new();
+        for rule in &self.rules {
+            let (op, terms) = match rule {
+                Rule::Select(op, terms) => (*op, terms),
+                Rule::Strength => {
+                    list.sort_by_key(|(_, info)| Reverse(strength_bits(info.cipher)));
+                    continue;
+                }
+            };
+            let selected = |info: &CipherSuiteInfo| terms.iter().all(|term| matches(term, info));
+            match op {
+                Op::Add => {
+                    for &(suite, info) in &candidates {
+                        if selected(info) && !killed.contains(&suite) && !list.iter().any(|&(s, _)| s == suite) {
+                            list.push((suite, info));
+                        }
+                    }
+                }
+                Op::Move => {
+                    let (moved, kept): (Vec<_>, Vec<_>) = list.iter().partition(|(_, info)| selected(info));
+                    list = kept;
+                    list.extend(moved);
+                }
+                Op::Remove => list.retain(|(_, info)| !selected(info)),
+                Op::Kill => {
+                    list.retain(|(_, info)| !selected(info));
+                    killed.extend(candidates.iter().filter(|(_, info)| selected(info)).map(|&(s, _)| s));
+                }
+            }
+        }
+
+        list.into_iter()
+            .filter(|(_, info)| meets_security_level(info, self.security_level))
+            .map(|(suite, _)| suite)
+            .collect()
+    }
+
+    /// Enables the suites the cipher string selects from those supported by
+    /// the context.
+    ///
+    /// Returns `errSecParam` if no suite is selected.
+    #[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub fn configure(&self, ctx: &mut SslContext) -> Result<()> {
+        let ciphers = self.select(&ctx.supported_ciphers()?);
+        if ciphers.is_empty() {
+            return Err(Error::from_code(errSecParam));
+        }
+        ctx.set_enabled_ciphers(&ciphers)
+    }
+}
+
+fn matches(term: &str, info: &CipherSuiteInfo) -> bool {
+    use Authentication as A;
+    use BulkCipher as C;
+    use KeyExchange as K;
+
+    let kx = info.key_exchange;
+    let auth = info.authentication;
+    match term {
+        "ALL" => info.cipher != C::Null,
+        "DEFAULT" => info.cipher != C::Null && !matches!(auth, A::Null | A::Anonymous) && meets_security_level(info, 1),
+        "COMPLEMENTOFALL" | "eNULL" | "NULL" => info.cipher == C::Null,
+        "COMPLEMENTOFDEFAULT" => matches!(auth, A::Null | A::Anonymous) || !meets_security_level(info, 1),
+        "HIGH" => strength_bits(info.cipher) >= 128 && !matches!(info.cipher, C::Rc4 | C::Idea),
+        "MEDIUM" => matches!(info.cipher, C::TripleDes | C::Rc4 | C::Idea),
+        "LOW" | "DES" => info.cipher == C::Des,
+        "EXP" | "EXPORT" => matches!(info.cipher, C::Rc2Export | C::Rc4Export | C::DesExport),
+
+        "kRSA" | "RSA" => kx == K::Rsa && auth != A::Psk,
+        "kDHE" | "kEDH" => kx == K::Dhe && auth != A::Psk,
+        "DHE" | "EDH" => kx == K::Dhe && matches!(auth, A::Rsa | A::Dss),
+        "ADH" => kx == K::Dhe && auth == A::Anonymous,
+        "kDH" => kx == K::Dh,
+        "kDHr" => kx == K::Dh && auth == A::Rsa,
+        "kDHd" => kx == K::Dh && auth == A::Dss,
+        "kECDHE" | "kEECDH" => kx == K::Ecdhe && auth != A::Psk,
+        "ECDHE" | "EECDH" => kx == K::Ecdhe && matches!(auth, A::Rsa | A::Ecdsa),
+        "AECDH" => kx == K::Ecdhe && auth == A::Anonymous,
+        "ECDH" => matches!(kx, K::Ecdh | K::Ecdhe) && auth != A::Psk,
+        "kECDH" => kx == K::Ecdh,
+        "kECDHr" => kx == K::Ecdh && auth == A::Rsa,
+        "kECDHe" => kx == K::Ecdh && auth == A::Ecdsa,
+        "kPSK" => kx == K::Psk,
+        "kDHEPSK" => kx == K::Dhe && auth == A::Psk,
+        "kECDHEPSK" => kx == K::Ecdhe && auth == A::Psk,
+        "kRSAPSK" => kx == K::Rsa && auth == A::Psk,
+
+        "aNULL" => matches!(auth, A::Null | A::Anonymous),
+        "aRSA" => auth == A::Rsa,
+        "aDSS" | "DSS" => auth == A::Dss,
+        "aECDSA" | "ECDSA" => auth == A::Ecdsa,
+        "aPSK" | "PSK" => auth == A::Psk,
+
+        "AES" => matches!(
+            info.cipher,
+            C::Aes128Cbc | C::Aes256Cbc | C::Aes128Gcm | C::Aes256Gcm | C::Aes128Ccm | C::Aes128Ccm8
+        ),
+        "AES128" => matches!(info.cipher, C::Aes128Cbc | C::Aes128Gcm | C::Aes128Ccm | C::Aes128Ccm8),
+        "AES256" => matches!(info.cipher, C::Aes256Cbc | C::Aes256Gcm),
+        "AESGCM" => matches!(info.cipher, C::Aes128Gcm | C::Aes256Gcm),
+        "AESCCM" => matches!(info.cipher, C::Aes128Ccm | C::Aes128Ccm8),
+        "AESCCM8" => info.cipher == C::Aes128Ccm8,
+        "CHACHA20" => info.cipher == C::ChaCha20Poly1305,
+        "3DES" => info.cipher == C::TripleDes,
+        "RC4" => matches!(info.cipher, C::Rc4 | C::Rc4Export),
+        "RC2" => info.cipher == C::Rc2Export,
+        "IDEA" => info.cipher == C::Idea,
+
+        "MD5" => info.mac == Mac::Md5,
+        "SHA1" | "SHA" => info.mac == Mac::Sha1,
+        "SHA256" => info.mac == Mac::Sha256,
+        "SHA384" => info.mac == Mac::Sha384,
+
+        "SSLv3" => info.min_version == TlsVersion::Ssl3,
+        "TLSv1" | "TLSv1.0" => info.min_version == TlsVersion::Tls1,
+        "TLSv1.2" => info.min_version == TlsVersion::Tls12,
+
+        name => info.openssl_name == Some(name) || info.iana_name == name,
+    }
+}
+
+fn strength_bits(cipher: BulkCipher) -> u16 {
+    match cipher {
+        BulkCipher::Null => 0,
+        BulkCipher::Rc2Export | BulkCipher::Rc4Export | BulkCipher::DesExport => 40,
+        BulkCipher::Des => 56,
+        BulkCipher::TripleDes => 112,
+        BulkCipher::Rc4
+        | BulkCipher::Idea
+        | BulkCipher::Aes128Cbc
+        | BulkCipher::Aes128Gcm
+        | BulkCipher::Aes128Ccm
+        | BulkCipher::Aes128Ccm8 => 128,
+        BulkCipher::Aes256Cbc | BulkCipher::Aes256Gcm | BulkCipher::ChaCha20Poly1305 => 256,
+    }
+}
+
+/// Applies the cipher suite restrictions of OpenSSL's security levels.
+fn meets_security_level(info: &CipherSuiteInfo, level: u8) -> bool {
+    let min_bits = match level {
+        0 => return true,
+        1 => 80,
+        2 => 112,
+        3 => 128,
+        4 => 192,
+        _ => 256,
+    };
+    strength_bits(info.cipher) >= min_bits
+        && info.mac != Mac::Md5
+        && (level < 2 || info.cipher != BulkCipher::Rc4)
+        && (level < 3 || info.forward_secrecy())
+        && (level < 4 || info.mac != Mac::Sha1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const AVAILABLE: &[CipherSuite] = &[
+        CipherSuite::TLS_AES_128_GCM_SHA256,
+        CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384,
+        CipherSuite::TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
+        CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256,
+        CipherSuite::TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256,
+        CipherSuite::TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256,
+        CipherSuite::TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA,
+        CipherSuite::TLS_DHE_RSA_WITH_AES_128_GCM_SHA256,
+        CipherSuite::TLS_RSA_WITH_AES_256_GCM_SHA384,
+        CipherSuite::TLS_RSA_WITH_AES_128_CBC_SHA,
+        CipherSuite::TLS_RSA_WITH_3DES_EDE_CBC_SHA,
+        CipherSuite::TLS_RSA_WITH_RC4_128_MD5,
+        CipherSuite::TLS_DH_anon_WITH_AES_128_CBC_SHA,
+        CipherSuite::TLS_ECDHE_RSA_WITH_NULL_SHA,
+        CipherSuite::TLS_EMPTY_RENEGOTIATION_INFO_SCSV,
+    ];
+
+    fn select(s: &str) -> Vec<&'static str> {
+        let cipher_string = s.parse::<CipherString>().unwrap();
+        cipher_string.select(AVAILABLE).iter().map(|suite| suite.openssl_name().unwrap()).collect()
+    }
+
+    #[test]
+    fn combines_aliases() {
+        assert_eq!(
+            vec![
+                "ECDHE-ECDSA-AES256-GCM-SHA384",
+                "ECDHE-RSA-AES256-GCM-SHA384",
+                "ECDHE-ECDSA-AES128-GCM-SHA256",
+                "ECDHE-RSA-AES128-GCM-SHA256",
+            ],
+            select("ECDHE+AESGCM:!aNULL:!MD5")
+        );
+        assert_eq!(vec!["ECDHE-RSA-AES128-SHA"], select("kECDHE+aRSA+AES128+SHA1"));
+        assert_eq!(vec!["ECDHE-RSA-AES128-GCM-SHA256"], select("TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256"));
+        assert_eq!(vec!["ADH-AES128-SHA"], select("aNULL+HIGH"));
+        assert_eq!(vec!["ECDHE-RSA-NULL-SHA"], select("eNULL"));
+        assert_eq!(vec!["RC4-MD5"], select("MEDIUM:!3DES"));
+        assert!(select("CAMELLIA:TLS_AES_128_GCM_SHA256:BOGUS+HIGH").is_empty());
+    }
+
+    #[test]
+    fn orders_suites() {
+        // adding keeps the position of listed suites, moving goes to the end
+        assert_eq!(
+            vec![
+                "ECDHE-ECDSA-AES256-GCM-SHA384",
+                "ECDHE-RSA-AES256-GCM-SHA384",
+                "ECDHE-ECDSA-AES128-GCM-SHA256",
+                "ECDHE-RSA-AES128-GCM-SHA256",
+                "AES256-GCM-SHA384",
+                "AES128-SHA",
+                "DES-CBC3-SHA",
+                "RC4-MD5",
+                "ECDHE-RSA-AES128-SHA",
+            ],
+            select("kRSA:ECDHE+AESGCM:+kRSA:ECDHE-RSA-AES128-SHA:kRSA")
+        );
+        assert_eq!(
+            vec!["AES256-GCM-SHA384", "AES128-SHA", "ECDHE-RSA-AES128-SHA", "DES-CBC3-SHA"],
+            select("kRSA+AES128,ECDHE-RSA-AES128-SHA;3DES AES256-GCM-SHA384@STRENGTH:!RC4")
+        );
+        assert_eq!(vec!["AES128-SHA", "ECDHE-RSA-AES128-SHA"], select("AES128-SHA:ECDHE-RSA-AES128-SHA"));
+    }
+
+    #[test]
+    fn removes_suites() {
+        assert_eq!(vec!["AES128-SHA", "AES256-GCM-SHA384"], select("kRSA+AES:-AES256:!3DES:kRSA:!RC4"));
+        assert_eq!(vec!["AES128-SHA"], select("kRSA+AES:!AES256:!3DES:kRSA:!RC4"));
+        assert!(select("kRSA:-kRSA").is_empty());
+    }
+
+    #[test]
+    fn default_and_security_level() {
+        let default = select("DEFAULT");
+        assert_eq!(10, default.len());
+        assert!(!default.contains(&"ADH-AES128-SHA"));
+        assert!(!default.contains(&"RC4-MD5"));
+        assert_eq!(12, select("ALL").len());
+        assert_eq!(select("ALL:!COMPLEMENTOFDEFAULT"), default);
+
+        assert_eq!(default, select("ALL:!aNULL@SECLEVEL=1"));
+        assert_eq!(7, select("DEFAULT:@SECLEVEL=3").len());
+        assert_eq!(
+            vec!["ECDHE-ECDSA-AES256-GCM-SHA384", "ECDHE-RSA-AES256-GCM-SHA384", "ECDHE-RSA-CHACHA20-POLY1305"],
+            select("DEFAULT:@SECLEVEL=5")
+        );
+    }
+
+    #[test]
+    fn rejects_malformed() {
+        for s in ["AES++SHA", "AES+", "!", "HIGH@FOO", "@SECLEVEL=6", "@SECLEVEL=", "DEFAULT:-"] {
+            assert!(s.parse::<CipherString>().is_err(), "{s}");
+        }
+        assert!("".parse::<CipherString>().unwrap().select(AVAILABLE).is_empty());
+    }
+}
@@ -1,11 +1,12 @@
 //! Cipher Suites supported by Secure Transport
 
 use security_framework_sys::cipher_suite::*;
+use std::fmt;
 
 macro_rules! make_suites {
     ($($suite:ident),+) => {
         /// TLS cipher suites.
-        #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+        #[derive(Copy, Clone, Hash, PartialEq, Eq)]
         pub struct CipherSuite(SSLCipherSuite);
 
         #[allow(missing_docs)]
@@ -29,6 +30,412 @@ macro_rules! make_suites {
     }
 }
 
+impl fmt::Debug for CipherSuite {
+    #[cold]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.iana_name() {
+            Some(name) => write!(fmt, "CipherSuite({name})"),
+            None => write!(fmt, "CipherSuite({:#06x})", self.0),
+        }
+    }
+}
+
+impl CipherSuite {
+    /// Returns the metadata of the suite, or `None` for unknown suites and
+    /// signaling values.
+    #[must_use]
+    pub fn info(&self) -> Option<&'static CipherSuiteInfo> {
+        SUITES
+            .binary_search_by_key(&self.0, |&(raw, _)| raw)
+            .ok()
+            .map(|i| &SUITES[i].1)
+    }
+
+    /// Returns the name of the suite in the IANA TLS registry, e.g.
+    /// `TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256`.
+    ///
+    /// Returns `None` for suites that are not in the registry, such as the
+    /// SSL 2 suites.
+    #[inline]
+    #[must_use]
+    pub fn iana_name(&self) -> Option<&'static str> {
+        self.info().map(|info| info.iana_name)
+    }
+
+    /// Returns the name OpenSSL uses for the suite, e.g.
+    /// `ECDHE-RSA-AES128-GCM-SHA256`.
+    #[inline]
+    #[must_use]
+    pub fn openssl_name(&self) -> Option<&'static str> {
+        self.info().and_then(|info| info.openssl_name)
+    }
+
+    /// Looks up a suite by its IANA name.
+    ///
+    /// Legacy suites can also be found by their `SSL_` prefixed names.
+    #[must_use]
+    pub fn from_iana_name(name: &str) -> Option<Self> {
+        let name = match name.strip_prefix("SSL_") {
+            Some(rest) => return Self::from_iana_name(&format!("TLS_{rest}")),
+            None => name,
+        };
+        SUITES
+            .iter()
+            .find(|(_, info)| info.iana_name == name)
+            .map(|&(raw, _)| Self(raw))
+    }
+
+    /// Returns the suites metadata is known for, in order of their values.
+    pub fn known() -> impl Iterator<Item = Self> {
+        SUITES.iter().map(|&(raw, _)| Self(raw))
+    }
+
+    /// Looks up a suite by the name OpenSSL uses for it.
+    #[must_use]
+    pub fn from_openssl_name(name: &str) -> Option<Self> {
+        SUITES
+            .iter()
+            .find(|(_, info)| info.openssl_name == Some(name))
+            .map(|&(raw, _)| Self(raw))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for CipherSuite {
+    /// Serializes the IANA name, or the number of suites without one.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.iana_name() {
+            Some(name) => serializer.serialize_str(name),
+            None => serializer.serialize_u64(self.0.into()),
+        }
+    }
+}
+
+/// The properties of a cipher suite.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CipherSuiteInfo {
+    /// The name in the IANA TLS registry.
+    pub iana_name: &'static str,
+    /// The name OpenSSL uses, if it implements the suite.
+    pub openssl_name: Option<&'static str>,
+    /// How the shared secret is established.
+    pub key_exchange: KeyExchange,
+    /// How the server, or both peers for PSK, are authenticated.
+    pub authentication: Authentication,
+    /// The cipher protecting records.
+    pub cipher: BulkCipher,
+    /// The MAC protecting records, `Mac::Aead` if part of the cipher.
+    pub mac: Mac,
+    /// The first protocol version the suite can be used with.
+    pub min_version: TlsVersion,
+    /// How the suite is rated today.
+    pub security: Security,
+}
+
+impl CipherSuiteInfo {
+    /// Returns whether the cipher is an AEAD, rather than a cipher combined
+    /// with a separate MAC.
+    #[inline]
+    #[must_use]
+    pub fn is_aead(&self) -> bool {
+        self.mac == Mac::Aead
+    }
+
+    /// Returns whether the key exchange is ephemeral, so a later compromise
+    /// of long-term keys does not reveal the traffic.
+    #[inline]
+    #[must_use]
+    pub fn forward_secrecy(&self) -> bool {
+        matches!(self.key_exchange, KeyExchange::Dhe | KeyExchange::Ecdhe | KeyExchange::Any)
+    }
+}
+
+/// The key exchange algorithm of a cipher suite.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum KeyExchange {
+    /// No key exchange.
+    Null,
+    /// RSA key transport.
+    Rsa,
+    /// Static Diffie-Hellman.
+    Dh,
+    /// Ephemeral Diffie-Hellman.
+    Dhe,
+    /// Static elliptic curve Diffie-Hellman.
+    Ecdh,
+    /// Ephemeral elliptic curve Diffie-Hellman.
+    Ecdhe,
+    /// The pre-shared key alone.
+    Psk,
+    /// Negotiated separately, as in TLS 1.3.
+    Any,
+}
+
+/// The authentication algorithm of a cipher suite.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Authentication {
+    /// No authentication.
+    Null,
+    /// No authentication, with a key exchange.
+    Anonymous,
+    /// RSA certificates.
+    Rsa,
+    /// DSA certificates.
+    Dss,
+    /// ECDSA certificates.
+    Ecdsa,
+    /// A pre-shared key.
+    Psk,
+    /// Negotiated separately, as in TLS 1.3.
+    Any,
+}
+
+/// The bulk cipher of a cipher suite.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum BulkCipher {
+    /// No encryption.
+    Null,
+    /// 40-bit export RC2 in CBC mode.
+    Rc2Export,
+    /// 40-bit export RC4.
+    Rc4Export,
+    /// 40-bit export DES in CBC mode.
+    DesExport,
+    /// 128-bit RC4.
+    Rc4,
+    /// IDEA in CBC mode.
+    Idea,
+    /// 56-bit DES in CBC mode.
+    Des,
+    /// Triple DES in CBC mode.
+    TripleDes,
+    /// AES-128 in CBC mode.
+    Aes128Cbc,
+    /// AES-256 in CBC mode.
+    Aes256Cbc,
+    /// AES-128 in GCM mode.
+    Aes128Gcm,
+    /// AES-256 in GCM mode.
+    Aes256Gcm,
+    /// AES-128 in CCM mode.
+    Aes128Ccm,
+    /// AES-128 in CCM mode with an 8 byte tag.
+    Aes128Ccm8,
+    /// ChaCha20-Poly1305.
+    ChaCha20Poly1305,
+}
+
+/// The MAC of a cipher suite.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Mac {
+    /// No MAC.
+    Null,
+    /// HMAC-MD5.
+    Md5,
+    /// HMAC-SHA1.
+    Sha1,
+    /// HMAC-SHA256.
+    Sha256,
+    /// HMAC-SHA384.
+    Sha384,
+    /// Integrity is provided by the AEAD cipher.
+    Aead,
+}
+
+/// A protocol version, for the purpose of cipher suite metadata.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TlsVersion {
+    /// SSL 3.0.
+    Ssl3,
+    /// TLS 1.0.
+    Tls1,
+    /// TLS 1.2.
+    Tls12,
+    /// TLS 1.3.
+    Tls13,
+}
+
+/// A security rating of a cipher suite, from worst to best.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Security {
+    /// Broken; offers no meaningful protection. Null, export, anonymous,
+    /// RC4, DES and MD5 based suites.
+    Insecure,
+    /// Without known practical attacks, but without forward secrecy or
+    /// using CBC mode.
+    Weak,
+    /// Forward secret with an AEAD cipher.
+    Secure,
+    /// Secure and widely recommended: ECDHE with an AEAD cipher, and TLS 1.3
+    /// suites with full-length tags.
+    Recommended,
+}
+
+macro_rules! suite {
+    ($raw:expr, $iana:expr, $openssl:expr, $kx:ident, $auth:ident, $cipher:ident, $mac:ident, $version:ident, $security:ident) => {
+        ($raw, CipherSuiteInfo {
+            iana_name: $iana,
+            openssl_name: $openssl,
+            key_exchange: KeyExchange::$kx,
+            authentication: Authentication::$auth,
+            cipher: BulkCipher::$cipher,
+            mac: Mac::$mac,
+            min_version: TlsVersion::$version,
+            security: Security::$security,
+        })
+    };
+}
+
+/// Metadata of known suites, sorted by value.
+static SUITES: &[(SSLCipherSuite, CipherSuiteInfo)] = &[
+    suite!(0x0000, "TLS_NULL_WITH_NULL_NULL", None, Null, Null, Null, Null, Ssl3, Insecure),
+    suite!(0x0001, "TLS_RSA_WITH_NULL_MD5", Some("NULL-MD5"), Rsa, Rsa, Null, Md5, Ssl3, Insecure),
+    suite!(0x0002, "TLS_RSA_WITH_NULL_SHA", Some("NULL-SHA"), Rsa, Rsa, Null, Sha1, Ssl3, Insecure),
+    suite!(0x0003, "TLS_RSA_EXPORT_WITH_RC4_40_MD5", Some("EXP-RC4-MD5"), Rsa, Rsa, Rc4Export, Md5, Ssl3, Insecure),
+    suite!(0x0004, "TLS_RSA_WITH_RC4_128_MD5", Some("RC4-MD5"), Rsa, Rsa, Rc4, Md5, Ssl3, Insecure),
+    suite!(0x0005, "TLS_RSA_WITH_RC4_128_SHA", Some("RC4-SHA"), Rsa, Rsa, Rc4, Sha1, Ssl3, Insecure),
+    suite!(0x0006, "TLS_RSA_EXPORT_WITH_RC2_CBC_40_MD5", Some("EXP-RC2-CBC-MD5"), Rsa, Rsa, Rc2Export, Md5, Ssl3, Insecure),
+    suite!(0x0007, "TLS_RSA_WITH_IDEA_CBC_SHA", Some("IDEA-CBC-SHA"), Rsa, Rsa, Idea, Sha1, Ssl3, Weak),
+    suite!(0x0008, "TLS_RSA_EXPORT_WITH_DES40_CBC_SHA", Some("EXP-DES-CBC-SHA"), Rsa, Rsa, DesExport, Sha1, Ssl3, Insecure),
+    suite!(0x0009, "TLS_RSA_WITH_DES_CBC_SHA", Some("DES-CBC-SHA"), Rsa, Rsa, Des, Sha1, Ssl3, Insecure),
+    suite!(0x000A, "TLS_RSA_WITH_3DES_EDE_CBC_SHA", Some("DES-CBC3-SHA"), Rsa, Rsa, TripleDes, Sha1, Ssl3, Weak),
+    suite!(0x000B, "TLS_DH_DSS_EXPORT_WITH_DES40_CBC_SHA", Some("EXP-DH-DSS-DES-CBC-SHA"), Dh, Dss, DesExport, Sha1, Ssl3, Insecure),
+    suite!(0x000C, "TLS_DH_DSS_WITH_DES_CBC_SHA", Some("DH-DSS-DES-CBC-SHA"), Dh, Dss, Des, Sha1, Ssl3, Insecure),
+    suite!(0x000D, "TLS_DH_DSS_WITH_3DES_EDE_CBC_SHA", Some("DH-DSS-DES-CBC3-SHA"), Dh, Dss, TripleDes, Sha1, Ssl3, Weak),
+    suite!(0x000E, "TLS_DH_RSA_EXPORT_WITH_DES40_CBC_SHA", Some("EXP-DH-RSA-DES-CBC-SHA"), Dh, Rsa, DesExport, Sha1, Ssl3, Insecure),
+    suite!(0x000F, "TLS_DH_RSA_WITH_DES_CBC_SHA", Some("DH-RSA-DES-CBC-SHA"), Dh, Rsa, Des, Sha1, Ssl3, Insecure),
+    suite!(0x0010, "TLS_DH_RSA_WITH_3DES_EDE_CBC_SHA", Some("DH-RSA-DES-CBC3-SHA"), Dh, Rsa, TripleDes, Sha1, Ssl3, Weak),
+    suite!(0x0011, "TLS_DHE_DSS_EXPORT_WITH_DES40_CBC_SHA", Some("EXP-EDH-DSS-DES-CBC-SHA"), Dhe, Dss, DesExport, Sha1, Ssl3, Insecure),
+    suite!(0x0012, "TLS_DHE_DSS_WITH_DES_CBC_SHA", Some("EDH-DSS-DES-CBC-SHA"), Dhe, Dss, Des, Sha1, Ssl3, Insecure),
+    suite!(0x0013, "TLS_DHE_DSS_WITH_3DES_EDE_CBC_SHA", Some("EDH-DSS-DES-CBC3-SHA"), Dhe, Dss, TripleDes, Sha1, Ssl3, Weak),
+    suite!(0x0014, "TLS_DHE_RSA_EXPORT_WITH_DES40_CBC_SHA", Some("EXP-EDH-RSA-DES-CBC-SHA"), Dhe, Rsa, DesExport, Sha1, Ssl3, Insecure),
+    suite!(0x0015, "TLS_DHE_RSA_WITH_DES_CBC_SHA", Some("EDH-RSA-DES-CBC-SHA"), Dhe, Rsa, Des, Sha1, Ssl3, Insecure),
+    suite!(0x0016, "TLS_DHE_RSA_WITH_3DES_EDE_CBC_SHA", Some("EDH-RSA-DES-CBC3-SHA"), Dhe, Rsa, TripleDes, Sha1, Ssl3, Weak),
+    suite!(0x0017, "TLS_DH_anon_EXPORT_WITH_RC4_40_MD5", Some("EXP-ADH-RC4-MD5"), Dhe, Anonymous, Rc4Export, Md5, Ssl3, Insecure),
+    suite!(0x0018, "TLS_DH_anon_WITH_RC4_128_MD5", Some("ADH-RC4-MD5"), Dhe, Anonymous, Rc4, Md5, Ssl3, Insecure),
+    suite!(0x0019, "TLS_DH_anon_EXPORT_WITH_DES40_CBC_SHA", Some("EXP-ADH-DES-CBC-SHA"), Dhe, Anonymous, DesExport, Sha1, Ssl3, Insecure),
+    suite!(0x001A, "TLS_DH_anon_WITH_DES_CBC_SHA", Some("ADH-DES-CBC-SHA"), Dhe, Anonymous, Des, Sha1, Ssl3, Insecure),
+    suite!(0x001B, "TLS_DH_anon_WITH_3DES_EDE_CBC_SHA", Some("ADH-DES-CBC3-SHA"), Dhe, Anonymous, TripleDes, Sha1, Ssl3, Insecure),
+    suite!(0x002C, "TLS_PSK_WITH_NULL_SHA", Some("PSK-NULL-SHA"), Psk, Psk, Null, Sha1, Tls1, Insecure),
+    suite!(0x002D, "TLS_DHE_PSK_WITH_NULL_SHA", Some("DHE-PSK-NULL-SHA"), Dhe, Psk, Null, Sha1, Tls1, Insecure),
+    suite!(0x002E, "TLS_RSA_PSK_WITH_NULL_SHA", Some("RSA-PSK-NULL-SHA"), Rsa, Psk, Null, Sha1, Tls1, Insecure),
+    suite!(0x002F, "TLS_RSA_WITH_AES_128_CBC_SHA", Some("AES128-SHA"), Rsa, Rsa, Aes128Cbc, Sha1, Ssl3, Weak),
+    suite!(0x0030, "TLS_DH_DSS_WITH_AES_128_CBC_SHA", Some("DH-DSS-AES128-SHA"), Dh, Dss, Aes128Cbc, Sha1, Ssl3, Weak),
+    suite!(0x0031, "TLS_DH_RSA_WITH_AES_128_CBC_SHA", Some("DH-RSA-AES128-SHA"), Dh, Rsa, Aes128Cbc, Sha1, Ssl3, Weak),
+    suite!(0x0032, "TLS_DHE_DSS_WITH_AES_128_CBC_SHA", Some("DHE-DSS-AES128-SHA"), Dhe, Dss, Aes128Cbc, Sha1, Ssl3, Weak),
+    suite!(0x0033, "TLS_DHE_RSA_WITH_AES_128_CBC_SHA", Some("DHE-RSA-AES128-SHA"), Dhe, Rsa, Aes128Cbc, Sha1, Ssl3, Weak),
+    suite!(0x0034, "TLS_DH_anon_WITH_AES_128_CBC_SHA", Some("ADH-AES128-SHA"), Dhe, Anonymous, Aes128Cbc, Sha1, Ssl3, Insecure),
+    suite!(0x0035, "TLS_RSA_WITH_AES_256_CBC_SHA", Some("AES256-SHA"), Rsa, Rsa, Aes256Cbc, Sha1, Ssl3, Weak),
+    suite!(0x0036, "TLS_DH_DSS_WITH_AES_256_CBC_SHA", Some("DH-DSS-AES256-SHA"), Dh, Dss, Aes256Cbc, Sha1, Ssl3, Weak),
+    suite!(0x0037, "TLS_DH_RSA_WITH_AES_256_CBC_SHA", Some("DH-RSA-AES256-SHA"), Dh, Rsa, Aes256Cbc, Sha1, Ssl3, Weak),
+    suite!(0x0038, "TLS_DHE_DSS_WITH_AES_256_CBC_SHA", Some("DHE-DSS-AES256-SHA"), Dhe, Dss, Aes256Cbc, Sha1, Ssl3, Weak),
+    suite!(0x0039, "TLS_DHE_RSA_WITH_AES_256_CBC_SHA", Some("DHE-RSA-AES256-SHA"), Dhe, Rsa, Aes256Cbc, Sha1, Ssl3, Weak),
+    suite!(0x003A, "TLS_DH_anon_WITH_AES_256_CBC_SHA", Some("ADH-AES256-SHA"), Dhe, Anonymous, Aes256Cbc, Sha1, Ssl3, Insecure),
+    suite!(0x003B, "TLS_RSA_WITH_NULL_SHA256", Some("NULL-SHA256"), Rsa, Rsa, Null, Sha256, Tls12, Insecure),
+    suite!(0x003C, "TLS_RSA_WITH_AES_128_CBC_SHA256", Some("AES128-SHA256"), Rsa, Rsa, Aes128Cbc, Sha256, Tls12, Weak),
+    suite!(0x003D, "TLS_RSA_WITH_AES_256_CBC_SHA256", Some("AES256-SHA256"), Rsa, Rsa, Aes256Cbc, Sha256, Tls12, Weak),
+    suite!(0x003E, "TLS_DH_DSS_WITH_AES_128_CBC_SHA256", Some("DH-DSS-AES128-SHA256"), Dh, Dss, Aes128Cbc, Sha256, Tls12, Weak),
+    suite!(0x003F, "TLS_DH_RSA_WITH_AES_128_CBC_SHA256", Some("DH-RSA-AES128-SHA256"), Dh, Rsa, Aes128Cbc, Sha256, Tls12, Weak),
+    suite!(0x0040, "TLS_DHE_DSS_WITH_AES_128_CBC_SHA256", Some("DHE-DSS-AES128-SHA256"), Dhe, Dss, Aes128Cbc, Sha256, Tls12, Weak),
+    suite!(0x0067, "TLS_DHE_RSA_WITH_AES_128_CBC_SHA256", Some("DHE-RSA-AES128-SHA256"), Dhe, Rsa, Aes128Cbc, Sha256, Tls12, Weak),
+    suite!(0x0068, "TLS_DH_DSS_WITH_AES_256_CBC_SHA256", Some("DH-DSS-AES256-SHA256"), Dh, Dss, Aes256Cbc, Sha256, Tls12, Weak),
+    suite!(0x0069, "TLS_DH_RSA_WITH_AES_256_CBC_SHA256", Some("DH-RSA-AES256-SHA256"), Dh, Rsa, Aes256Cbc, Sha256, Tls12, Weak),
+    suite!(0x006A, "TLS_DHE_DSS_WITH_AES_256_CBC_SHA256", Some("DHE-DSS-AES256-SHA256"), Dhe, Dss, Aes256Cbc, Sha256, Tls12, Weak),
+    suite!(0x006B, "TLS_DHE_RSA_WITH_AES_256_CBC_SHA256", Some("DHE-RSA-AES256-SHA256"), Dhe, Rsa, Aes256Cbc, Sha256, Tls12, Weak),
+    suite!(0x006C, "TLS_DH_anon_WITH_AES_128_CBC_SHA256", Some("ADH-AES128-SHA256"), Dhe, Anonymous, Aes128Cbc, Sha256, Tls12, Insecure),
+    suite!(0x006D, "TLS_DH_anon_WITH_AES_256_CBC_SHA256", Some("ADH-AES256-SHA256"), Dhe, Anonymous, Aes256Cbc, Sha256, Tls12, Insecure),
+    suite!(0x008A, "TLS_PSK_WITH_RC4_128_SHA", Some("PSK-RC4-SHA"), Psk, Psk, Rc4, Sha1, Tls1, Insecure),
+    suite!(0x008B, "TLS_PSK_WITH_3DES_EDE_CBC_SHA", Some("PSK-3DES-EDE-CBC-SHA"), Psk, Psk, TripleDes, Sha1, Tls1, Weak),
+    suite!(0x008C, "TLS_PSK_WITH_AES_128_CBC_SHA", Some("PSK-AES128-CBC-SHA"), Psk, Psk, Aes128Cbc, Sha1, Tls1, Weak),
+    suite!(0x008D, "TLS_PSK_WITH_AES_256_CBC_SHA", Some("PSK-AES256-CBC-SHA"), Psk, Psk, Aes256Cbc, Sha1, Tls1, Weak),
+    suite!(0x008E, "TLS_DHE_PSK_WITH_RC4_128_SHA", Some("DHE-PSK-RC4-SHA"), Dhe, Psk, Rc4, Sha1, Tls1, Insecure),
+    suite!(0x008F, "TLS_DHE_PSK_WITH_3DES_EDE_CBC_SHA", Some("DHE-PSK-3DES-EDE-CBC-SHA"), Dhe, Psk, TripleDes, Sha1, Tls1, Weak),
+    suite!(0x0090, "TLS_DHE_PSK_WITH_AES_128_CBC_SHA", Some("DHE-PSK-AES128-CBC-SHA"), Dhe, Psk, Aes128Cbc, Sha1, Tls1, Weak),
+    suite!(0x0091, "TLS_DHE_PSK_WITH_AES_256_CBC_SHA", Some("DHE-PSK-AES256-CBC-SHA"), Dhe, Psk, Aes256Cbc, Sha1, Tls1, Weak),
+    suite!(0x0092, "TLS_RSA_PSK_WITH_RC4_128_SHA", Some("RSA-PSK-RC4-SHA"), Rsa, Psk, Rc4, Sha1, Tls1, Insecure),
+    suite!(0x0093, "TLS_RSA_PSK_WITH_3DES_EDE_CBC_SHA", Some("RSA-PSK-3DES-EDE-CBC-SHA"), Rsa, Psk, TripleDes, Sha1, Tls1, Weak),
+    suite!(0x0094, "TLS_RSA_PSK_WITH_AES_128_CBC_SHA", Some("RSA-PSK-AES128-CBC-SHA"), Rsa, Psk, Aes128Cbc, Sha1, Tls1, Weak),
+    suite!(0x0095, "TLS_RSA_PSK_WITH_AES_256_CBC_SHA", Some("RSA-PSK-AES256-CBC-SHA"), Rsa, Psk, Aes256Cbc, Sha1, Tls1, Weak),
+    suite!(0x009C, "TLS_RSA_WITH_AES_128_GCM_SHA256", Some("AES128-GCM-SHA256"), Rsa, Rsa, Aes128Gcm, Aead, Tls12, Weak),
+    suite!(0x009D, "TLS_RSA_WITH_AES_256_GCM_SHA384", Some("AES256-GCM-SHA384"), Rsa, Rsa, Aes256Gcm, Aead, Tls12, Weak),
+    suite!(0x009E, "TLS_DHE_RSA_WITH_AES_128_GCM_SHA256", Some("DHE-RSA-AES128-GCM-SHA256"), Dhe, Rsa, Aes128Gcm, Aead, Tls12, Secure),
+    suite!(0x009F, "TLS_DHE_RSA_WITH_AES_256_GCM_SHA384", Some("DHE-RSA-AES256-GCM-SHA384"), Dhe, Rsa, Aes256Gcm, Aead, Tls12, Secure),
+    suite!(0x00A0, "TLS_DH_RSA_WITH_AES_128_GCM_SHA256", Some("DH-RSA-AES128-GCM-SHA256"), Dh, Rsa, Aes128Gcm, Aead, Tls12, Weak),
+    suite!(0x00A1, "TLS_DH_RSA_WITH_AES_256_GCM_SHA384", Some("DH-RSA-AES256-GCM-SHA384"), Dh, Rsa, Aes256Gcm, Aead, Tls12, Weak),
+    suite!(0x00A2, "TLS_DHE_DSS_WITH_AES_128_GCM_SHA256", Some("DHE-DSS-AES128-GCM-SHA256"), Dhe, Dss, Aes128Gcm, Aead, Tls12, Secure),
+    suite!(0x00A3, "TLS_DHE_DSS_WITH_AES_256_GCM_SHA384", Some("DHE-DSS-AES256-GCM-SHA384"), Dhe, Dss, Aes256Gcm, Aead, Tls12, Secure),
+    suite!(0x00A4, "TLS_DH_DSS_WITH_AES_128_GCM_SHA256", Some("DH-DSS-AES128-GCM-SHA256"), Dh, Dss, Aes128Gcm, Aead, Tls12, Weak),
+    suite!(0x00A5, "TLS_DH_DSS_WITH_AES_256_GCM_SHA384", Some("DH-DSS-AES256-GCM-SHA384"), Dh, Dss, Aes256Gcm, Aead, Tls12, Weak),
+    suite!(0x00A6, "TLS_DH_anon_WITH_AES_128_GCM_SHA256", Some("ADH-AES128-GCM-SHA256"), Dhe, Anonymous, Aes128Gcm, Aead, Tls12, Insecure),
+    suite!(0x00A7, "TLS_DH_anon_WITH_AES_256_GCM_SHA384", Some("ADH-AES256-GCM-SHA384"), Dhe, Anonymous, Aes256Gcm, Aead, Tls12, Insecure),
+    suite!(0x00A8, "TLS_PSK_WITH_AES_128_GCM_SHA256", Some("PSK-AES128-GCM-SHA256"), Psk, Psk, Aes128Gcm, Aead, Tls12, Weak),
+    suite!(0x00A9, "TLS_PSK_WITH_AES_256_GCM_SHA384", Some("PSK-AES256-GCM-SHA384"), Psk, Psk, Aes256Gcm, Aead, Tls12, Weak),
+    suite!(0x00AA, "TLS_DHE_PSK_WITH_AES_128_GCM_SHA256", Some("DHE-PSK-AES128-GCM-SHA256"), Dhe, Psk, Aes128Gcm, Aead, Tls12, Secure),
+    suite!(0x00AB, "TLS_DHE_PSK_WITH_AES_256_GCM_SHA384", Some("DHE-PSK-AES256-GCM-SHA384"), Dhe, Psk, Aes256Gcm, Aead, Tls12, Secure),
+    suite!(0x00AC, "TLS_RSA_PSK_WITH_AES_128_GCM_SHA256", Some("RSA-PSK-AES128-GCM-SHA256"), Rsa, Psk, Aes128Gcm, Aead, Tls12, Weak),
+    suite!(0x00AD, "TLS_RSA_PSK_WITH_AES_256_GCM_SHA384", Some("RSA-PSK-AES256-GCM-SHA384"), Rsa, Psk, Aes256Gcm, Aead, Tls12, Weak),
+    suite!(0x00AE, "TLS_PSK_WITH_AES_128_CBC_SHA256", Some("PSK-AES128-CBC-SHA256"), Psk, Psk, Aes128Cbc, Sha256, Tls1, Weak),
+    suite!(0x00AF, "TLS_PSK_WITH_AES_256_CBC_SHA384", Some("PSK-AES256-CBC-SHA384"), Psk, Psk, Aes256Cbc, Sha384, Tls1, Weak),
+    suite!(0x00B0, "TLS_PSK_WITH_NULL_SHA256", Some("PSK-NULL-SHA256"), Psk, Psk, Null, Sha256, Tls1, Insecure),
+    suite!(0x00B1, "TLS_PSK_WITH_NULL_SHA384", Some("PSK-NULL-SHA384"), Psk, Psk, Null, Sha384, Tls1, Insecure),
+    suite!(0x00B2, "TLS_DHE_PSK_WITH_AES_128_CBC_SHA256", Some("DHE-PSK-AES128-CBC-SHA256"), Dhe, Psk, Aes128Cbc, Sha256, Tls1, Weak),
+    suite!(0x00B3, "TLS_DHE_PSK_WITH_AES_256_CBC_SHA384", Some("DHE-PSK-AES256-CBC-SHA384"), Dhe, Psk, Aes256Cbc, Sha384, Tls1, Weak),
+    suite!(0x00B4, "TLS_DHE_PSK_WITH_NULL_SHA256", Some("DHE-PSK-NULL-SHA256"), Dhe, Psk, Null, Sha256, Tls1, Insecure),
+    suite!(0x00B5, "TLS_DHE_PSK_WITH_NULL_SHA384", Some("DHE-PSK-NULL-SHA384"), Dhe, Psk, Null, Sha384, Tls1, Insecure),
+    suite!(0x00B6, "TLS_RSA_PSK_WITH_AES_128_CBC_SHA256", Some("RSA-PSK-AES128-CBC-SHA256"), Rsa, Psk, Aes128Cbc, Sha256, Tls1, Weak),
+    suite!(0x00B7, "TLS_RSA_PSK_WITH_AES_256_CBC_SHA384", Some("RSA-PSK-AES256-CBC-SHA384"), Rsa, Psk, Aes256Cbc, Sha384, Tls1, Weak),
+    suite!(0x00B8, "TLS_RSA_PSK_WITH_NULL_SHA256", Some("RSA-PSK-NULL-SHA256"), Rsa, Psk, Null, Sha256, Tls1, Insecure),
+    suite!(0x00B9, "TLS_RSA_PSK_WITH_NULL_SHA384", Some("RSA-PSK-NULL-SHA384"), Rsa, Psk, Null, Sha384, Tls1, Insecure),
+    suite!(0x1301, "TLS_AES_128_GCM_SHA256", Some("TLS_AES_128_GCM_SHA256"), Any, Any, Aes128Gcm, Aead, Tls13, Recommended),
+    suite!(0x1302, "TLS_AES_256_GCM_SHA384", Some("TLS_AES_256_GCM_SHA384"), Any, Any, Aes256Gcm, Aead, Tls13, Recommended),
+    suite!(0x1303, "TLS_CHACHA20_POLY1305_SHA256", Some("TLS_CHACHA20_POLY1305_SHA256"), Any, Any, ChaCha20Poly1305, Aead, Tls13, Recommended),
+    suite!(0x1304, "TLS_AES_128_CCM_SHA256", Some("TLS_AES_128_CCM_SHA256"), Any, Any, Aes128Ccm, Aead, Tls13, Recommended),
+    suite!(0x1305, "TLS_AES_128_CCM_8_SHA256", Some("TLS_AES_128_CCM_8_SHA256"), Any, Any, Aes128Ccm8, Aead, Tls13, Secure),
+    suite!(0xC001, "TLS_ECDH_ECDSA_WITH_NULL_SHA", Some("ECDH-ECDSA-NULL-SHA"), Ecdh, Ecdsa, Null, Sha1, Tls1, Insecure),
+    suite!(0xC002, "TLS_ECDH_ECDSA_WITH_RC4_128_SHA", Some("ECDH-ECDSA-RC4-SHA"), Ecdh, Ecdsa, Rc4, Sha1, Tls1, Insecure),
+    suite!(0xC003, "TLS_ECDH_ECDSA_WITH_3DES_EDE_CBC_SHA", Some("ECDH-ECDSA-DES-CBC3-SHA"), Ecdh, Ecdsa, TripleDes, Sha1, Tls1, Weak),
+    suite!(0xC004, "TLS_ECDH_ECDSA_WITH_AES_128_CBC_SHA", Some("ECDH-ECDSA-AES128-SHA"), Ecdh, Ecdsa, Aes128Cbc, Sha1, Tls1, Weak),
+    suite!(0xC005, "TLS_ECDH_ECDSA_WITH_AES_256_CBC_SHA", Some("ECDH-ECDSA-AES256-SHA"), Ecdh, Ecdsa, Aes256Cbc, Sha1, Tls1, Weak),
+    suite!(0xC006, "TLS_ECDHE_ECDSA_WITH_NULL_SHA", Some("ECDHE-ECDSA-NULL-SHA"), Ecdhe, Ecdsa, Null, Sha1, Tls1, Insecure),
+    suite!(0xC007, "TLS_ECDHE_ECDSA_WITH_RC4_128_SHA", Some("ECDHE-ECDSA-RC4-SHA"), Ecdhe, Ecdsa, Rc4, Sha1, Tls1, Insecure),
+    suite!(0xC008, "TLS_ECDHE_ECDSA_WITH_3DES_EDE_CBC_SHA", Some("ECDHE-ECDSA-DES-CBC3-SHA"), Ecdhe, Ecdsa, TripleDes, Sha1, Tls1, Weak),
+    suite!(0xC009, "TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA", Some("ECDHE-ECDSA-AES128-SHA"), Ecdhe, Ecdsa, Aes128Cbc, Sha1, Tls1, Weak),
+    suite!(0xC00A, "TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA", Some("ECDHE-ECDSA-AES256-SHA"), Ecdhe, Ecdsa, Aes256Cbc, Sha1, Tls1, Weak),
+    suite!(0xC00B, "TLS_ECDH_RSA_WITH_NULL_SHA", Some("ECDH-RSA-NULL-SHA"), Ecdh, Rsa, Null, Sha1, Tls1, Insecure),
+    suite!(0xC00C, "TLS_ECDH_RSA_WITH_RC4_128_SHA", Some("ECDH-RSA-RC4-SHA"), Ecdh, Rsa, Rc4, Sha1, Tls1, Insecure),
+    suite!(0xC00D, "TLS_ECDH_RSA_WITH_3DES_EDE_CBC_SHA", Some("ECDH-RSA-DES-CBC3-SHA"), Ecdh, Rsa, TripleDes, Sha1, Tls1, Weak),
+    suite!(0xC00E, "TLS_ECDH_RSA_WITH_AES_128_CBC_SHA", Some("ECDH-RSA-AES128-SHA"), Ecdh, Rsa, Aes128Cbc, Sha1, Tls1, Weak),
+    suite!(0xC00F, "TLS_ECDH_RSA_WITH_AES_256_CBC_SHA", Some("ECDH-RSA-AES256-SHA"), Ecdh, Rsa, Aes256Cbc, Sha1, Tls1, Weak),
+    suite!(0xC010, "TLS_ECDHE_RSA_WITH_NULL_SHA", Some("ECDHE-RSA-NULL-SHA"), Ecdhe, Rsa, Null, Sha1, Tls1, Insecure),
+    suite!(0xC011, "TLS_ECDHE_RSA_WITH_RC4_128_SHA", Some("ECDHE-RSA-RC4-SHA"), Ecdhe, Rsa, Rc4, Sha1, Tls1, Insecure),
+    suite!(0xC012, "TLS_ECDHE_RSA_WITH_3DES_EDE_CBC_SHA", Some("ECDHE-RSA-DES-CBC3-SHA"), Ecdhe, Rsa, TripleDes, Sha1, Tls1, Weak),
+    suite!(0xC013, "TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA", Some("ECDHE-RSA-AES128-SHA"), Ecdhe, Rsa, Aes128Cbc, Sha1, Tls1, Weak),
+    suite!(0xC014, "TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA", Some("ECDHE-RSA-AES256-SHA"), Ecdhe, Rsa, Aes256Cbc, Sha1, Tls1, Weak),
+    suite!(0xC015, "TLS_ECDH_anon_WITH_NULL_SHA", Some("AECDH-NULL-SHA"), Ecdhe, Anonymous, Null, Sha1, Tls1, Insecure),
+    suite!(0xC016, "TLS_ECDH_anon_WITH_RC4_128_SHA", Some("AECDH-RC4-SHA"), Ecdhe, Anonymous, Rc4, Sha1, Tls1, Insecure),
+    suite!(0xC017, "TLS_ECDH_anon_WITH_3DES_EDE_CBC_SHA", Some("AECDH-DES-CBC3-SHA"), Ecdhe, Anonymous, TripleDes, Sha1, Tls1, Insecure),
+    suite!(0xC018, "TLS_ECDH_anon_WITH_AES_128_CBC_SHA", Some("AECDH-AES128-SHA"), Ecdhe, Anonymous, Aes128Cbc, Sha1, Tls1, Insecure),
+    suite!(0xC019, "TLS_ECDH_anon_WITH_AES_256_CBC_SHA", Some("AECDH-AES256-SHA"), Ecdhe, Anonymous, Aes256Cbc, Sha1, Tls1, Insecure),
+    suite!(0xC023, "TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA256", Some("ECDHE-ECDSA-AES128-SHA256"), Ecdhe, Ecdsa, Aes128Cbc, Sha256, Tls12, Weak),
+    suite!(0xC024, "TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA384", Some("ECDHE-ECDSA-AES256-SHA384"), Ecdhe, Ecdsa, Aes256Cbc, Sha384, Tls12, Weak),
+    suite!(0xC025, "TLS_ECDH_ECDSA_WITH_AES_128_CBC_SHA256", Some("ECDH-ECDSA-AES128-SHA256"), Ecdh, Ecdsa, Aes128Cbc, Sha256, Tls12, Weak),
+    suite!(0xC026, "TLS_ECDH_ECDSA_WITH_AES_256_CBC_SHA384", Some("ECDH-ECDSA-AES256-SHA384"), Ecdh, Ecdsa, Aes256Cbc, Sha384, Tls12, Weak),
+    suite!(0xC027, "TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA256", Some("ECDHE-RSA-AES128-SHA256"), Ecdhe, Rsa, Aes128Cbc, Sha256, Tls12, Weak),
+    suite!(0xC028, "TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA384", Some("ECDHE-RSA-AES256-SHA384"), Ecdhe, Rsa, Aes256Cbc, Sha384, Tls12, Weak),
+    suite!(0xC029, "TLS_ECDH_RSA_WITH_AES_128_CBC_SHA256", Some("ECDH-RSA-AES128-SHA256"), Ecdh, Rsa, Aes128Cbc, Sha256, Tls12, Weak),
+    suite!(0xC02A, "TLS_ECDH_RSA_WITH_AES_256_CBC_SHA384", Some("ECDH-RSA-AES256-SHA384"), Ecdh, Rsa, Aes256Cbc, Sha384, Tls12, Weak),
+    suite!(0xC02B, "TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256", Some("ECDHE-ECDSA-AES128-GCM-SHA256"), Ecdhe, Ecdsa, Aes128Gcm, Aead, Tls12, Recommended),
+    suite!(0xC02C, "TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384", Some("ECDHE-ECDSA-AES256-GCM-SHA384"), Ecdhe, Ecdsa, Aes256Gcm, Aead, Tls12, Recommended),
+    suite!(0xC02D, "TLS_ECDH_ECDSA_WITH_AES_128_GCM_SHA256", Some("ECDH-ECDSA-AES128-GCM-SHA256"), Ecdh, Ecdsa, Aes128Gcm, Aead, Tls12, Weak),
+    suite!(0xC02E, "TLS_ECDH_ECDSA_WITH_AES_256_GCM_SHA384", Some("ECDH-ECDSA-AES256-GCM-SHA384"), Ecdh, Ecdsa, Aes256Gcm, Aead, Tls12, Weak),
+    suite!(0xC02F, "TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256", Some("ECDHE-RSA-AES128-GCM-SHA256"), Ecdhe, Rsa, Aes128Gcm, Aead, Tls12, Recommended),
+    suite!(0xC030, "TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384", Some("ECDHE-RSA-AES256-GCM-SHA384"), Ecdhe, Rsa, Aes256Gcm, Aead, Tls12, Recommended),
+    suite!(0xC031, "TLS_ECDH_RSA_WITH_AES_128_GCM_SHA256", Some("ECDH-RSA-AES128-GCM-SHA256"), Ecdh, Rsa, Aes128Gcm, Aead, Tls12, Weak),
+    suite!(0xC032, "TLS_ECDH_RSA_WITH_AES_256_GCM_SHA384", Some("ECDH-RSA-AES256-GCM-SHA384"), Ecdh, Rsa, Aes256Gcm, Aead, Tls12, Weak),
+    suite!(0xCCA8, "TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256", Some("ECDHE-RSA-CHACHA20-POLY1305"), Ecdhe, Rsa, ChaCha20Poly1305, Aead, Tls12, Recommended),
+    suite!(0xCCA9, "TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256", Some("ECDHE-ECDSA-CHACHA20-POLY1305"), Ecdhe, Ecdsa, ChaCha20Poly1305, Aead, Tls12, Recommended),
+];
+
 make_suites! {
     // The commented out ones up here are aliases of the matching TLS suites
     SSL_NULL_WITH_NULL_NULL,
@@ -232,6 +639,17 @@ make_suites! {
     TLS_ECDH_RSA_WITH_AES_128_GCM_SHA256,
     TLS_ECDH_RSA_WITH_AES_256_GCM_SHA384,
 
+    /* RFC 7905 - ChaCha20-Poly1305 Cipher Suites for TLS */
+    TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256,
+    TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256,
+
+    /* TLS 1.3 standard cipher suites */
+    TLS_AES_128_GCM_SHA256,
+    TLS_AES_256_GCM_SHA384,
+    TLS_CHACHA20_POLY1305_SHA256,
+    TLS_AES_128_CCM_SHA256,
+    TLS_AES_128_CCM_8_SHA256,
+
     /* RFC 5746 - Secure Renegotiation */
     TLS_EMPTY_RENEGOTIATION_INFO_SCSV,
     /*
@@ -244,3 +662,86 @@ make_suites! {
     SSL_RSA_WITH_3DES_EDE_CBC_MD5,
     SSL_NO_SUCH_CIPHERSUITE
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn iana_names() {
+        assert!(SUITES.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(Some("TLS_RSA_WITH_RC4_128_MD5"), CipherSuite::SSL_RSA_WITH_RC4_128_MD5.iana_name());
+        assert_eq!(
+            Some("TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256"),
+            CipherSuite::TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256.iana_name()
+        );
+        assert_eq!(Some("TLS_AES_128_GCM_SHA256"), CipherSuite::TLS_AES_128_GCM_SHA256.iana_name());
+        assert_eq!(None, CipherSuite::SSL_RSA_WITH_RC2_CBC_MD5.iana_name());
+        assert_eq!(None, CipherSuite::TLS_EMPTY_RENEGOTIATION_INFO_SCSV.info());
+    }
+
+    #[test]
+    fn known_suites() {
+        assert_eq!(SUITES.len(), CipherSuite::known().count());
+        assert!(CipherSuite::known().all(|suite| suite.info().is_some()));
+        assert!(CipherSuite::known().any(|suite| suite == CipherSuite::TLS_PSK_WITH_AES_128_GCM_SHA256));
+    }
+
+    #[test]
+    fn lookup_by_name() {
+        for (raw, info) in SUITES {
+            assert_eq!(Some(CipherSuite(*raw)), CipherSuite::from_iana_name(info.iana_name));
+            if let Some(name) = info.openssl_name {
+                assert_eq!(Some(CipherSuite(*raw)), CipherSuite::from_openssl_name(name), "{name}");
+            }
+        }
+        assert_eq!(
+            Some(CipherSuite::TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256),
+            CipherSuite::from_openssl_name("ECDHE-ECDSA-CHACHA20-POLY1305")
+        );
+        assert_eq!(
+            Some(CipherSuite::TLS_RSA_WITH_3DES_EDE_CBC_SHA),
+            CipherSuite::from_openssl_name("DES-CBC3-SHA")
+        );
+        assert_eq!(
+            Some(CipherSuite::SSL_DHE_RSA_WITH_DES_CBC_SHA),
+            CipherSuite::from_iana_name("SSL_DHE_RSA_WITH_DES_CBC_SHA")
+        );
+        assert_eq!(None, CipherSuite::from_iana_name("TLS_BOGUS"));
+        assert_eq!(None, CipherSuite::from_openssl_name("TLS_RSA_WITH_RC4_128_MD5"));
+    }
+
+    #[test]
+    fn consistent_metadata() {
+        for (raw, info) in SUITES {
+            let name = info.iana_name;
+            assert_eq!(info.is_aead(), name.contains("_GCM_") || name.contains("_CCM") || name.contains("_CHACHA20_"), "{name}");
+            assert_eq!(info.min_version == TlsVersion::Tls13, *raw >> 8 == 0x13, "{name}");
+            if info.security >= Security::Secure {
+                assert!(info.is_aead() && info.forward_secrecy(), "{name}");
+            }
+            if info.authentication == Authentication::Anonymous || name.contains("_EXPORT_") {
+                assert_eq!(Security::Insecure, info.security, "{name}");
+            }
+        }
+
+        let info = CipherSuite::TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384.info().unwrap();
+        assert_eq!(KeyExchange::Ecdhe, info.key_exchange);
+        assert_eq!(Authentication::Rsa, info.authentication);
+        assert_eq!(BulkCipher::Aes256Gcm, info.cipher);
+        assert_eq!(TlsVersion::Tls12, info.min_version);
+        assert_eq!(Security::Recommended, info.security);
+        let info = CipherSuite::TLS_RSA_WITH_AES_128_CBC_SHA.info().unwrap();
+        assert!(!info.forward_secrecy());
+        assert_eq!(Security::Weak, info.security);
+    }
+
+    #[test]
+    fn debug_names_suites() {
+        assert_eq!(
+            "CipherSuite(TLS_AES_128_GCM_SHA256)",
+            format!("{:?}", CipherSuite::TLS_AES_128_GCM_SHA256)
+        );
+        assert_eq!("CipherSuite(0xff80)", format!("{:?}", CipherSuite::SSL_RSA_WITH_RC2_CBC_MD5));
+    }
+}
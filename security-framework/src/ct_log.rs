@@ -0,0 +1,458 @@
+//! Certificate Transparency log lists.
+//!
+//! A [`CtLogList`] is loaded from the JSON v3 log list format published by
+//! browser vendors, e.g. `https://www.gstatic.com/ct/log_list/v3/log_list.json`.
+//! It is used to check that a certificate carries signed certificate timestamps
+//! from enough distinct logs, without relying on the logs the system knows of.
+
+use security_framework_sys::base::errSecDecode;
+use std::time::SystemTime;
+
+use crate::der;
+use crate::json::Value;
+use crate::sct::SignedCertificateTimestamp;
+#[cfg(any(all(target_os = "macos", feature = "OSX_10_12"), target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use crate::sct::LogEntry;
+use crate::{Error, Result};
+
+/// The state of a log in the list, which decides whether its SCTs count.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum LogState {
+    /// The log has applied for inclusion; its SCTs do not count.
+    Pending,
+    /// The log is being monitored for compliance; its SCTs count.
+    Qualified,
+    /// The log is trusted; its SCTs count.
+    Usable,
+    /// The log no longer accepts certificates; its SCTs count.
+    ReadOnly,
+    /// The log is no longer trusted; only SCTs issued before its retirement count.
+    Retired,
+    /// The log was not accepted; its SCTs do not count.
+    Rejected,
+}
+
+impl LogState {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "pending" => Self::Pending,
+            "qualified" => Self::Qualified,
+            "usable" => Self::Usable,
+            "readonly" => Self::ReadOnly,
+            "retired" => Self::Retired,
+            "rejected" => Self::Rejected,
+            _ => return None,
+        })
+    }
+}
+
+/// A single Certificate Transparency log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CtLog {
+    operator: String,
+    description: String,
+    log_id: [u8; 32],
+    key: Vec<u8>,
+    url: String,
+    mmd: u64,
+    state: Option<(LogState, SystemTime)>,
+    temporal_interval: Option<(SystemTime, SystemTime)>,
+}
+
+impl CtLog {
+    /// The name of the organization running the log.
+    #[inline(always)]
+    #[must_use]
+    pub fn operator(&self) -> &str {
+        &self.operator
+    }
+
+    /// A human readable description of the log.
+    #[inline(always)]
+    #[must_use]
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// The SHA-256 hash of the log's public key, which SCTs refer to it by.
+    #[inline(always)]
+    #[must_use]
+    pub fn log_id(&self) -> &[u8; 32] {
+        &self.log_id
+    }
+
+    /// The log's DER encoded `SubjectPublicKeyInfo`.
+    #[inline(always)]
+    #[must_use]
+    pub fn key(&self) -> &[u8] {
+        &self.key
+    }
+
+    /// The log's submission URL.
+    #[inline(always)]
+    #[must_use]
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The maximum merge delay in seconds.
+    #[inline(always)]
+    #[must_use]
+    pub fn mmd(&self) -> u64 {
+        self.mmd
+    }
+
+    /// The log's state and when it was entered.
+    #[inline(always)]
+    #[must_use]
+    pub fn state(&self) -> Option<(LogState, SystemTime)> {
+        self.state
+    }
+
+    /// The range of certificate expiry times the log accepts, if it is sharded.
+    #[inline(always)]
+    #[must_use]
+    pub fn temporal_interval(&self) -> Option<(SystemTime, SystemTime)> {
+        self.temporal_interval
+    }
+
+    /// Returns `true` if the log issued `sct` and, judging by the log's state,
+    /// the SCT counts when checked at `now`. The signature is not checked.
+    #[must_use]
+    pub fn accepts(&self, sct: &SignedCertificateTimestamp, now: SystemTime) -> bool {
+        if sct.log_id() != &self.log_id || sct.timestamp() > now {
+            return false;
+        }
+        match self.state {
+            Some((LogState::Qualified | LogState::Usable | LogState::ReadOnly, _)) => true,
+            Some((LogState::Retired, retired)) => sct.timestamp() < retired,
+            Some((LogState::Pending | LogState::Rejected, _)) | None => false,
+        }
+    }
+
+    /// Returns `true` if the log's signature on `sct` is valid for `entry`.
+    ///
+    /// Logs sign with ECDSA or RSA, using SHA-256.
+    #[cfg(any(all(target_os = "macos", feature = "OSX_10_12"), target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    #[must_use]
+    pub fn verify(&self, sct: &SignedCertificateTimestamp, entry: &LogEntry<'_>) -> bool {
+        use crate::key::{Algorithm, KeyType, SecKey};
+        use core_foundation::data::CFData;
+
+        // TLS HashAlgorithm sha256 with SignatureAlgorithm rsa or ecdsa
+        let (key_type, algorithm) = match (sct.hash_algorithm(), sct.signature_algorithm()) {
+            (4, 1) => (KeyType::rsa(), Algorithm::RSASignatureMessagePKCS1v15SHA256),
+            (4, 3) => (KeyType::ec(), Algorithm::ECDSASignatureMessageX962SHA256),
+            _ => return false,
+        };
+        let key = match public_key_bits(&self.key).and_then(|bits| SecKey::public_from_data(key_type, &CFData::from_buffer(bits)).ok()) {
+            Some(key) => key,
+            None => return false,
+        };
+        key.verify_signature(algorithm, &sct.signed_data(entry), sct.signature())
+            .unwrap_or(false)
+    }
+}
+
+/// Returns the contents of the `subjectPublicKey` of a `SubjectPublicKeyInfo`,
+/// which is what `SecKeyCreateWithData` expects for RSA and EC keys.
+#[cfg_attr(
+    not(any(all(target_os = "macos", feature = "OSX_10_12"), target_os = "ios", target_os = "tvos", target_os = "watchos")),
+    allow(dead_code)
+)]
+fn public_key_bits(spki: &[u8]) -> Option<&[u8]> {
+    let mut spki = der::Reader::new(spki).read_sequence()?;
+    spki.read(der::SEQUENCE)?;
+    match spki.read_contents(der::BIT_STRING)?.split_first()? {
+        (0, bits) => Some(bits),
+        _ => None,
+    }
+}
+
+/// A list of Certificate Transparency logs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CtLogList {
+    version: String,
+    timestamp: Option<SystemTime>,
+    logs: Vec<CtLog>,
+}
+
+impl CtLogList {
+    /// Parses a log list in the JSON v3 format.
+    ///
+    /// Both RFC 6962 logs and static CT API (tiled) logs are loaded. Returns
+    /// `errSecDecode` if the document is malformed.
+    pub fn from_json(json: &[u8]) -> Result<Self> {
+        Self::from_json_inner(json).ok_or_else(|| Error::from_code(errSecDecode))
+    }
+
+    fn from_json_inner(json: &[u8]) -> Option<Self> {
+        let list = Value::parse(json)?;
+        let version = match list.get("version") {
+            Some(version) => version.as_str()?.to_owned(),
+            None => String::new(),
+        };
+        let timestamp = match list.get("log_list_timestamp") {
+            Some(timestamp) => Some(rfc3339(timestamp.as_str()?)?),
+            None => None,
+        };
+        let mut logs = vec![];
+        for operator in list.get("operators")?.as_array()? {
+            let name = operator.get("name")?.as_str()?;
+            let entries = ["logs", "tiled_logs"].iter().filter_map(|key| operator.get(key));
+            for entries in entries {
+                for log in entries.as_array()? {
+                    logs.push(CtLog::from_json(name, log)?);
+                }
+            }
+        }
+        Some(Self { version, timestamp, logs })
+    }
+
+    /// The version of the list.
+    #[inline(always)]
+    #[must_use]
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// The time the list was published.
+    #[inline(always)]
+    #[must_use]
+    pub fn timestamp(&self) -> Option<SystemTime> {
+        self.timestamp
+    }
+
+    /// All logs, in the order of the list.
+    #[inline(always)]
+    #[must_use]
+    pub fn logs(&self) -> &[CtLog] {
+        &self.logs
+    }
+
+    /// Returns the log with the given ID.
+    #[must_use]
+    pub fn find(&self, log_id: &[u8; 32]) -> Option<&CtLog> {
+        self.logs.iter().find(|log| log.log_id() == log_id)
+    }
+
+    /// Returns the number of distinct logs that issued a valid SCT for `entry`
+    /// among `scts`, checked at `now`.
+    ///
+    /// An SCT is valid if its log is in the list, the log's state lets it count
+    /// (see [`CtLog::accepts`]) and the log's signature verifies.
+    #[cfg(any(all(target_os = "macos", feature = "OSX_10_12"), target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    #[must_use]
+    pub fn count_valid(&self, scts: &[SignedCertificateTimestamp], entry: &LogEntry<'_>, now: SystemTime) -> usize {
+        self.count_logs(scts, now, |log, sct| log.verify(sct, entry))
+    }
+
+    /// Returns the number of distinct logs that issued a valid SCT embedded in
+    /// the DER encoded `certificate`, which was issued by `issuer`.
+    #[cfg(any(all(target_os = "macos", feature = "OSX_10_12"), target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub fn count_embedded(&self, certificate: &[u8], issuer: &[u8], now: SystemTime) -> Result<usize> {
+        let scts = SignedCertificateTimestamp::from_certificate(certificate)?;
+        if scts.is_empty() {
+            return Ok(0);
+        }
+        Ok(self.count_valid(&scts, &LogEntry::precert(certificate, issuer)?, now))
+    }
+
+    #[cfg_attr(
+        not(any(all(target_os = "macos", feature = "OSX_10_12"), target_os = "ios", target_os = "tvos", target_os = "watchos")),
+        allow(dead_code)
+    )]
+    fn count_logs<F>(&self, scts: &[SignedCertificateTimestamp], now: SystemTime, verify: F) -> usize
+    where
+        F: Fn(&CtLog, &SignedCertificateTimestamp) -> bool,
+    {
+        let mut logs: Vec<&[u8; 32]> = vec![];
+        for sct in scts {
+            if logs.contains(&sct.log_id()) {
+                continue;
+            }
+            if let Some(log) = self.find(sct.log_id()) {
+                if log.accepts(sct, now) && verify(log, sct) {
+                    logs.push(log.log_id());
+                }
+            }
+        }
+        logs.len()
+    }
+}
+
+impl CtLog {
+    fn from_json(operator: &str, log: &Value) -> Option<Self> {
+        let mut log_id = [0; 32];
+        let id = base64(log.get("log_id")?.as_str()?)?;
+        if id.len() != 32 {
+            return None;
+        }
+        log_id.copy_from_slice(&id);
+        let url = log.get("url").or_else(|| log.get("submission_url"))?.as_str()?;
+        let state = match log.get("state").map(|state| state.as_object()) {
+            Some(Some([(name, details)])) => {
+                Some((LogState::from_name(name)?, rfc3339(details.get("timestamp")?.as_str()?)?))
+            }
+            Some(_) => return None,
+            None => None,
+        };
+        let temporal_interval = match log.get("temporal_interval") {
+            Some(interval) => Some((
+                rfc3339(interval.get("start_inclusive")?.as_str()?)?,
+                rfc3339(interval.get("end_exclusive")?.as_str()?)?,
+            )),
+            None => None,
+        };
+        Some(Self {
+            operator: operator.to_owned(),
+            description: log.get("description").and_then(Value::as_str).unwrap_or_default().to_owned(),
+            log_id,
+            key: base64(log.get("key")?.as_str()?)?,
+            url: url.to_owned(),
+            mmd: log.get("mmd").map_or(Some(0), Value::as_u64)?,
+            state,
+            temporal_interval,
+        })
+    }
+}
+
+/// Parses an RFC 3339 timestamp in UTC, such as `2024-01-01T00:00:00Z`.
+fn rfc3339(s: &str) -> Option<SystemTime> {
+    let b = s.as_bytes();
+    if b.len() < 20 || b[4] != b'-' || b[7] != b'-' || !matches!(b[10], b'T' | b't') || b[13] != b':' || b[16] != b':' {
+        return None;
+    }
+    // the same fields as a GeneralizedTime, in a different layout
+    let mut time = [&b[..4], &b[5..7], &b[8..10], &b[11..13], &b[14..16], &b[17..]].concat();
+    if let Some(zone) = time.last_mut() {
+        zone.make_ascii_uppercase();
+    }
+    der::generalized_time(&time)
+}
+
+/// Decodes standard, padded base64.
+fn base64(s: &str) -> Option<Vec<u8>> {
+    let s = s.as_bytes();
+    if s.len() % 4 != 0 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(s.len() / 4 * 3);
+    for (i, chunk) in s.chunks(4).enumerate() {
+        let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+        if padding > 2 || (padding > 0 && i != s.len() / 4 - 1) {
+            return None;
+        }
+        let mut n = 0u32;
+        for &c in &chunk[..4 - padding] {
+            let value = match c {
+                b'A'..=b'Z' => c - b'A',
+                b'a'..=b'z' => c - b'a' + 26,
+                b'0'..=b'9' => c - b'0' + 52,
+                b'+' => 62,
+                b'/' => 63,
+                _ => return None,
+            };
+            n = (n << 6) | u32::from(value);
+        }
+        n <<= 6 * padding;
+        out.extend_from_slice(&n.to_be_bytes()[1..4 - padding]);
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sct::test::{list, sct};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn time(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn parse_log_list() {
+        let list = CtLogList::from_json(include_bytes!("../test/ct/log_list.json")).unwrap();
+        assert_eq!("1.0", list.version());
+        assert_eq!(Some(time(1_790_812_800)), list.timestamp());
+        assert_eq!(3, list.logs().len());
+
+        let log = &list.logs()[1];
+        assert_eq!("Test Operator", log.operator());
+        assert_eq!("Test RSA Log", log.description());
+        assert_eq!("https://ct.example.com/b/", log.url());
+        assert_eq!(86400, log.mmd());
+        assert_eq!(Some((LogState::Qualified, time(1_748_736_000) + Duration::from_millis(500))), log.state());
+        assert_eq!(Some((time(1_767_225_600), time(1_798_761_600))), log.temporal_interval());
+        assert_eq!(Some(log), list.find(log.log_id()));
+        assert_eq!(None, list.find(&[0; 32]));
+        assert_eq!(Some(&0x30), log.key().first());
+        assert_eq!("Other Operator", list.logs()[2].operator());
+
+        assert!(CtLogList::from_json(b"{}").is_err());
+        assert!(CtLogList::from_json(br#"{"operators": [{"name": "x", "logs": [{"log_id": "AAAA", "key": "", "url": ""}]}]}"#).is_err());
+        let empty = CtLogList::from_json(br#"{"operators": [{"name": "x", "logs": []}]}"#).unwrap();
+        assert!(empty.logs().is_empty());
+        assert_eq!(None, empty.timestamp());
+    }
+
+    #[test]
+    fn count_logs() {
+        let mut logs = CtLogList::from_json(include_bytes!("../test/ct/log_list.json")).unwrap();
+        for (log, id) in logs.logs.iter_mut().zip(1..) {
+            log.log_id = [id; 32];
+        }
+        // logs 1 and 2 count, log 3 retired at 1_756_684_800
+        let scts = SignedCertificateTimestamp::parse_list(&list(&[
+            sct(1, 1_767_225_601_000),
+            sct(1, 1_767_225_602_000),
+            sct(2, 1_767_225_603_000),
+            sct(3, 1_767_225_604_000),
+            sct(4, 1_767_225_605_000),
+        ]))
+        .unwrap();
+        let now = time(1_790_000_000);
+        assert_eq!(2, logs.count_logs(&scts, now, |_, _| true));
+        assert_eq!(1, logs.count_logs(&scts, now, |log, _| log.log_id() == &[2; 32]));
+        assert_eq!(0, logs.count_logs(&scts, time(1_767_225_600), |_, _| true));
+
+        let early = SignedCertificateTimestamp::parse(&sct(3, 1_756_684_799_000)).unwrap();
+        assert!(logs.logs()[2].accepts(&early, now));
+        assert!(!logs.logs()[2].accepts(&scts[3], now));
+        assert!(!logs.logs()[0].accepts(&scts[2], now));
+    }
+
+    #[test]
+    fn parse_time() {
+        assert_eq!(Some(time(1_767_225_600)), rfc3339("2026-01-01T00:00:00Z"));
+        assert_eq!(Some(time(1_767_225_600) + Duration::from_millis(250)), rfc3339("2026-01-01t00:00:00.25z"));
+        assert_eq!(None, rfc3339("2026-01-01T00:00:00+01:00"));
+        assert_eq!(None, rfc3339("2026-01-01 00:00:00Z"));
+    }
+
+    #[test]
+    fn decode_base64() {
+        assert_eq!(Some(b"".to_vec()), base64(""));
+        assert_eq!(Some(b"f".to_vec()), base64("Zg=="));
+        assert_eq!(Some(b"fo".to_vec()), base64("Zm8="));
+        assert_eq!(Some(b"foo".to_vec()), base64("Zm9v"));
+        assert_eq!(Some(vec![0xfb, 0xff]), base64("+/8="));
+        assert_eq!(None, base64("Zg="));
+        assert_eq!(None, base64("Zg==Zm9v"));
+        assert_eq!(None, base64("Z==="));
+        assert_eq!(None, base64("Zm9-"));
+    }
+
+    #[test]
+    #[cfg(any(all(target_os = "macos", feature = "OSX_10_12"), target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    fn count_embedded() {
+        let list = CtLogList::from_json(include_bytes!("../test/ct/log_list.json")).unwrap();
+        let leaf = include_bytes!("../test/ct/leaf.der");
+        let root = include_bytes!("../test/ct/root.der");
+        let now = time(1_790_000_000);
+        assert_eq!(2, list.count_embedded(leaf, root, now).unwrap());
+        assert_eq!(0, list.count_embedded(leaf, leaf, now).unwrap());
+        assert_eq!(0, list.count_embedded(root, root, now).unwrap());
+    }
+}
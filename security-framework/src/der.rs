@@ -0,0 +1,394 @@
+//! A minimal DER reader for the parts of X.509 and related structures that
+//! are inspected without going through the Security framework.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub(crate) const BOOLEAN: u8 = 0x01;
+pub(crate) const INTEGER: u8 = 0x02;
+pub(crate) const BIT_STRING: u8 = 0x03;
+pub(crate) const OCTET_STRING: u8 = 0x04;
+pub(crate) const OID: u8 = 0x06;
+pub(crate) const ENUMERATED: u8 = 0x0a;
+pub(crate) const GENERALIZED_TIME: u8 = 0x18;
+pub(crate) const SEQUENCE: u8 = 0x30;
+
+/// The tag of a constructed, context-specific field, which is what
+/// explicit tagging and implicitly tagged `SEQUENCE`s produce.
+#[inline]
+pub(crate) const fn explicit(number: u8) -> u8 {
+    0xa0 | number
+}
+
+/// The tag of an implicitly tagged, primitive context-specific field.
+#[inline]
+pub(crate) const fn implicit(number: u8) -> u8 {
+    0x80 | number
+}
+
+/// OID 2.5.29.14, the subject key identifier extension.
+const OID_SUBJECT_KEY_IDENTIFIER: &[u8] = &[0x55, 0x1d, 0x0e];
+/// OID 2.5.29.35, the authority key identifier extension.
+const OID_AUTHORITY_KEY_IDENTIFIER: &[u8] = &[0x55, 0x1d, 0x23];
+/// OID 1.3.6.1.4.1.11129.2.4.2, the embedded signed certificate timestamp list.
+const OID_SCT_LIST: &[u8] = &[0x2b, 0x06, 0x01, 0x04, 0x01, 0xd6, 0x79, 0x02, 0x04, 0x02];
+
+/// A single tag-length-value element.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct Tlv<'a> {
+    pub tag: u8,
+    pub contents: &'a [u8],
+    /// The whole element, including its header.
+    pub raw: &'a [u8],
+}
+
+/// Reads a sequence of DER elements out of a buffer.
+///
+/// Only single-byte tags and definite lengths are supported, which covers
+/// everything DER permits in the structures this crate looks at.
+#[derive(Debug, Clone)]
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    #[inline]
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns the tag of the next element without consuming it.
+    #[inline]
+    pub fn peek_tag(&self) -> Option<u8> {
+        self.data.first().copied()
+    }
+
+    /// Reads the next element, whatever its tag.
+    pub fn read_any(&mut self) -> Option<Tlv<'a>> {
+        let (&tag, rest) = self.data.split_first()?;
+        // high tag numbers are never used by the structures we parse
+        if tag & 0x1f == 0x1f {
+            return None;
+        }
+        let (&first, mut rest) = rest.split_first()?;
+        let len = if first & 0x80 == 0 {
+            usize::from(first)
+        } else {
+            let count = usize::from(first & 0x7f);
+            if count == 0 || count > std::mem::size_of::<usize>() || rest.len() < count {
+                return None;
+            }
+            let (bytes, remainder) = rest.split_at(count);
+            rest = remainder;
+            bytes.iter().fold(0, |len, &b| (len << 8) | usize::from(b))
+        };
+        if rest.len() < len {
+            return None;
+        }
+        let header = self.data.len() - rest.len();
+        let raw = &self.data[..header + len];
+        let contents = &rest[..len];
+        self.data = &rest[len..];
+        Some(Tlv { tag, contents, raw })
+    }
+
+    /// Reads the next element, which must have the given tag.
+    #[inline]
+    pub fn read(&mut self, tag: u8) -> Option<Tlv<'a>> {
+        if self.peek_tag()? == tag {
+            self.read_any()
+        } else {
+            None
+        }
+    }
+
+    /// Reads the contents of the next element, which must have the given tag.
+    #[inline]
+    pub fn read_contents(&mut self, tag: u8) -> Option<&'a [u8]> {
+        self.read(tag).map(|tlv| tlv.contents)
+    }
+
+    /// Reads the next element if it has the given tag.
+    ///
+    /// A malformed element is left unread, so the caller's next read fails.
+    #[inline]
+    pub fn read_optional(&mut self, tag: u8) -> Option<Tlv<'a>> {
+        if self.peek_tag() == Some(tag) {
+            self.read_any()
+        } else {
+            None
+        }
+    }
+
+    /// Reads a `SEQUENCE` and returns a reader over its contents.
+    #[inline]
+    pub fn read_sequence(&mut self) -> Option<Reader<'a>> {
+        self.read_contents(SEQUENCE).map(Reader::new)
+    }
+}
+
+/// Parses the contents of a DER `GeneralizedTime`, `YYYYMMDDHHMMSS[.f+]Z`.
+pub(crate) fn generalized_time(contents: &[u8]) -> Option<SystemTime> {
+    let (digits, fraction) = match contents.split_last()? {
+        (b'Z', rest) if rest.len() >= 14 => rest.split_at(14),
+        _ => return None,
+    };
+    if !digits.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    let nanos = match fraction.split_first() {
+        None => 0,
+        Some((b'.', frac)) if !frac.is_empty() && frac.len() <= 9 && frac.iter().all(u8::is_ascii_digit) => {
+            frac.iter().fold(0, |n, d| n * 10 + u32::from(d - b'0')) * 10u32.pow(9 - frac.len() as u32)
+        }
+        _ => return None,
+    };
+    let number = |range: std::ops::Range<usize>| digits[range].iter().fold(0, |n, d| n * 10 + i64::from(d - b'0'));
+    let (year, month, day) = (number(0..4), number(4..6), number(6..8));
+    let (hour, minute, second) = (number(8..10), number(10..12), number(12..14));
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+
+    // days since the epoch in the proleptic Gregorian calendar
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    let seconds = days * 86400 + hour * 3600 + minute * 60 + second;
+    Some(if seconds >= 0 {
+        UNIX_EPOCH + Duration::new(seconds as u64, nanos)
+    } else {
+        UNIX_EPOCH - Duration::from_secs(seconds.unsigned_abs()) + Duration::from_nanos(u64::from(nanos))
+    })
+}
+
+/// The fields of an X.509 certificate used for path building and channel binding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Certificate<'a> {
+    /// The DER encoded issuer `Name`.
+    pub issuer: &'a [u8],
+    /// The DER encoded subject `Name`.
+    pub subject: &'a [u8],
+    /// The content octets of the outer signature algorithm OID.
+    pub signature_algorithm: &'a [u8],
+    /// The encoded parameters of the outer signature algorithm, if any.
+    pub signature_parameters: Option<&'a [u8]>,
+    /// The DER encoded `SubjectPublicKeyInfo`.
+    pub subject_public_key_info: &'a [u8],
+    pub subject_key_identifier: Option<&'a [u8]>,
+    pub authority_key_identifier: Option<&'a [u8]>,
+    /// The TLS encoded list of embedded signed certificate timestamps.
+    pub signed_certificate_timestamps: Option<&'a [u8]>,
+}
+
+impl<'a> Certificate<'a> {
+    /// Parses a DER encoded certificate.
+    pub fn parse(der: &'a [u8]) -> Option<Self> {
+        let mut outer = Reader::new(der);
+        let mut cert = outer.read_sequence()?;
+        if !outer.is_empty() {
+            return None;
+        }
+        let mut tbs = cert.read_sequence()?;
+        let mut algorithm = cert.read_sequence()?;
+        let signature_algorithm = algorithm.read_contents(OID)?;
+        let signature_parameters = algorithm.read_any().map(|tlv| tlv.raw);
+
+        tbs.read_optional(explicit(0));
+        tbs.read(INTEGER)?;
+        tbs.read(SEQUENCE)?;
+        let issuer = tbs.read(SEQUENCE)?.raw;
+        tbs.read(SEQUENCE)?;
+        let subject = tbs.read(SEQUENCE)?.raw;
+        let subject_public_key_info = tbs.read(SEQUENCE)?.raw;
+        tbs.read_optional(implicit(1));
+        tbs.read_optional(implicit(2));
+
+        let mut subject_key_identifier = None;
+        let mut authority_key_identifier = None;
+        let mut signed_certificate_timestamps = None;
+        if let Some(extensions) = tbs.read_optional(explicit(3)) {
+            let mut extensions = Reader::new(extensions.contents).read_sequence()?;
+            while !extensions.is_empty() {
+                let mut extension = extensions.read_sequence()?;
+                let oid = extension.read_contents(OID)?;
+                extension.read_optional(BOOLEAN);
+                let value = extension.read_contents(OCTET_STRING)?;
+                if oid == OID_SUBJECT_KEY_IDENTIFIER {
+                    subject_key_identifier = Some(Reader::new(value).read_contents(OCTET_STRING)?);
+                } else if oid == OID_AUTHORITY_KEY_IDENTIFIER {
+                    let mut aki = Reader::new(value).read_sequence()?;
+                    authority_key_identifier = aki.read_optional(implicit(0)).map(|tlv| tlv.contents);
+                } else if oid == OID_SCT_LIST {
+                    signed_certificate_timestamps = Some(Reader::new(value).read_contents(OCTET_STRING)?);
+                }
+            }
+        }
+        if !tbs.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            issuer,
+            subject,
+            signature_algorithm,
+            signature_parameters,
+            subject_public_key_info,
+            subject_key_identifier,
+            authority_key_identifier,
+            signed_certificate_timestamps,
+        })
+    }
+
+    /// Returns `true` if the certificate names itself as its issuer.
+    #[inline]
+    pub fn is_self_issued(&self) -> bool {
+        self.issuer == self.subject
+            && match (self.authority_key_identifier, self.subject_key_identifier) {
+                (Some(aki), Some(ski)) => aki == ski,
+                _ => true,
+            }
+    }
+
+    /// Returns `true` if `issuer` may have issued this certificate, judging
+    /// by names and key identifiers. Signatures are not checked.
+    #[inline]
+    pub fn is_issued_by(&self, issuer: &Certificate<'_>) -> bool {
+        self.issuer == issuer.subject
+            && match (self.authority_key_identifier, issuer.subject_key_identifier) {
+                (Some(aki), Some(ski)) => aki == ski,
+                _ => true,
+            }
+    }
+}
+
+/// Returns the `TBSCertificate` of a certificate with the embedded signed
+/// certificate timestamp list removed, which is what logs sign for an SCT
+/// embedded in the certificate (RFC 6962, section 3.2).
+pub(crate) fn precert_tbs_certificate(der: &[u8]) -> Option<Vec<u8>> {
+    let mut tbs = Reader::new(der).read_sequence()?.read_sequence()?;
+    let mut contents = vec![];
+    while !tbs.is_empty() {
+        let field = tbs.read_any()?;
+        if field.tag != explicit(3) {
+            contents.extend_from_slice(field.raw);
+            continue;
+        }
+        let mut extensions = Reader::new(field.contents).read_sequence()?;
+        let mut kept = vec![];
+        while !extensions.is_empty() {
+            let extension = extensions.read(SEQUENCE)?;
+            if Reader::new(extension.contents).read_contents(OID)? != OID_SCT_LIST {
+                kept.extend_from_slice(extension.raw);
+            }
+        }
+        if !kept.is_empty() {
+            write(&mut contents, explicit(3), &encode(SEQUENCE, &kept));
+        }
+    }
+    Some(encode(SEQUENCE, &contents))
+}
+
+/// Encodes a single element.
+pub(crate) fn encode(tag: u8, contents: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(contents.len() + 6);
+    write(&mut out, tag, contents);
+    out
+}
+
+fn write(out: &mut Vec<u8>, tag: u8, contents: &[u8]) {
+    out.push(tag);
+    let len = contents.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let skip = bytes.iter().take_while(|&&b| b == 0).count();
+        out.push(0x80 | (bytes.len() - skip) as u8);
+        out.extend_from_slice(&bytes[skip..]);
+    }
+    out.extend_from_slice(contents);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reader_lengths() {
+        let mut long = vec![OCTET_STRING, 0x82, 0x01, 0x00];
+        long.extend_from_slice(&[0xab; 256]);
+        long.extend_from_slice(&[INTEGER, 0x01, 0x05]);
+        let mut reader = Reader::new(&long);
+        assert_eq!(256, reader.read_contents(OCTET_STRING).unwrap().len());
+        assert_eq!(None, reader.read(OCTET_STRING));
+        assert_eq!(&[0x05][..], reader.read_contents(INTEGER).unwrap());
+        assert!(reader.is_empty());
+
+        assert_eq!(None, Reader::new(&[OCTET_STRING, 0x03, 0x00]).read_any());
+        assert_eq!(None, Reader::new(&[OCTET_STRING, 0x80]).read_any());
+    }
+
+    #[test]
+    fn parse_generalized_time() {
+        let time = |s: &str| generalized_time(s.as_bytes()).map(|t| t.duration_since(UNIX_EPOCH).unwrap());
+        assert_eq!(Some(Duration::from_secs(0)), time("19700101000000Z"));
+        assert_eq!(Some(Duration::from_secs(1_792_342_552)), time("20261018165552Z"));
+        assert_eq!(Some(Duration::from_secs(951_782_400)), time("20000229000000Z"));
+        assert_eq!(Some(Duration::new(1_759_320_000, 250_000_000)), time("20251001120000.25Z"));
+        assert_eq!(None, time("20251001120000"));
+        assert_eq!(None, time("20251301120000Z"));
+        assert_eq!(None, time("2025100112000Z"));
+        assert_eq!(None, time("20251001120000.Z"));
+    }
+
+    #[test]
+    fn parse_certificate() {
+        let leaf = Certificate::parse(include_bytes!("../test/chain/leaf.der")).unwrap();
+        let intermediate = Certificate::parse(include_bytes!("../test/chain/intermediate.der")).unwrap();
+        let root = Certificate::parse(include_bytes!("../test/chain/root.der")).unwrap();
+        assert!(leaf.is_issued_by(&intermediate));
+        assert!(intermediate.is_issued_by(&root));
+        assert!(!leaf.is_issued_by(&root));
+        assert!(root.is_self_issued());
+        assert!(!intermediate.is_self_issued());
+        // ecdsa-with-SHA256
+        assert_eq!(&[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02][..], leaf.signature_algorithm);
+        assert_eq!(None, leaf.signed_certificate_timestamps);
+    }
+
+    #[test]
+    fn precert_tbs() {
+        let der = include_bytes!("../test/ct/leaf.der");
+        let cert = Certificate::parse(der).unwrap();
+        assert!(cert.signed_certificate_timestamps.is_some());
+        assert_eq!(
+            &include_bytes!("../test/ct/precert_tbs.der")[..],
+            &precert_tbs_certificate(der).unwrap()[..]
+        );
+
+        let root = include_bytes!("../test/ct/root.der");
+        let tbs = Reader::new(root).read_sequence().unwrap().read(SEQUENCE).unwrap().raw;
+        assert_eq!(tbs, &precert_tbs_certificate(root).unwrap()[..]);
+
+        let long = encode(OCTET_STRING, &[0; 300]);
+        assert_eq!(&[OCTET_STRING, 0x82, 0x01, 0x2c][..], &long[..4]);
+        assert_eq!(&[0; 300][..], Reader::new(&long).read_contents(OCTET_STRING).unwrap());
+    }
+
+    #[test]
+    fn parse_v1_certificate() {
+        let cert = Certificate::parse(include_bytes!("../test/server.der")).unwrap();
+        assert!(cert.is_self_issued());
+        assert_eq!(None, cert.subject_key_identifier);
+        assert!(Certificate::parse(&include_bytes!("../test/server.der")[1..]).is_none());
+    }
+}
@@ -0,0 +1,190 @@
+//! Minimal Grand Central Dispatch queue support for the asynchronous APIs.
+
+use security_framework_sys::base::dispatch_queue_t;
+#[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use security_framework_sys::base::dispatch_data_t;
+use std::ffi::CString;
+use std::fmt;
+use std::os::raw::{c_char, c_long, c_ulong, c_void};
+use std::ptr;
+
+extern "C" {
+    fn dispatch_get_global_queue(identifier: c_long, flags: c_ulong) -> dispatch_queue_t;
+    fn dispatch_queue_create(label: *const c_char, attr: *const c_void) -> dispatch_queue_t;
+    fn dispatch_retain(object: *mut c_void);
+    fn dispatch_release(object: *mut c_void);
+    fn dispatch_async_f(
+        queue: dispatch_queue_t,
+        context: *mut c_void,
+        work: unsafe extern "C" fn(*mut c_void),
+    );
+    #[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    fn dispatch_data_create(
+        buffer: *const c_void,
+        size: usize,
+        queue: dispatch_queue_t,
+        destructor: *const c_void,
+    ) -> dispatch_data_t;
+    #[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    fn dispatch_data_create_map(
+        data: dispatch_data_t,
+        buffer: *mut *const c_void,
+        size: *mut usize,
+    ) -> dispatch_data_t;
+}
+
+/// A dispatch queue on which asynchronous work and its completion handlers run.
+pub struct DispatchQueue(dispatch_queue_t);
+
+unsafe impl Sync for DispatchQueue {}
+unsafe impl Send for DispatchQueue {}
+
+impl fmt::Debug for DispatchQueue {
+    #[cold]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_tuple("DispatchQueue").field(&self.0).finish()
+    }
+}
+
+impl DispatchQueue {
+    /// Returns the global concurrent queue with the default quality of service.
+    #[must_use]
+    pub fn global() -> Self {
+        // Global queues ignore retain and release, so there is no extra reference to take.
+        unsafe { Self(dispatch_get_global_queue(0, 0)) }
+    }
+
+    /// Creates a new serial queue with the given label.
+    #[must_use]
+    pub fn serial(label: &str) -> Self {
+        let label = CString::new(label.replace('\0', "")).unwrap_or_default();
+        unsafe { Self(dispatch_queue_create(label.as_ptr(), ptr::null())) }
+    }
+
+    /// Wraps a raw `dispatch_queue_t`, taking an additional reference to it.
+    ///
+    /// # Safety
+    ///
+    /// `queue` must be a valid dispatch queue.
+    #[must_use]
+    pub unsafe fn wrap_under_get_rule(queue: dispatch_queue_t) -> Self {
+        dispatch_retain(queue.cast());
+        Self(queue)
+    }
+
+    /// Returns the raw `dispatch_queue_t`.
+    #[inline(always)]
+    #[must_use]
+    pub fn as_raw(&self) -> dispatch_queue_t {
+        self.0
+    }
+
+    /// Submits a closure for asynchronous execution on the queue.
+    pub fn exec_async<F>(&self, work: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        unsafe extern "C" fn trampoline<F: FnOnce()>(context: *mut c_void) {
+            let work = Box::from_raw(context.cast::<F>());
+            work();
+        }
+
+        let context = Box::into_raw(Box::new(work));
+        unsafe { dispatch_async_f(self.0, context.cast(), trampoline::<F>) }
+    }
+}
+
+impl Clone for DispatchQueue {
+    #[inline]
+    fn clone(&self) -> Self {
+        unsafe { Self::wrap_under_get_rule(self.0) }
+    }
+}
+
+impl Drop for DispatchQueue {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { dispatch_release(self.0.cast()) }
+    }
+}
+
+/// An immutable buffer, as passed to and returned by the Network.framework
+/// TLS APIs.
+#[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+pub(crate) struct DispatchData(dispatch_data_t);
+
+#[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+impl DispatchData {
+    /// Copies `bytes` into a new buffer.
+    pub(crate) fn new(bytes: &[u8]) -> Self {
+        // a null destructor makes libdispatch copy the bytes
+        unsafe { Self(dispatch_data_create(bytes.as_ptr().cast(), bytes.len(), ptr::null_mut(), ptr::null())) }
+    }
+
+    /// Takes ownership of a buffer returned by a `create` or `copy` function.
+    pub(crate) unsafe fn wrap_under_create_rule(data: dispatch_data_t) -> Option<Self> {
+        if data.is_null() {
+            None
+        } else {
+            Some(Self(data))
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn as_raw(&self) -> dispatch_data_t {
+        self.0
+    }
+
+    /// Copies the contents of the buffer.
+    pub(crate) fn to_vec(&self) -> Vec<u8> {
+        let mut buffer = ptr::null();
+        let mut size = 0;
+        unsafe {
+            let map = dispatch_data_create_map(self.0, &mut buffer, &mut size);
+            let bytes = if size == 0 {
+                Vec::new()
+            } else {
+                std::slice::from_raw_parts(buffer.cast::<u8>(), size).to_vec()
+            };
+            dispatch_release(map.cast());
+            bytes
+        }
+    }
+}
+
+#[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+impl Drop for DispatchData {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { dispatch_release(self.0.cast()) }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn exec_async() {
+        let (tx, rx) = mpsc::channel();
+        let queue = DispatchQueue::serial("security-framework.test");
+        queue.exec_async(move || tx.send(42).unwrap());
+        assert_eq!(42, rx.recv().unwrap());
+    }
+
+    #[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    #[test]
+    fn data_round_trip() {
+        assert_eq!(b"hello".to_vec(), DispatchData::new(b"hello").to_vec());
+        assert!(DispatchData::new(b"").to_vec().is_empty());
+    }
+
+    #[test]
+    fn global_clone() {
+        let (tx, rx) = mpsc::channel();
+        let queue = DispatchQueue::global().clone();
+        queue.exec_async(move || tx.send(()).unwrap());
+        rx.recv().unwrap();
+    }
+}
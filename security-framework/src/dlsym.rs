@@ -5,12 +5,10 @@ use std::marker;
 use std::mem;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
-use libc;
-
 macro_rules! dlsym {
     (fn $name:ident($($t:ty),*) -> $ret:ty) => (
         #[allow(bad_style)]
-        static $name: $crate::dlsym::DlSym<unsafe extern fn($($t),*) -> $ret> =
+        static $name: $crate::dlsym::DlSym<unsafe extern "C" fn($($t),*) -> $ret> =
             $crate::dlsym::DlSym {
                 name: concat!(stringify!($name), "\0"),
                 addr: ::std::sync::atomic::AtomicUsize::new(0),
@@ -43,7 +41,7 @@ impl<F> DlSym<F> {
 
 unsafe fn fetch(name: &str) -> usize {
     assert_eq!(name.as_bytes()[name.len() - 1], 0);
-    match libc::dlsym(libc::RTLD_DEFAULT, name.as_ptr() as *const _) as usize {
+    match libc::dlsym(libc::RTLD_DEFAULT, name.as_ptr().cast()) as usize {
         0 => 1,
         n => n,
     }
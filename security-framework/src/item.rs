@@ -144,6 +144,7 @@ pub struct ItemSearchOptions {
     access_group: Option<CFString>,
     pub_key_hash: Option<CFData>,
     app_label: Option<CFData>,
+    issuers: Option<CFArray<CFData>>,
 }
 
 #[cfg(target_os = "macos")]
@@ -275,7 +276,19 @@ impl ItemSearchOptions {
         self
     }
 
+    /// Search only for certificates and identities issued by, or chaining
+    /// up to, one of the given DER encoded distinguished names.
+    pub fn issuers<T: AsRef<[u8]>>(&mut self, issuers: &[T]) -> &mut Self {
+        let issuers = issuers
+            .iter()
+            .map(|issuer| CFData::from_buffer(issuer.as_ref()))
+            .collect::<Vec<_>>();
+        self.issuers = Some(CFArray::from_CFTypes(&issuers));
+        self
+    }
+
     /// Search for objects.
+    #[allow(clippy::too_many_lines)]
     pub fn search(&self) -> Result<Vec<SearchResult>> {
         unsafe {
             let mut params = vec![];
@@ -372,6 +385,13 @@ impl ItemSearchOptions {
                 ));
             }
 
+            if let Some(ref issuers) = self.issuers {
+                params.push((
+                    CFString::wrap_under_get_rule(kSecMatchIssuers),
+                    issuers.as_CFType(),
+                ));
+            }
+
             let params = CFDictionary::from_CFType_pairs(&params);
 
             let mut ret = ptr::null();
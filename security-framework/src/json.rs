@@ -0,0 +1,264 @@
+//! A minimal JSON reader for the documents this crate loads, such as
+//! Certificate Transparency log lists.
+
+/// Nesting beyond this is rejected rather than risking the stack.
+const MAX_DEPTH: usize = 64;
+
+/// A parsed JSON value.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    /// Members in document order; lookups return the first match.
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Parses a complete UTF-8 document.
+    pub fn parse(json: &[u8]) -> Option<Self> {
+        let json = std::str::from_utf8(json).ok()?;
+        let mut parser = Parser { rest: json.as_bytes(), depth: 0 };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.rest.is_empty() {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the member named `key` of an object.
+    pub fn get(&self, key: &str) -> Option<&Self> {
+        match self {
+            Self::Object(members) => members.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_array(&self) -> Option<&[Self]> {
+        match self {
+            Self::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_object(&self) -> Option<&[(String, Self)]> {
+        match self {
+            Self::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+
+    /// Returns a number that is a non-negative integer.
+    #[inline]
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            Self::Number(n) if n >= 0. && n.fract() == 0. && n < 2f64.powi(64) => Some(n as u64),
+            _ => None,
+        }
+    }
+}
+
+struct Parser<'a> {
+    rest: &'a [u8],
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while let Some((b' ' | b'\t' | b'\n' | b'\r', rest)) = self.rest.split_first() {
+            self.rest = rest;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.rest.first().copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let (&b, rest) = self.rest.split_first()?;
+        self.rest = rest;
+        Some(b)
+    }
+
+    fn literal(&mut self, literal: &[u8], value: Value) -> Option<Value> {
+        self.rest = self.rest.strip_prefix(literal)?;
+        Some(value)
+    }
+
+    fn value(&mut self) -> Option<Value> {
+        match self.peek()? {
+            b'{' => self.nested(Self::object),
+            b'[' => self.nested(Self::array),
+            b'"' => self.string().map(Value::String),
+            b't' => self.literal(b"true", Value::Bool(true)),
+            b'f' => self.literal(b"false", Value::Bool(false)),
+            b'n' => self.literal(b"null", Value::Null),
+            _ => self.number().map(Value::Number),
+        }
+    }
+
+    fn nested(&mut self, parse: fn(&mut Self) -> Option<Value>) -> Option<Value> {
+        if self.depth == MAX_DEPTH {
+            return None;
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn object(&mut self) -> Option<Value> {
+        self.next();
+        let mut members = vec![];
+        if self.peek()? == b'}' {
+            self.next();
+            return Some(Value::Object(members));
+        }
+        loop {
+            if self.peek()? != b'"' {
+                return None;
+            }
+            let name = self.string()?;
+            if self.peek()? != b':' {
+                return None;
+            }
+            self.next();
+            members.push((name, self.value()?));
+            match self.peek()? {
+                b',' => self.next(),
+                b'}' => {
+                    self.next();
+                    return Some(Value::Object(members));
+                }
+                _ => return None,
+            };
+        }
+    }
+
+    fn array(&mut self) -> Option<Value> {
+        self.next();
+        let mut values = vec![];
+        if self.peek()? == b']' {
+            self.next();
+            return Some(Value::Array(values));
+        }
+        loop {
+            values.push(self.value()?);
+            match self.peek()? {
+                b',' => self.next(),
+                b']' => {
+                    self.next();
+                    return Some(Value::Array(values));
+                }
+                _ => return None,
+            };
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.next();
+        let mut s = Vec::new();
+        loop {
+            match self.next()? {
+                b'"' => return String::from_utf8(s).ok(),
+                b'\\' => {
+                    let c = match self.next()? {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.escaped_char()?,
+                        _ => return None,
+                    };
+                    s.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                0..=0x1f => return None,
+                b => s.push(b),
+            }
+        }
+    }
+
+    fn escaped_char(&mut self) -> Option<char> {
+        let high = self.hex4()?;
+        if !(0xd800..0xdc00).contains(&high) {
+            return char::from_u32(high);
+        }
+        // a high surrogate must be followed by an escaped low surrogate
+        self.rest = self.rest.strip_prefix(b"\\u")?;
+        let low = self.hex4()?;
+        if !(0xdc00..0xe000).contains(&low) {
+            return None;
+        }
+        char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00))
+    }
+
+    fn hex4(&mut self) -> Option<u32> {
+        if self.rest.len() < 4 {
+            return None;
+        }
+        let (digits, rest) = self.rest.split_at(4);
+        self.rest = rest;
+        digits
+            .iter()
+            .try_fold(0, |n, &d| char::from(d).to_digit(16).map(|d| (n << 4) | d))
+    }
+
+    fn number(&mut self) -> Option<f64> {
+        let len = self
+            .rest
+            .iter()
+            .position(|b| !matches!(b, b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E'))
+            .unwrap_or(self.rest.len());
+        let (number, rest) = self.rest.split_at(len);
+        // Rust's float syntax is looser than JSON's in a few places
+        let digits = number.strip_prefix(b"-").unwrap_or(number);
+        if !digits.first()?.is_ascii_digit() || (digits.len() > 1 && digits[0] == b'0' && digits[1].is_ascii_digit()) {
+            return None;
+        }
+        let n = std::str::from_utf8(number).ok()?.parse().ok()?;
+        self.rest = rest;
+        Some(n)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let value = Value::parse(br#" {"a": [1, -2.5e1, true, null], "b": {"c": "\u00e9\ud83d\ude00\n"}, "a": 0} "#).unwrap();
+        assert_eq!(
+            Some(&[Value::Number(1.), Value::Number(-25.), Value::Bool(true), Value::Null][..]),
+            value.get("a").and_then(Value::as_array)
+        );
+        assert_eq!(Some("\u{e9}\u{1f600}\n"), value.get("b").and_then(|b| b.get("c")).and_then(Value::as_str));
+        assert_eq!(Some(86400), Value::Number(86400.).as_u64());
+        assert_eq!(None, Value::Number(-1.).as_u64());
+
+        for bad in [&b"{"[..], b"[1,]", b"{\"a\" 1}", b"01", b".5", b"\"\\ud83d\"", b"\"\x01\"", b"1 2", b"tru"] {
+            assert_eq!(None, Value::parse(bad), "{}", String::from_utf8_lossy(bad));
+        }
+        assert_eq!(None, Value::parse(&[b'['; MAX_DEPTH + 1]));
+    }
+}
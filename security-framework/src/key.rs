@@ -146,6 +146,29 @@ impl SecKey {
         }
     }
 
+    /// Creates a public key of the given type from its external
+    /// representation: PKCS #1 for RSA, ANSI X9.63 for elliptic curve keys.
+    #[cfg(any(feature = "OSX_10_12", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub(crate) fn public_from_data(key_type: KeyType, key_data: &CFData) -> Result<Self, CFError> {
+        use security_framework_sys::item::{kSecAttrKeyClass, kSecAttrKeyClassPublic};
+        use security_framework_sys::key::SecKeyCreateWithData;
+
+        let mut attributes = CFMutableDictionary::new();
+        unsafe {
+            attributes.add(&kSecAttrKeyType.to_void(), &key_type.to_str().to_void());
+            attributes.add(&kSecAttrKeyClass.to_void(), &kSecAttrKeyClassPublic.to_void());
+        }
+        let mut error: CFErrorRef = ::std::ptr::null_mut();
+        let key = unsafe {
+            SecKeyCreateWithData(key_data.as_concrete_TypeRef(), attributes.as_concrete_TypeRef(), &mut error)
+        };
+        if key.is_null() {
+            Err(unsafe { CFError::wrap_under_create_rule(error) })
+        } else {
+            Ok(unsafe { SecKey::wrap_under_create_rule(key) })
+        }
+    }
+
     /// Returns the programmatic identifier for the key. For keys of class
     /// kSecAttrKeyClassPublic and kSecAttrKeyClassPrivate, the value is the
     /// hash of the public key.
@@ -0,0 +1,125 @@
+//! TLS key logging for debugging.
+//!
+//! A [`KeyLog`] receives the secrets of each connection as lines of the NSS
+//! key log format, which Wireshark and other tools use to decrypt captured
+//! traffic. Anyone holding these lines can decrypt the connections they
+//! describe, so this module is only available with the `keylog` feature and
+//! should never be enabled in production builds.
+//!
+//! Secure Transport exposes the master secret of TLS 1.2 and earlier
+//! connections, logged with the `CLIENT_RANDOM` label; TLS 1.3 connections
+//! are not logged. Network.framework does not expose secrets, so builders
+//! with a key log refuse to create `ProtocolOptions` for it.
+
+use std::fmt::{self, Write as _};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+
+type KeyLogFn = dyn Fn(&str) + Send + Sync;
+
+/// A destination for NSS key log lines.
+#[derive(Clone)]
+pub struct KeyLog(Arc<KeyLogFn>);
+
+impl fmt::Debug for KeyLog {
+    #[cold]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("KeyLog")
+    }
+}
+
+impl KeyLog {
+    /// Creates a key log passing each line, without a trailing newline, to
+    /// `callback`.
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        Self(Arc::new(callback))
+    }
+
+    /// Creates a key log appending lines to the file at `path`, which is
+    /// created if it does not exist.
+    ///
+    /// Errors writing to the file are ignored.
+    pub fn file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self::from_file(file))
+    }
+
+    /// Creates a key log appending lines to the file named by the
+    /// `SSLKEYLOGFILE` environment variable, as browsers do.
+    ///
+    /// Returns `None` if the variable is not set or the file cannot be opened.
+    #[must_use]
+    pub fn from_env() -> Option<Self> {
+        let path = std::env::var_os("SSLKEYLOGFILE")?;
+        Self::file(path).ok()
+    }
+
+    fn from_file(file: File) -> Self {
+        let file = Mutex::new(file);
+        Self::new(move |line| {
+            let mut file = file.lock().unwrap_or_else(PoisonError::into_inner);
+            let _ = writeln!(file, "{line}");
+        })
+    }
+
+    /// Logs `secret` for the connection identified by `client_random`.
+    ///
+    /// `label` is one of the labels of the NSS key log format, such as
+    /// `CLIENT_RANDOM` for a TLS 1.2 master secret.
+    pub fn log(&self, label: &str, client_random: &[u8], secret: &[u8]) {
+        (self.0)(&line(label, client_random, secret));
+    }
+}
+
+fn line(label: &str, client_random: &[u8], secret: &[u8]) -> String {
+    let mut line = String::with_capacity(label.len() + 2 + 2 * (client_random.len() + secret.len()));
+    line.push_str(label);
+    for field in [client_random, secret] {
+        line.push(' ');
+        for b in field {
+            let _ = write!(line, "{b:02x}");
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn format() {
+        assert_eq!("CLIENT_RANDOM 00ff10 abcd", line("CLIENT_RANDOM", &[0, 0xff, 0x10], &[0xab, 0xcd]));
+
+        let lines = Arc::new(Mutex::new(vec![]));
+        let log = {
+            let lines = Arc::clone(&lines);
+            KeyLog::new(move |line| lines.lock().unwrap().push(line.to_owned()))
+        };
+        log.clone().log("CLIENT_RANDOM", &[1; 32], &[2; 48]);
+        let lines = lines.lock().unwrap();
+        assert_eq!(1, lines.len());
+        assert_eq!(14 + 64 + 1 + 96, lines[0].len());
+        assert!(lines[0].starts_with("CLIENT_RANDOM 0101"));
+    }
+
+    #[test]
+    fn file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keys.log");
+        let log = KeyLog::file(&path).unwrap();
+        log.log("CLIENT_RANDOM", &[1], &[2]);
+        log.log("CLIENT_RANDOM", &[3], &[4]);
+        drop(log);
+        KeyLog::file(&path).unwrap().log("CLIENT_RANDOM", &[5], &[6]);
+        assert_eq!(
+            "CLIENT_RANDOM 01 02\nCLIENT_RANDOM 03 04\nCLIENT_RANDOM 05 06\n",
+            std::fs::read_to_string(&path).unwrap()
+        );
+    }
+}
@@ -16,7 +16,7 @@ use crate::os::macos::access::SecAccess;
 #[cfg(target_os = "macos")]
 use crate::os::macos::keychain::SecKeychain;
 
-#[cfg(test)]
+#[cfg(all(test, any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos")))]
 macro_rules! p {
     ($e:expr) => {
         match $e {
@@ -26,7 +26,10 @@ macro_rules! p {
     };
 }
 
-#[cfg(all(not(feature = "OSX_10_13"), any(feature = "alpn", feature = "session-tickets")))]
+#[cfg(any(
+    all(not(feature = "OSX_10_13"), any(feature = "alpn", feature = "session-tickets")),
+    all(feature = "keylog", any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))
+))]
 #[macro_use]
 mod dlsym;
 
@@ -36,25 +39,43 @@ pub mod authorization;
 pub mod base;
 #[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
 pub mod certificate;
+pub mod certificate_chain;
+pub mod channel_binding;
 pub mod cipher_suite;
+pub mod cipher_string;
+pub mod ct_log;
+mod der;
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+pub mod dispatch;
 #[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
 pub mod identity;
 #[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
 pub mod import_export;
 #[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
 pub mod item;
+mod json;
 #[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
 pub mod key;
+#[cfg(feature = "keylog")]
+pub mod key_log;
+pub mod ocsp;
 pub mod os;
 pub mod passwords;
 pub mod passwords_options;
 #[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
 pub mod policy;
+#[cfg(any(all(target_os = "macos", feature = "OSX_10_15"), target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+pub mod protocol_options;
 pub mod random;
+pub mod sct;
 #[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
 pub mod secure_transport;
+pub mod session_cache;
+pub mod tls_preset;
 #[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
 pub mod trust;
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+pub mod trust_policy;
 #[cfg(target_os = "macos")]
 pub mod trust_settings;
 
@@ -82,7 +103,7 @@ fn cvt(err: OSStatus) -> Result<()> {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos")))]
 mod test {
     use crate::certificate::SecCertificate;
 
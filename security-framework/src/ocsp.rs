@@ -0,0 +1,356 @@
+//! Online Certificate Status Protocol response inspection.
+//!
+//! These are the responses passed to `SecTrust::set_trust_ocsp_response`,
+//! typically stapled by a server. Parsing does not verify the responder's
+//! signature; that happens during trust evaluation.
+
+use security_framework_sys::base::errSecDecode;
+use std::time::SystemTime;
+
+use crate::der::{self, Reader};
+use crate::{Error, Result};
+
+/// OID 1.3.6.1.5.5.7.48.1.1, `id-pkix-ocsp-basic`.
+const OID_OCSP_BASIC: &[u8] = &[0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x30, 0x01, 0x01];
+
+/// The status of an OCSP response as a whole.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum OcspResponseStatus {
+    /// The response contains certificate statuses.
+    Successful,
+    /// The request was malformed.
+    MalformedRequest,
+    /// The responder hit an internal error.
+    InternalError,
+    /// The responder is temporarily unable to answer.
+    TryLater,
+    /// The request must be signed.
+    SigRequired,
+    /// The client is not authorized to query the responder.
+    Unauthorized,
+    /// A status not defined by RFC 6960.
+    Other(u8),
+}
+
+impl OcspResponseStatus {
+    fn from_value(value: u8) -> Self {
+        match value {
+            0 => Self::Successful,
+            1 => Self::MalformedRequest,
+            2 => Self::InternalError,
+            3 => Self::TryLater,
+            5 => Self::SigRequired,
+            6 => Self::Unauthorized,
+            other => Self::Other(other),
+        }
+    }
+}
+
+/// Identifies the responder that signed an OCSP response.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ResponderId {
+    /// The DER encoded subject name of the responder.
+    ByName(Vec<u8>),
+    /// The SHA-1 hash of the responder's public key.
+    ByKey(Vec<u8>),
+}
+
+/// The revocation status of a single certificate.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CertStatus {
+    /// The certificate is not revoked.
+    Good,
+    /// The certificate was revoked.
+    Revoked {
+        /// When the certificate was revoked.
+        time: SystemTime,
+        /// The `CRLReason` code, if the responder gave one.
+        reason: Option<u8>,
+    },
+    /// The responder does not know about the certificate.
+    Unknown,
+}
+
+/// The status of one certificate within an OCSP response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SingleResponse {
+    hash_algorithm: Vec<u8>,
+    issuer_name_hash: Vec<u8>,
+    issuer_key_hash: Vec<u8>,
+    serial_number: Vec<u8>,
+    status: CertStatus,
+    this_update: SystemTime,
+    next_update: Option<SystemTime>,
+}
+
+impl SingleResponse {
+    /// The content octets of the OID of the hash algorithm used for the issuer hashes.
+    #[inline(always)]
+    #[must_use]
+    pub fn hash_algorithm(&self) -> &[u8] {
+        &self.hash_algorithm
+    }
+
+    /// The hash of the issuer's DER encoded name.
+    #[inline(always)]
+    #[must_use]
+    pub fn issuer_name_hash(&self) -> &[u8] {
+        &self.issuer_name_hash
+    }
+
+    /// The hash of the issuer's public key.
+    #[inline(always)]
+    #[must_use]
+    pub fn issuer_key_hash(&self) -> &[u8] {
+        &self.issuer_key_hash
+    }
+
+    /// The big-endian serial number of the certificate.
+    #[inline(always)]
+    #[must_use]
+    pub fn serial_number(&self) -> &[u8] {
+        &self.serial_number
+    }
+
+    /// The revocation status of the certificate.
+    #[inline(always)]
+    #[must_use]
+    pub fn status(&self) -> CertStatus {
+        self.status
+    }
+
+    /// The time at which the status was known to be correct.
+    #[inline(always)]
+    #[must_use]
+    pub fn this_update(&self) -> SystemTime {
+        self.this_update
+    }
+
+    /// The time by which newer status information will be available.
+    #[inline(always)]
+    #[must_use]
+    pub fn next_update(&self) -> Option<SystemTime> {
+        self.next_update
+    }
+
+    fn parse(reader: &mut Reader<'_>) -> Option<Self> {
+        let mut single = reader.read_sequence()?;
+
+        let mut cert_id = single.read_sequence()?;
+        let hash_algorithm = cert_id.read_sequence()?.read_contents(der::OID)?.to_vec();
+        let issuer_name_hash = cert_id.read_contents(der::OCTET_STRING)?.to_vec();
+        let issuer_key_hash = cert_id.read_contents(der::OCTET_STRING)?.to_vec();
+        let serial_number = cert_id.read_contents(der::INTEGER)?.to_vec();
+
+        let status = single.read_any()?;
+        let status = match status.tag {
+            tag if tag == der::implicit(0) => CertStatus::Good,
+            tag if tag == der::explicit(1) => {
+                let mut revoked = Reader::new(status.contents);
+                let time = der::generalized_time(revoked.read_contents(der::GENERALIZED_TIME)?)?;
+                let reason = match revoked.read_optional(der::explicit(0)) {
+                    Some(reason) => match Reader::new(reason.contents).read_contents(der::ENUMERATED)? {
+                        [reason] => Some(*reason),
+                        _ => return None,
+                    },
+                    None => None,
+                };
+                CertStatus::Revoked { time, reason }
+            }
+            tag if tag == der::implicit(2) => CertStatus::Unknown,
+            _ => return None,
+        };
+
+        let this_update = der::generalized_time(single.read_contents(der::GENERALIZED_TIME)?)?;
+        let next_update = match single.read_optional(der::explicit(0)) {
+            Some(next) => Some(der::generalized_time(
+                Reader::new(next.contents).read_contents(der::GENERALIZED_TIME)?,
+            )?),
+            None => None,
+        };
+
+        Some(Self {
+            hash_algorithm,
+            issuer_name_hash,
+            issuer_key_hash,
+            serial_number,
+            status,
+            this_update,
+            next_update,
+        })
+    }
+}
+
+/// A parsed OCSP response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OcspResponse {
+    status: OcspResponseStatus,
+    responder: Option<ResponderId>,
+    produced_at: Option<SystemTime>,
+    responses: Vec<SingleResponse>,
+}
+
+impl OcspResponse {
+    /// Parses a DER encoded `OCSPResponse`.
+    ///
+    /// Only basic responses are understood; other response types are rejected.
+    pub fn parse(der: &[u8]) -> Result<Self> {
+        Self::parse_inner(der).ok_or_else(|| Error::from_code(errSecDecode))
+    }
+
+    fn parse_inner(der: &[u8]) -> Option<Self> {
+        let mut outer = Reader::new(der);
+        let mut response = outer.read_sequence()?;
+        if !outer.is_empty() {
+            return None;
+        }
+        let status = match response.read_contents(der::ENUMERATED)? {
+            [status] => OcspResponseStatus::from_value(*status),
+            _ => return None,
+        };
+
+        let bytes = match response.read_optional(der::explicit(0)) {
+            Some(bytes) => bytes,
+            None => {
+                return Some(Self {
+                    status,
+                    responder: None,
+                    produced_at: None,
+                    responses: vec![],
+                })
+            }
+        };
+        let mut bytes = Reader::new(bytes.contents).read_sequence()?;
+        if bytes.read_contents(der::OID)? != OID_OCSP_BASIC {
+            return None;
+        }
+        let mut basic = Reader::new(bytes.read_contents(der::OCTET_STRING)?).read_sequence()?;
+        let mut data = basic.read_sequence()?;
+
+        data.read_optional(der::explicit(0));
+        let responder = data.read_any()?;
+        let responder = match responder.tag {
+            tag if tag == der::explicit(1) => {
+                ResponderId::ByName(Reader::new(responder.contents).read(der::SEQUENCE)?.raw.to_vec())
+            }
+            tag if tag == der::explicit(2) => {
+                ResponderId::ByKey(Reader::new(responder.contents).read_contents(der::OCTET_STRING)?.to_vec())
+            }
+            _ => return None,
+        };
+        let produced_at = der::generalized_time(data.read_contents(der::GENERALIZED_TIME)?)?;
+
+        let mut singles = data.read_sequence()?;
+        let mut responses = vec![];
+        while !singles.is_empty() {
+            responses.push(SingleResponse::parse(&mut singles)?);
+        }
+
+        Some(Self {
+            status,
+            responder: Some(responder),
+            produced_at: Some(produced_at),
+            responses,
+        })
+    }
+
+    /// The status of the response as a whole.
+    #[inline(always)]
+    #[must_use]
+    pub fn status(&self) -> OcspResponseStatus {
+        self.status
+    }
+
+    /// The responder that signed the response, if it was successful.
+    #[inline(always)]
+    #[must_use]
+    pub fn responder(&self) -> Option<&ResponderId> {
+        self.responder.as_ref()
+    }
+
+    /// The time the response was signed, if it was successful.
+    #[inline(always)]
+    #[must_use]
+    pub fn produced_at(&self) -> Option<SystemTime> {
+        self.produced_at
+    }
+
+    /// The statuses of the certificates covered by the response.
+    #[inline(always)]
+    #[must_use]
+    pub fn responses(&self) -> &[SingleResponse] {
+        &self.responses
+    }
+
+    /// The earliest `nextUpdate` of the contained statuses.
+    ///
+    /// A server stapling this response should fetch a new one before then.
+    #[must_use]
+    pub fn next_update(&self) -> Option<SystemTime> {
+        self.responses.iter().filter_map(SingleResponse::next_update).min()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn good() {
+        let response = OcspResponse::parse(include_bytes!("../test/ocsp/good.der")).unwrap();
+        assert_eq!(OcspResponseStatus::Successful, response.status());
+        match response.responder() {
+            Some(ResponderId::ByName(name)) => assert_eq!(
+                crate::der::Certificate::parse(include_bytes!("../test/chain/intermediate.der"))
+                    .unwrap()
+                    .subject,
+                &name[..]
+            ),
+            other => panic!("{other:?}"),
+        }
+        assert_eq!(Some(at(1_792_342_552)), response.produced_at());
+        assert_eq!(Some(at(4_945_942_552)), response.next_update());
+
+        let single = &response.responses()[0];
+        assert_eq!(CertStatus::Good, single.status());
+        assert_eq!(&[0x03][..], single.serial_number());
+        // sha1
+        assert_eq!(&[0x2b, 0x0e, 0x03, 0x02, 0x1a][..], single.hash_algorithm());
+        assert_eq!(20, single.issuer_name_hash().len());
+        assert_eq!(at(1_792_342_552), single.this_update());
+    }
+
+    #[test]
+    fn revoked() {
+        let response = OcspResponse::parse(include_bytes!("../test/ocsp/revoked.der")).unwrap();
+        match response.responder() {
+            Some(ResponderId::ByKey(hash)) => assert_eq!(20, hash.len()),
+            other => panic!("{other:?}"),
+        }
+        let single = &response.responses()[0];
+        assert_eq!(
+            CertStatus::Revoked {
+                time: at(1_759_320_000),
+                reason: Some(1),
+            },
+            single.status()
+        );
+        assert_eq!(Some(at(1_792_346_156)), single.next_update());
+    }
+
+    #[test]
+    fn unsuccessful() {
+        let response = OcspResponse::parse(&[0x30, 0x03, 0x0a, 0x01, 0x03]).unwrap();
+        assert_eq!(OcspResponseStatus::TryLater, response.status());
+        assert!(response.responses().is_empty());
+        assert_eq!(None, response.next_update());
+
+        assert!(OcspResponse::parse(&[0x30, 0x03, 0x0a, 0x01]).is_err());
+        assert!(OcspResponse::parse(&include_bytes!("../test/ocsp/good.der")[..100]).is_err());
+    }
+}
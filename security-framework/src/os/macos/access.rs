@@ -1,8 +1,16 @@
 //! Access functionality.
 
 use core_foundation::base::TCFType;
-use security_framework_sys::access::SecAccessGetTypeID;
-use security_framework_sys::base::SecAccessRef;
+use security_framework_sys::access::*;
+use security_framework_sys::base::{errSecParam, SecAccessRef, SecTrustedApplicationRef};
+use std::ffi::CString;
+use std::fmt;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::ptr;
+
+use crate::base::{Error, Result};
+use crate::cvt;
 
 declare_TCFType! {
     /// A type representing access settings.
@@ -12,3 +20,61 @@ impl_TCFType!(SecAccess, SecAccessRef, SecAccessGetTypeID);
 
 unsafe impl Sync for SecAccess {}
 unsafe impl Send for SecAccess {}
+
+declare_TCFType! {
+    /// A type representing an application, as used by access and trust settings.
+    SecTrustedApplication, SecTrustedApplicationRef
+}
+impl_TCFType!(SecTrustedApplication, SecTrustedApplicationRef, SecTrustedApplicationGetTypeID);
+
+unsafe impl Sync for SecTrustedApplication {}
+unsafe impl Send for SecTrustedApplication {}
+
+impl fmt::Debug for SecTrustedApplication {
+    #[cold]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("SecTrustedApplication").finish()
+    }
+}
+
+impl SecTrustedApplication {
+    /// Creates a trusted application for the executable or bundle at the given path.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path_name = CString::new(path.as_ref().as_os_str().as_bytes())
+            .map_err(|_| Error::from_code(errSecParam))?;
+
+        unsafe {
+            let mut app = ptr::null_mut();
+            cvt(SecTrustedApplicationCreateFromPath(path_name.as_ptr().cast(), &mut app))?;
+            Ok(Self::wrap_under_create_rule(app))
+        }
+    }
+
+    /// Creates a trusted application for the calling application.
+    pub fn current() -> Result<Self> {
+        unsafe {
+            let mut app = ptr::null_mut();
+            cvt(SecTrustedApplicationCreateFromPath(ptr::null(), &mut app))?;
+            Ok(Self::wrap_under_create_rule(app))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn trusted_application() {
+        let current = SecTrustedApplication::current().unwrap();
+        let exe = std::env::current_exe().unwrap();
+        let from_path = SecTrustedApplication::from_path(exe).unwrap();
+        assert_eq!(current.type_of(), from_path.type_of());
+    }
+
+    #[test]
+    fn trusted_application_interior_nul() {
+        let err = SecTrustedApplication::from_path("/Applications/Safari.app\0/evil").unwrap_err();
+        assert_eq!(err.code(), errSecParam);
+    }
+}
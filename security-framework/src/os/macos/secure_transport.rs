@@ -211,6 +211,7 @@ impl<S> MidHandshakeSslStreamExt for MidHandshakeSslStream<S> {
 
 #[cfg(test)]
 mod test {
+    use std::io;
     use std::io::prelude::*;
     use std::net::{TcpListener, TcpStream};
     use std::thread;
@@ -221,6 +222,7 @@ mod test {
     use crate::os::macos::test::identity;
     use crate::secure_transport::*;
     use crate::test::certificate;
+    use crate::tls_preset::TlsPreset;
 
     #[test]
     fn server_client() {
@@ -299,6 +301,720 @@ mod test {
         handle.join().unwrap();
     }
 
+    #[test]
+    fn into_parts() {
+        let listener = p!(TcpListener::bind("localhost:0"));
+        let port = p!(listener.local_addr()).port();
+
+        let handle = thread::spawn(move || {
+            let dir = p!(tempdir());
+
+            let identity = identity(dir.path());
+            let builder = ServerBuilder::new(&identity, &[]);
+
+            let stream = p!(listener.accept()).0;
+            let mut stream = p!(builder.handshake(stream));
+            p!(stream.set_write_buffer_size(1024));
+            p!(stream.write_all(b"secret"));
+            assert_eq!(6, stream.buffered_write_size());
+
+            // continue without TLS, sending the buffered write first
+            let mut stream = p!(stream.into_inner());
+            p!(stream.write_all(b"plain"));
+        });
+
+        let stream = p!(TcpStream::connect(("localhost", port)));
+        let mut stream = p!(ClientBuilder::new()
+            .anchor_certificates(&[certificate()])
+            .danger_accept_invalid_certs(true)
+            .handshake("foobar.com", stream));
+
+        let mut buf = [0; 1];
+        p!(stream.read_exact(&mut buf));
+        assert_eq!(b"s", &buf);
+        let (mut stream, plaintext) = p!(stream.into_parts());
+        assert_eq!(b"ecret", &plaintext[..]);
+
+        let mut buf = vec![];
+        p!(stream.read_to_end(&mut buf));
+        assert_eq!(b"plain", &buf[..]);
+
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn into_parts_unsent_writes() {
+        use std::net::Shutdown;
+
+        let listener = p!(TcpListener::bind("localhost:0"));
+        let port = p!(listener.local_addr()).port();
+
+        let handle = thread::spawn(move || {
+            let dir = p!(tempdir());
+
+            let identity = identity(dir.path());
+            let builder = ServerBuilder::new(&identity, &[]);
+
+            let stream = p!(listener.accept()).0;
+            let mut stream = p!(builder.handshake(stream));
+            let mut buf = vec![];
+            p!(stream.read_to_end(&mut buf));
+        });
+
+        let stream = p!(TcpStream::connect(("localhost", port)));
+        let mut stream = p!(ClientBuilder::new()
+            .anchor_certificates(&[certificate()])
+            .handshake("foobar.com", stream));
+        p!(stream.set_write_buffer_size(1024));
+        p!(stream.write_all(b"secret"));
+        p!(stream.get_ref().shutdown(Shutdown::Write));
+
+        let err = stream.into_parts().unwrap_err();
+        assert_eq!(io::ErrorKind::BrokenPipe, err.error().kind());
+        let stream = err.into_inner();
+        assert_eq!(6, stream.buffered_write_size());
+        drop(stream);
+
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn write_buffering() {
+        use std::io::IoSlice;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        // counts the writes Secure Transport makes, one per record
+        #[derive(Debug)]
+        struct CountingStream(TcpStream, Arc<AtomicUsize>);
+
+        impl Read for CountingStream {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                self.0.read(buf)
+            }
+        }
+
+        impl Write for CountingStream {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.1.fetch_add(1, Ordering::SeqCst);
+                self.0.write(buf)
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                self.0.flush()
+            }
+        }
+
+        let listener = p!(TcpListener::bind("localhost:0"));
+        let port = p!(listener.local_addr()).port();
+
+        let handle = thread::spawn(move || {
+            let dir = p!(tempdir());
+
+            let identity = identity(dir.path());
+            let builder = ServerBuilder::new(&identity, &[]);
+
+            let stream = p!(listener.accept()).0;
+            let mut stream = p!(builder.handshake(stream));
+
+            let mut buf = vec![];
+            p!(stream.read_to_end(&mut buf));
+            buf
+        });
+
+        let writes = Arc::new(AtomicUsize::new(0));
+        let stream = p!(TcpStream::connect(("localhost", port)));
+        let mut stream = p!(ClientBuilder::new()
+            .anchor_certificates(&[certificate()])
+            .danger_accept_invalid_certs(true)
+            .handshake("foobar.com", CountingStream(stream, Arc::clone(&writes))));
+        assert!(stream.set_max_record_size(0).is_err());
+        assert!(stream.set_max_record_size(16385).is_err());
+
+        let mut expected = vec![];
+        p!(stream.set_write_buffer_size(1024));
+        writes.store(0, Ordering::SeqCst);
+        for i in 0..100u8 {
+            p!(stream.write_all(&[i; 10]));
+            expected.extend_from_slice(&[i; 10]);
+        }
+        assert_eq!(1000, stream.buffered_write_size());
+        assert_eq!(0, writes.load(Ordering::SeqCst));
+        p!(stream.flush());
+        assert_eq!(0, stream.buffered_write_size());
+        assert_eq!(1, writes.load(Ordering::SeqCst));
+
+        p!(stream.set_write_buffer_size(0));
+        p!(stream.set_max_record_size(100));
+        writes.store(0, Ordering::SeqCst);
+        let bufs = [IoSlice::new(b"hello "), IoSlice::new(b""), IoSlice::new(&[b'!'; 200])];
+        let n = p!(stream.write_vectored(&bufs));
+        assert_eq!(100, n);
+        expected.extend_from_slice(b"hello ");
+        expected.extend_from_slice(&[b'!'; 94]);
+        p!(stream.write_all(&[b'?'; 250]));
+        expected.extend_from_slice(&[b'?'; 250]);
+        assert_eq!(4, writes.load(Ordering::SeqCst));
+
+        // dropping the stream sends what is still buffered
+        p!(stream.set_write_buffer_size(1024));
+        p!(stream.write_all(b"goodbye"));
+        expected.extend_from_slice(b"goodbye");
+        assert_eq!(7, stream.buffered_write_size());
+        drop(stream);
+        assert_eq!(expected, handle.join().unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "keylog")]
+    fn key_log() {
+        use crate::key_log::KeyLog;
+        use std::sync::{Arc, Mutex};
+
+        fn collect() -> (KeyLog, Arc<Mutex<Vec<String>>>) {
+            let lines = Arc::new(Mutex::new(vec![]));
+            let log_lines = Arc::clone(&lines);
+            let log = KeyLog::new(move |line| log_lines.lock().unwrap().push(line.to_owned()));
+            (log, lines)
+        }
+
+        let listener = p!(TcpListener::bind("localhost:0"));
+        let port = p!(listener.local_addr()).port();
+        let (server_log, server_lines) = collect();
+
+        let handle = thread::spawn(move || {
+            let dir = p!(tempdir());
+
+            let identity = identity(dir.path());
+            let mut builder = ServerBuilder::new(&identity, &[]);
+            builder.key_log(&server_log);
+
+            let stream = p!(listener.accept()).0;
+            let mut stream = p!(builder.handshake(stream));
+
+            let mut buf = [0; 12];
+            p!(stream.read(&mut buf));
+            assert_eq!(&buf[..], b"hello world!");
+        });
+
+        let (client_log, client_lines) = collect();
+        let stream = p!(TcpStream::connect(("localhost", port)));
+        let mut stream = p!(ClientBuilder::new()
+            .anchor_certificates(&[certificate()])
+            .danger_accept_invalid_certs(true)
+            .protocol_max(SslProtocol::TLS12)
+            .key_log(&client_log)
+            .handshake("foobar.com", stream));
+
+        p!(stream.write_all(b"hello world!"));
+
+        handle.join().unwrap();
+
+        // nothing is logged where the private functions are unavailable
+        let available = ["SSLInternalClientRandom\0", "SSLInternalMasterSecret\0"]
+            .iter()
+            .all(|name| unsafe { !libc::dlsym(libc::RTLD_DEFAULT, name.as_ptr().cast()).is_null() });
+        let client_lines = client_lines.lock().unwrap();
+        assert_eq!(*client_lines, *server_lines.lock().unwrap());
+        if available {
+            assert_eq!(1, client_lines.len());
+            let hex = |field: &str, len| field.len() == len && field.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'));
+            let fields = client_lines[0].split(' ').collect::<Vec<_>>();
+            assert!(
+                matches!(fields[..], ["CLIENT_RANDOM", random, secret] if hex(random, 64) && hex(secret, 96)),
+                "{}",
+                client_lines[0]
+            );
+        } else {
+            assert!(client_lines.is_empty());
+        }
+
+        #[cfg(feature = "OSX_10_15")]
+        {
+            let mut builder = ClientBuilder::new();
+            builder.key_log(&client_log);
+            let err = builder.protocol_options("foobar.com").unwrap_err();
+            assert_eq!(security_framework_sys::base::errSecUnimplemented, err.code());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "OSX_10_11")]
+    fn server_sni_resolver() {
+        use std::sync::{mpsc, Mutex};
+
+        let listener = p!(TcpListener::bind("localhost:0"));
+        let port = p!(listener.local_addr()).port();
+
+        let handle = thread::spawn(move || {
+            let dir = p!(tempdir());
+
+            let identity = identity(dir.path());
+            let (tx, rx) = mpsc::channel();
+            let tx = Mutex::new(tx);
+            let resolved = identity.clone();
+            let mut builder = ServerBuilder::new(&identity, &[]);
+            builder.with_sni_resolver(move |name| {
+                tx.lock().unwrap().send(name.to_owned()).unwrap();
+                Some((resolved.clone(), vec![]))
+            });
+
+            let stream = p!(listener.accept()).0;
+            let mut stream = p!(builder.handshake(stream));
+
+            let mut buf = [0; 12];
+            p!(stream.read(&mut buf));
+            assert_eq!(&buf[..], b"hello world!");
+            assert_eq!("foobar.com", rx.recv().unwrap());
+        });
+
+        let mut ctx = p!(SslContext::new(
+            SslProtocolSide::CLIENT,
+            SslConnectionType::STREAM
+        ));
+        p!(ctx.set_peer_domain_name("foobar.com"));
+        p!(ctx.set_break_on_server_auth(true));
+        let stream = p!(TcpStream::connect(("localhost", port)));
+
+        let stream = match ctx.handshake(stream) {
+            Ok(_) => panic!("unexpected success"),
+            Err(HandshakeError::Interrupted(stream)) => stream,
+            Err(err) => panic!("unexpected error {err:?}"),
+        };
+
+        let mut peer_trust = p!(stream.context().peer_trust2()).unwrap();
+        p!(peer_trust.set_anchor_certificates(&[certificate()]));
+        p!(peer_trust.evaluate_with_error());
+
+        let mut stream = p!(stream.handshake());
+        p!(stream.write_all(b"hello world!"));
+
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn step_nonblocking_handshake() {
+        let listener = p!(TcpListener::bind("localhost:0"));
+        let port = p!(listener.local_addr()).port();
+
+        let handle = thread::spawn(move || {
+            let dir = p!(tempdir());
+
+            let identity = identity(dir.path());
+            let builder = ServerBuilder::new(&identity, &[]);
+
+            let stream = p!(listener.accept()).0;
+            let mut stream = p!(builder.handshake(stream));
+            p!(stream.write_all(b"hello world!"));
+        });
+
+        let mut ctx = p!(SslContext::new(
+            SslProtocolSide::CLIENT,
+            SslConnectionType::STREAM
+        ));
+        p!(ctx.set_peer_domain_name("foobar.com"));
+        p!(ctx.set_break_on_server_auth(true));
+        let stream = p!(TcpStream::connect(("localhost", port)));
+        p!(stream.set_nonblocking(true));
+
+        let mut stream = match ctx.handshake(stream) {
+            Err(HandshakeError::Interrupted(stream)) => stream,
+            Ok(_) => panic!("unexpected success"),
+            Err(err) => panic!("unexpected error {err:?}"),
+        };
+        assert!(stream.would_block());
+
+        let mut server_auth = false;
+        loop {
+            match p!(stream.step()) {
+                HandshakeStatus::WantRead | HandshakeStatus::WantWrite => thread::yield_now(),
+                HandshakeStatus::ServerAuth(mut trust) => {
+                    p!(trust.set_anchor_certificates(&[certificate()]));
+                    p!(trust.evaluate_with_error());
+                    server_auth = true;
+                }
+                HandshakeStatus::Done => break,
+                status => panic!("unexpected status {status:?}"),
+            }
+        }
+        assert!(server_auth);
+
+        let mut stream = stream.into_stream().unwrap();
+        p!(stream.get_mut().set_nonblocking(false));
+        let mut buf = [0; 12];
+        p!(stream.read_exact(&mut buf));
+        assert_eq!(&buf[..], b"hello world!");
+
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn memory_bio() {
+        let dir = p!(tempdir());
+        let identity = identity(dir.path());
+
+        let mut server = p!(SslContext::new(
+            SslProtocolSide::SERVER,
+            SslConnectionType::STREAM
+        ));
+        p!(server.set_certificate(&identity, &[]));
+        let mut server = match server.handshake(MemoryBio::new()) {
+            Err(HandshakeError::Interrupted(stream)) => stream,
+            Ok(_) => panic!("unexpected success"),
+            Err(err) => panic!("unexpected error {err:?}"),
+        };
+
+        let mut client = p!(SslContext::new(
+            SslProtocolSide::CLIENT,
+            SslConnectionType::STREAM
+        ));
+        p!(client.set_peer_domain_name("foobar.com"));
+        p!(client.set_break_on_server_auth(true));
+        let mut client = match client.handshake(MemoryBio::new()) {
+            Err(HandshakeError::Interrupted(stream)) => stream,
+            Ok(_) => panic!("unexpected success"),
+            Err(err) => panic!("unexpected error {err:?}"),
+        };
+        assert!(client.get_mut().outgoing_len() > 0);
+
+        let (mut client_done, mut server_done) = (false, false);
+        for _ in 0..10 {
+            let data = client.get_mut().take_outgoing();
+            server.get_mut().push_incoming(&data);
+            if !server_done {
+                server_done = matches!(p!(server.step()), HandshakeStatus::Done);
+            }
+
+            let data = server.get_mut().take_outgoing();
+            client.get_mut().push_incoming(&data);
+            if !client_done {
+                match p!(client.step()) {
+                    HandshakeStatus::ServerAuth(mut trust) => {
+                        p!(trust.set_anchor_certificates(&[certificate()]));
+                        p!(trust.evaluate_with_error());
+                        client_done = matches!(p!(client.step()), HandshakeStatus::Done);
+                    }
+                    status => client_done = matches!(status, HandshakeStatus::Done),
+                }
+            }
+            if client_done && server_done {
+                break;
+            }
+        }
+
+        let mut client = client.into_stream().unwrap();
+        let mut server = server.into_stream().unwrap();
+        p!(client.write_all(b"hello world!"));
+        let data = client.get_mut().take_outgoing();
+        assert!(data.len() > 12);
+
+        let mut buf = [0; 12];
+        assert_eq!(
+            std::io::ErrorKind::WouldBlock,
+            server.read(&mut buf).unwrap_err().kind()
+        );
+        server.get_mut().push_incoming(&data);
+        p!(server.read_exact(&mut buf));
+        assert_eq!(&buf[..], b"hello world!");
+        assert_eq!(0, server.get_mut().incoming_len());
+    }
+
+    #[test]
+    fn server_client_auth() {
+        let listener = p!(TcpListener::bind("localhost:0"));
+        let port = p!(listener.local_addr()).port();
+
+        let handle = thread::spawn(move || {
+            let dir = p!(tempdir());
+
+            let identity = identity(dir.path());
+            let mut builder = ServerBuilder::new(&identity, &[]);
+            builder
+                .client_auth(ClientAuthMode::Required, &[certificate()])
+                .with_client_cert_verifier(|chain| chain[0] == certificate());
+
+            let stream = p!(listener.accept()).0;
+            let mut stream = p!(builder.handshake(stream));
+            assert_eq!(Some(&[certificate()][..]), stream.peer_certificates());
+
+            let mut buf = [0; 12];
+            p!(stream.read(&mut buf));
+            assert_eq!(&buf[..], b"hello world!");
+        });
+
+        let dir = p!(tempdir());
+        let identity = identity(dir.path());
+        let stream = p!(TcpStream::connect(("localhost", port)));
+        let mut stream = p!(ClientBuilder::new()
+            .anchor_certificates(&[certificate()])
+            .identity(&identity, &[])
+            .handshake("foobar.com", stream));
+        assert_eq!(Some(&[certificate()][..]), stream.peer_certificates());
+        p!(stream.write_all(b"hello world!"));
+
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn channel_binding() {
+        use crate::channel_binding::{tls_server_end_point, ChannelBindingType};
+
+        let listener = p!(TcpListener::bind("localhost:0"));
+        let port = p!(listener.local_addr()).port();
+        let expected = p!(tls_server_end_point(&certificate().to_der()));
+
+        let server_expected = expected.clone();
+        let handle = thread::spawn(move || {
+            let dir = p!(tempdir());
+
+            let identity = identity(dir.path());
+            let builder = ServerBuilder::new(&identity, &[]);
+
+            for _ in 0..2 {
+                let stream = p!(listener.accept()).0;
+                let mut stream = p!(builder.handshake(stream));
+                let binding = p!(stream.channel_binding(ChannelBindingType::TlsServerEndPoint));
+                assert_eq!(server_expected, binding);
+                assert!(stream.channel_binding(ChannelBindingType::TlsUnique).is_err());
+
+                let mut buf = [0; 12];
+                p!(stream.read(&mut buf));
+                assert_eq!(&buf[..], b"hello world!");
+            }
+        });
+
+        let stream = p!(TcpStream::connect(("localhost", port)));
+        let mut stream = p!(ClientBuilder::new()
+            .anchor_certificates(&[certificate()])
+            .handshake("foobar.com", stream));
+        let binding = p!(stream.channel_binding(ChannelBindingType::TlsServerEndPoint));
+        assert_eq!(expected, binding);
+        p!(stream.write_all(b"hello world!"));
+
+        // the certificate is not verified, so it comes from the peer's trust
+        let stream = p!(TcpStream::connect(("localhost", port)));
+        let mut stream = p!(ClientBuilder::new()
+            .danger_accept_invalid_certs(true)
+            .handshake("foobar.com", stream));
+        let binding = p!(stream.channel_binding(ChannelBindingType::TlsServerEndPoint));
+        assert_eq!(expected, binding);
+        p!(stream.write_all(b"hello world!"));
+
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn channel_binding_server_context() {
+        use crate::channel_binding::ChannelBindingType;
+        use security_framework_sys::base::errSecParam;
+
+        let listener = p!(TcpListener::bind("localhost:0"));
+        let port = p!(listener.local_addr()).port();
+
+        let handle = thread::spawn(move || {
+            let dir = p!(tempdir());
+
+            let mut ctx = p!(SslContext::new(
+                SslProtocolSide::SERVER,
+                SslConnectionType::STREAM
+            ));
+            let identity = identity(dir.path());
+            p!(ctx.set_certificate(&identity, &[]));
+            p!(ctx.set_client_side_authenticate(SslAuthenticate::ALWAYS));
+            p!(ctx.set_break_on_client_auth(true));
+
+            let stream = p!(listener.accept()).0;
+            let stream = match ctx.handshake(stream) {
+                Ok(_) => panic!("unexpected success"),
+                Err(HandshakeError::Interrupted(stream)) => stream,
+                Err(err) => panic!("unexpected error {err:?}"),
+            };
+
+            let mut peer_trust = p!(stream.context().peer_trust2()).unwrap();
+            p!(peer_trust.set_anchor_certificates(&[certificate()]));
+            p!(peer_trust.evaluate_with_error());
+
+            // the server's certificate is not recorded, and the peer trust
+            // holds the client's certificate
+            let mut stream = p!(stream.handshake());
+            let err = stream.channel_binding(ChannelBindingType::TlsServerEndPoint).unwrap_err();
+            assert_eq!(errSecParam, err.code());
+
+            let mut buf = [0; 12];
+            p!(stream.read(&mut buf));
+            assert_eq!(&buf[..], b"hello world!");
+        });
+
+        let dir = p!(tempdir());
+        let identity = identity(dir.path());
+        let stream = p!(TcpStream::connect(("localhost", port)));
+        let mut stream = p!(ClientBuilder::new()
+            .anchor_certificates(&[certificate()])
+            .identity(&identity, &[])
+            .handshake("foobar.com", stream));
+        p!(stream.write_all(b"hello world!"));
+
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn server_psk() {
+        let dir = p!(tempdir());
+        let identity = identity(dir.path());
+        let mut builder = ServerBuilder::new(&identity, &[]);
+        builder.pre_shared_key(b"secret", b"device").psk_identity_hint(b"devices");
+        let err = builder.new_ssl_context().unwrap_err();
+        assert_eq!(security_framework_sys::base::errSecUnimplemented, err.code());
+        assert!(!format!("{builder:?}").contains("secret"));
+
+        #[cfg(feature = "OSX_10_15")]
+        {
+            p!(builder.protocol_options());
+            builder.client_auth(ClientAuthMode::Required, &[]);
+            assert!(builder.protocol_options().is_err());
+        }
+    }
+
+    #[test]
+    fn server_preset() {
+        let listener = p!(TcpListener::bind("localhost:0"));
+        let port = p!(listener.local_addr()).port();
+
+        let handle = thread::spawn(move || {
+            let dir = p!(tempdir());
+
+            let identity = identity(dir.path());
+            let mut builder = ServerBuilder::new(&identity, &[]);
+            builder.preset(TlsPreset::Intermediate);
+
+            let stream = p!(listener.accept()).0;
+            let mut stream = p!(builder.handshake(stream));
+
+            let mut buf = [0; 12];
+            p!(stream.read(&mut buf));
+            assert_eq!(&buf[..], b"hello world!");
+        });
+
+        let stream = p!(TcpStream::connect(("localhost", port)));
+        let mut stream = p!(ClientBuilder::new()
+            .anchor_certificates(&[certificate()])
+            .preset(TlsPreset::Old)
+            .handshake("foobar.com", stream));
+        let cipher = p!(stream.context().negotiated_cipher());
+        assert!(TlsPreset::Intermediate.cipher_suites().contains(&cipher));
+        p!(stream.write_all(b"hello world!"));
+
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn connection_info() {
+        let listener = p!(TcpListener::bind("localhost:0"));
+        let port = p!(listener.local_addr()).port();
+
+        let handle = thread::spawn(move || {
+            let dir = p!(tempdir());
+
+            let identity = identity(dir.path());
+            let builder = ServerBuilder::new(&identity, &[]);
+
+            let stream = p!(listener.accept()).0;
+            let mut stream = p!(builder.handshake(stream));
+            let info = p!(stream.connection_info());
+            assert!(info.peer_certificates.is_empty());
+            assert_eq!(SslClientCertificateState::NONE, info.client_certificate_state);
+
+            let mut buf = [0; 12];
+            p!(stream.read(&mut buf));
+            assert_eq!(&buf[..], b"hello world!");
+        });
+
+        let stream = p!(TcpStream::connect(("localhost", port)));
+        let mut stream = p!(ClientBuilder::new()
+            .anchor_certificates(&[certificate()])
+            .handshake("foobar.com", stream));
+        let info = p!(stream.connection_info());
+        assert_eq!(Some("foobar.com"), info.server_name.as_deref());
+        assert_eq!(vec![certificate().to_der()], info.peer_certificates);
+        assert_eq!(p!(stream.context().negotiated_cipher()), info.cipher_suite);
+        assert!(info.cipher_suite_name.is_some());
+        assert_eq!(Some(false), info.session_resumed);
+        #[cfg(feature = "OSX_10_9")]
+        assert_eq!(Some(false), info.false_start_enabled);
+        p!(stream.write_all(b"hello world!"));
+
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn client_cert_resolver() {
+        use std::sync::{mpsc, Mutex};
+
+        let listener = p!(TcpListener::bind("localhost:0"));
+        let port = p!(listener.local_addr()).port();
+
+        let handle = thread::spawn(move || {
+            let dir = p!(tempdir());
+
+            let identity = identity(dir.path());
+            let mut builder = ServerBuilder::new(&identity, &[]);
+            builder.client_auth(ClientAuthMode::Required, &[certificate()]);
+
+            let stream = p!(listener.accept()).0;
+            let stream = p!(builder.handshake(stream));
+            assert_eq!(Some(&[certificate()][..]), stream.peer_certificates());
+        });
+
+        let dir = p!(tempdir());
+        let identity = identity(dir.path());
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+        let stream = p!(TcpStream::connect(("localhost", port)));
+        p!(ClientBuilder::new()
+            .anchor_certificates(&[certificate()])
+            .client_cert_resolver(move |names| {
+                tx.lock().unwrap().send(names.to_vec()).unwrap();
+                Some((identity.clone(), vec![]))
+            })
+            .handshake("foobar.com", stream));
+
+        let der = certificate().to_der();
+        let subject = crate::der::Certificate::parse(&der).unwrap().subject;
+        assert_eq!(vec![subject.to_vec()], rx.recv().unwrap());
+
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn server_client_auth_rejected() {
+        let listener = p!(TcpListener::bind("localhost:0"));
+        let port = p!(listener.local_addr()).port();
+
+        let handle = thread::spawn(move || {
+            let dir = p!(tempdir());
+
+            let identity = identity(dir.path());
+            let mut builder = ServerBuilder::new(&identity, &[]);
+            builder
+                .client_auth(ClientAuthMode::Optional, &[certificate()])
+                .with_client_cert_verifier(|_| false);
+
+            let stream = p!(listener.accept()).0;
+            assert!(builder.handshake(stream).is_err());
+        });
+
+        let dir = p!(tempdir());
+        let identity = identity(dir.path());
+        let stream = p!(TcpStream::connect(("localhost", port)));
+        let result = ClientBuilder::new()
+            .anchor_certificates(&[certificate()])
+            .identity(&identity, &[])
+            .handshake("foobar.com", stream);
+        assert!(result.is_err());
+
+        handle.join().unwrap();
+    }
+
     #[test]
     fn client_bad_cert() {
         let _ = env_logger::try_init();
@@ -644,4 +1360,106 @@ mod test {
 
         p!(handle.join());
     }
+
+    #[test]
+    fn shutdown() {
+        let listener = p!(TcpListener::bind("localhost:0"));
+        let port = p!(listener.local_addr()).port();
+
+        let handle = thread::spawn(move || {
+            let dir = p!(tempdir());
+
+            let identity = identity(dir.path());
+            let builder = ServerBuilder::new(&identity, &[]);
+
+            let stream = p!(listener.accept()).0;
+            let mut stream = p!(builder.handshake(stream));
+            assert!(!p!(stream.shutdown()));
+        });
+
+        let stream = p!(TcpStream::connect(("localhost", port)));
+        let mut stream = p!(ClientBuilder::new()
+            .anchor_certificates(&[certificate()])
+            .handshake("foobar.com", stream));
+        stream.set_require_close_notify(true);
+
+        let mut buf = [0; 1];
+        assert_eq!(p!(stream.read(&mut buf)), 0);
+        assert!(stream.close_notify_received());
+        assert!(p!(stream.shutdown()));
+
+        p!(handle.join());
+    }
+
+    #[derive(Debug)]
+    struct ExplodingStream {
+        stream: TcpStream,
+        explode: Option<io::ErrorKind>,
+    }
+
+    impl Read for ExplodingStream {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.explode {
+                Some(io::ErrorKind::UnexpectedEof) => Ok(0),
+                Some(kind) => Err(kind.into()),
+                None => self.stream.read(buf),
+            }
+        }
+    }
+
+    impl Write for ExplodingStream {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.stream.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.stream.flush()
+        }
+    }
+
+    fn truncated_read(explode: io::ErrorKind, require_close_notify: bool) -> io::Result<usize> {
+        let listener = p!(TcpListener::bind("localhost:0"));
+        let port = p!(listener.local_addr()).port();
+
+        let handle = thread::spawn(move || {
+            let dir = p!(tempdir());
+
+            let identity = identity(dir.path());
+            let builder = ServerBuilder::new(&identity, &[]);
+
+            let stream = p!(listener.accept()).0;
+            // keep the connection open
+            p!(builder.handshake(stream))
+        });
+
+        let stream = ExplodingStream {
+            stream: p!(TcpStream::connect(("localhost", port))),
+            explode: None,
+        };
+        let mut stream = p!(ClientBuilder::new()
+            .anchor_certificates(&[certificate()])
+            .handshake("foobar.com", stream));
+        stream.get_mut().explode = Some(explode);
+        stream.set_require_close_notify(require_close_notify);
+
+        let mut buf = [0; 1];
+        let result = stream.read(&mut buf);
+        assert!(!stream.close_notify_received());
+        drop(p!(handle.join()));
+        result
+    }
+
+    #[test]
+    fn missing_close_notify() {
+        assert_eq!(p!(truncated_read(io::ErrorKind::UnexpectedEof, false)), 0);
+        let err = truncated_read(io::ErrorKind::UnexpectedEof, true).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn connection_reset() {
+        assert_eq!(p!(truncated_read(io::ErrorKind::ConnectionReset, false)), 0);
+        let err = truncated_read(io::ErrorKind::ConnectionReset, true).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionReset);
+    }
 }
@@ -1,8 +1,13 @@
 //! Security Policies support.
 #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
 use core_foundation::base::CFOptionFlags;
-use core_foundation::base::TCFType;
+use core_foundation::base::{CFType, TCFType};
+use core_foundation::boolean::CFBoolean;
+use core_foundation::dictionary::CFDictionary;
+#[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use core_foundation::number::CFNumber;
 use core_foundation::string::CFString;
+use core_foundation_sys::string::CFStringRef;
 #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
 use security_framework_sys::base::errSecParam;
 use security_framework_sys::base::SecPolicyRef;
@@ -49,6 +54,253 @@ bitflags::bitflags! {
     }
 }
 
+/// Identifies the kind of certificate evaluation a policy performs.
+#[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+#[derive(Debug, Copy, Clone)]
+pub struct PolicyIdentifier(CFStringRef);
+
+#[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+impl PolicyIdentifier {
+    /// The basic X.509 policy, `kSecPolicyAppleX509Basic`.
+    #[inline(always)]
+    #[must_use]
+    pub fn x509_basic() -> Self {
+        unsafe { Self(kSecPolicyAppleX509Basic) }
+    }
+
+    /// The SSL/TLS policy, `kSecPolicyAppleSSL`.
+    #[inline(always)]
+    #[must_use]
+    pub fn ssl() -> Self {
+        unsafe { Self(kSecPolicyAppleSSL) }
+    }
+
+    /// The S/MIME policy, `kSecPolicyAppleSMIME`.
+    #[inline(always)]
+    #[must_use]
+    pub fn smime() -> Self {
+        unsafe { Self(kSecPolicyAppleSMIME) }
+    }
+
+    /// The Extensible Authentication Protocol policy, `kSecPolicyAppleEAP`.
+    #[inline(always)]
+    #[must_use]
+    pub fn eap() -> Self {
+        unsafe { Self(kSecPolicyAppleEAP) }
+    }
+
+    /// The IP Security policy, `kSecPolicyAppleIPsec`.
+    #[inline(always)]
+    #[must_use]
+    pub fn ipsec() -> Self {
+        unsafe { Self(kSecPolicyAppleIPsec) }
+    }
+
+    /// The code signing policy, `kSecPolicyAppleCodeSigning`.
+    #[cfg(target_os = "macos")]
+    #[inline(always)]
+    #[must_use]
+    pub fn code_signing() -> Self {
+        unsafe { Self(kSecPolicyAppleCodeSigning) }
+    }
+
+    /// The Apple ID validation policy, `kSecPolicyAppleIDValidation`.
+    #[inline(always)]
+    #[must_use]
+    pub fn apple_id_validation() -> Self {
+        unsafe { Self(kSecPolicyAppleIDValidation) }
+    }
+
+    /// The time stamping policy, `kSecPolicyAppleTimeStamping`.
+    #[inline(always)]
+    #[must_use]
+    pub fn time_stamping() -> Self {
+        unsafe { Self(kSecPolicyAppleTimeStamping) }
+    }
+
+    /// The revocation policy, `kSecPolicyAppleRevocation`.
+    #[inline(always)]
+    #[must_use]
+    pub fn revocation() -> Self {
+        unsafe { Self(kSecPolicyAppleRevocation) }
+    }
+
+    #[inline]
+    fn to_value(self) -> CFString {
+        unsafe { CFString::wrap_under_get_rule(self.0) }
+    }
+}
+
+/// A builder for the properties passed to `SecPolicy::create_with_properties`.
+#[derive(Debug, Clone, Default)]
+pub struct PolicyOptions {
+    name: Option<String>,
+    client: Option<bool>,
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    revocation_flags: Option<RevocationPolicy>,
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    team_identifier: Option<String>,
+}
+
+impl PolicyOptions {
+    /// Creates a new builder with no properties set.
+    #[inline(always)]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the name the certificate must match, `kSecPolicyName`.
+    ///
+    /// This is the hostname for SSL and IP Security policies, and the email address for S/MIME.
+    #[inline]
+    pub fn name(&mut self, name: &str) -> &mut Self {
+        self.name = Some(name.to_owned());
+        self
+    }
+
+    /// Sets which side's certificate is being evaluated, `kSecPolicyClient`.
+    ///
+    /// As with `SecPolicy::create_ssl`, pass `SslProtocolSide::SERVER` to validate a server's
+    /// certificate chain, and `SslProtocolSide::CLIENT` to validate a client's.
+    #[inline]
+    pub fn protocol_side(&mut self, protocol_side: SslProtocolSide) -> &mut Self {
+        self.client = Some(protocol_side == SslProtocolSide::CLIENT);
+        self
+    }
+
+    /// Sets the revocation checking flags, `kSecPolicyRevocationFlags`.
+    ///
+    /// This is only used with `PolicyIdentifier::revocation()`.
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    #[inline]
+    pub fn revocation_flags(&mut self, flags: RevocationPolicy) -> &mut Self {
+        self.revocation_flags = Some(flags);
+        self
+    }
+
+    /// Sets the team identifier the certificate must contain, `kSecPolicyTeamIdentifier`.
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    #[inline]
+    pub fn team_identifier(&mut self, team_identifier: &str) -> &mut Self {
+        self.team_identifier = Some(team_identifier.to_owned());
+        self
+    }
+
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    fn to_dictionary(&self) -> CFDictionary<CFString, CFType> {
+        let mut params = vec![];
+        unsafe {
+            if let Some(ref name) = self.name {
+                params.push((
+                    CFString::wrap_under_get_rule(kSecPolicyName),
+                    CFString::new(name).into_CFType(),
+                ));
+            }
+            if let Some(client) = self.client {
+                params.push((
+                    CFString::wrap_under_get_rule(kSecPolicyClient),
+                    CFBoolean::from(client).into_CFType(),
+                ));
+            }
+            if let Some(flags) = self.revocation_flags {
+                params.push((
+                    CFString::wrap_under_get_rule(kSecPolicyRevocationFlags),
+                    CFNumber::from(flags.bits() as i64).into_CFType(),
+                ));
+            }
+            if let Some(ref team_identifier) = self.team_identifier {
+                params.push((
+                    CFString::wrap_under_get_rule(kSecPolicyTeamIdentifier),
+                    CFString::new(team_identifier).into_CFType(),
+                ));
+            }
+        }
+        CFDictionary::from_CFType_pairs(&params)
+    }
+}
+
+/// The properties of an existing policy, as returned by `SecPolicy::properties`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyProperties {
+    oid: Option<String>,
+    name: Option<String>,
+    client: bool,
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    revocation_flags: Option<RevocationPolicy>,
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    team_identifier: Option<String>,
+}
+
+impl PolicyProperties {
+    /// The policy identifier, `kSecPolicyOid`.
+    ///
+    /// This is the string value of one of the `PolicyIdentifier` constants, e.g. `1.2.840.113635.100.1.3` for SSL.
+    #[inline(always)]
+    #[must_use]
+    pub fn oid(&self) -> Option<&str> {
+        self.oid.as_deref()
+    }
+
+    /// The name the certificate must match, `kSecPolicyName`.
+    #[inline(always)]
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Which side's certificate the policy evaluates, derived from `kSecPolicyClient`.
+    #[inline]
+    #[must_use]
+    pub fn protocol_side(&self) -> SslProtocolSide {
+        if self.client {
+            SslProtocolSide::CLIENT
+        } else {
+            SslProtocolSide::SERVER
+        }
+    }
+
+    /// The revocation checking flags, `kSecPolicyRevocationFlags`.
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    #[inline(always)]
+    #[must_use]
+    pub fn revocation_flags(&self) -> Option<RevocationPolicy> {
+        self.revocation_flags
+    }
+
+    /// The team identifier the certificate must contain, `kSecPolicyTeamIdentifier`.
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    #[inline(always)]
+    #[must_use]
+    pub fn team_identifier(&self) -> Option<&str> {
+        self.team_identifier.as_deref()
+    }
+
+    fn from_dictionary(dict: &CFDictionary<CFString, CFType>) -> Self {
+        let string = |key: CFStringRef| {
+            dict.find(unsafe { CFString::wrap_under_get_rule(key) })
+                .and_then(|value| value.downcast::<CFString>())
+                .map(|value| value.to_string())
+        };
+        Self {
+            oid: string(unsafe { kSecPolicyOid }),
+            name: string(unsafe { kSecPolicyName }),
+            client: dict
+                .find(unsafe { CFString::wrap_under_get_rule(kSecPolicyClient) })
+                .and_then(|value| value.downcast::<CFBoolean>())
+                .map_or(false, bool::from),
+            #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+            revocation_flags: dict
+                .find(unsafe { CFString::wrap_under_get_rule(kSecPolicyRevocationFlags) })
+                .and_then(|value| value.downcast::<CFNumber>())
+                .and_then(|value| value.to_i64())
+                .map(|bits| RevocationPolicy::from_bits_truncate(bits as CFOptionFlags)),
+            #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+            team_identifier: string(unsafe { kSecPolicyTeamIdentifier }),
+        }
+    }
+}
+
 impl SecPolicy {
     /// Creates a `SecPolicy` for evaluating SSL certificate chains.
     ///
@@ -90,15 +342,133 @@ impl SecPolicy {
             Self::wrap_under_create_rule(policy)
         }
     }
+
+    /// Creates a policy of the given kind, configured with the given properties.
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub fn create_with_properties(identifier: PolicyIdentifier, options: &PolicyOptions) -> crate::Result<Self> {
+        let properties = options.to_dictionary();
+        let policy = unsafe {
+            SecPolicyCreateWithProperties(identifier.to_value().as_CFTypeRef(), properties.as_concrete_TypeRef())
+        };
+
+        if policy.is_null() {
+            Err(Error::from_code(errSecParam))
+        } else {
+            Ok(unsafe { Self::wrap_under_create_rule(policy) })
+        }
+    }
+
+    /// Creates a policy for evaluating S/MIME certificates, optionally
+    /// requiring them to be issued for the given email address.
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub fn create_smime(email: Option<&str>) -> crate::Result<Self> {
+        Self::create_named(PolicyIdentifier::smime(), None, email)
+    }
+
+    /// Creates a policy for evaluating code signing certificates.
+    #[cfg(all(target_os = "macos", feature = "OSX_10_9"))]
+    pub fn create_code_signing() -> crate::Result<Self> {
+        Self::create_named(PolicyIdentifier::code_signing(), None, None)
+    }
+
+    /// Creates a policy for evaluating Extensible Authentication Protocol certificates.
+    ///
+    /// The side which you are evaluating should be provided, as with `create_ssl`.
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub fn create_eap(protocol_side: SslProtocolSide, server_name: Option<&str>) -> crate::Result<Self> {
+        Self::create_named(PolicyIdentifier::eap(), Some(protocol_side), server_name)
+    }
+
+    /// Creates a policy for evaluating IP Security certificates.
+    ///
+    /// The side which you are evaluating should be provided, as with `create_ssl`.
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub fn create_ipsec(protocol_side: SslProtocolSide, hostname: Option<&str>) -> crate::Result<Self> {
+        Self::create_named(PolicyIdentifier::ipsec(), Some(protocol_side), hostname)
+    }
+
+    /// Creates a policy for evaluating Apple ID certificates.
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub fn create_apple_id_validation() -> crate::Result<Self> {
+        Self::create_named(PolicyIdentifier::apple_id_validation(), None, None)
+    }
+
+    /// Creates a policy for evaluating time stamping certificates.
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub fn create_time_stamping() -> crate::Result<Self> {
+        Self::create_named(PolicyIdentifier::time_stamping(), None, None)
+    }
+
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    fn create_named(identifier: PolicyIdentifier, protocol_side: Option<SslProtocolSide>, name: Option<&str>) -> crate::Result<Self> {
+        let mut options = PolicyOptions::new();
+        if let Some(protocol_side) = protocol_side {
+            options.protocol_side(protocol_side);
+        }
+        if let Some(name) = name {
+            options.name(name);
+        }
+        Self::create_with_properties(identifier, &options)
+    }
+
+    /// Returns the properties of this policy.
+    #[must_use]
+    pub fn properties(&self) -> PolicyProperties {
+        unsafe {
+            let properties = SecPolicyCopyProperties(self.0);
+            if properties.is_null() {
+                return PolicyProperties::from_dictionary(&CFDictionary::from_CFType_pairs(&[]));
+            }
+            PolicyProperties::from_dictionary(&CFDictionary::wrap_under_create_rule(properties))
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::policy::SecPolicy;
+    use crate::policy::*;
     use crate::secure_transport::SslProtocolSide;
 
     #[test]
     fn create_ssl() {
         SecPolicy::create_ssl(SslProtocolSide::SERVER, Some("certifi.org"));
     }
+
+    #[test]
+    fn ssl_properties() {
+        let policy = SecPolicy::create_ssl(SslProtocolSide::SERVER, Some("certifi.org"));
+        let properties = policy.properties();
+        assert_eq!(Some("certifi.org"), properties.name());
+        assert_eq!(SslProtocolSide::SERVER, properties.protocol_side());
+        #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+        assert_eq!(PolicyIdentifier::ssl().to_value().to_string().as_str(), properties.oid().unwrap());
+    }
+
+    #[test]
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    fn create_with_properties() {
+        let policy = SecPolicy::create_with_properties(
+            PolicyIdentifier::ssl(),
+            PolicyOptions::new()
+                .name("certifi.org")
+                .protocol_side(SslProtocolSide::CLIENT),
+        )
+        .unwrap();
+        let properties = policy.properties();
+        assert_eq!(Some("certifi.org"), properties.name());
+        assert_eq!(SslProtocolSide::CLIENT, properties.protocol_side());
+    }
+
+    #[test]
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    fn create_named() {
+        let smime = SecPolicy::create_smime(Some("foo@example.com")).unwrap();
+        assert_eq!(Some("foo@example.com"), smime.properties().name());
+        SecPolicy::create_eap(SslProtocolSide::SERVER, Some("radius.example.com")).unwrap();
+        SecPolicy::create_ipsec(SslProtocolSide::SERVER, Some("vpn.example.com")).unwrap();
+        SecPolicy::create_apple_id_validation().unwrap();
+        SecPolicy::create_time_stamping().unwrap();
+        #[cfg(target_os = "macos")]
+        SecPolicy::create_code_signing().unwrap();
+    }
 }
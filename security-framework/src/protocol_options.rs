@@ -0,0 +1,694 @@
+//! TLS configuration for Network.framework connections.
+//!
+//! Secure Transport is deprecated and does not negotiate TLS 1.3 everywhere.
+//! Network.framework implements TLS for `nw_connection` itself, configured
+//! through the `sec_protocol_options_t` of its TLS protocol options and
+//! described by the `sec_protocol_metadata_t` of established connections.
+//!
+//! [`ClientOptions`] takes the settings of
+//! [`ClientBuilder`](crate::secure_transport::ClientBuilder) and applies them
+//! to [`ProtocolOptions`]. Requires macOS 10.15 or iOS 13.
+
+use core_foundation::array::CFArray;
+use core_foundation::base::TCFType;
+use security_framework_sys::base::errSecParam;
+use security_framework_sys::network::{nw_protocol_options_t, nw_release, nw_tls_copy_sec_protocol_options, nw_tls_create_options};
+use security_framework_sys::sec_protocol::*;
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::os::raw::c_char;
+use std::ptr;
+use std::rc::Rc;
+
+use crate::certificate::SecCertificate;
+use crate::cipher_suite::{Authentication, CipherSuite, CipherSuiteInfo, KeyExchange};
+use crate::dispatch::{DispatchData, DispatchQueue};
+use crate::identity::SecIdentity;
+use crate::secure_transport::{PreSharedKey, SslProtocol, SslProtocolSide};
+use crate::trust::SecTrust;
+use crate::trust_policy::TrustPolicy;
+use crate::{Error, Result};
+
+/// A TLS or DTLS protocol version.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TlsProtocolVersion(tls_protocol_version_t);
+
+#[allow(missing_docs)]
+impl TlsProtocolVersion {
+    pub const TLS1: Self = Self(tls_protocol_version_TLSv10);
+    pub const TLS11: Self = Self(tls_protocol_version_TLSv11);
+    pub const TLS12: Self = Self(tls_protocol_version_TLSv12);
+    pub const TLS13: Self = Self(tls_protocol_version_TLSv13);
+    pub const DTLS1: Self = Self(tls_protocol_version_DTLSv10);
+    pub const DTLS12: Self = Self(tls_protocol_version_DTLSv12);
+
+    #[inline(always)]
+    #[must_use]
+    pub fn from_raw(raw: tls_protocol_version_t) -> Self {
+        Self(raw)
+    }
+
+    #[inline(always)]
+    #[must_use]
+    pub fn to_raw(self) -> tls_protocol_version_t {
+        self.0
+    }
+}
+
+impl TryFrom<SslProtocol> for TlsProtocolVersion {
+    type Error = Error;
+
+    /// Converts a Secure Transport protocol version, returning `errSecParam`
+    /// for SSL versions, which Network.framework does not implement.
+    fn try_from(protocol: SslProtocol) -> Result<Self> {
+        match protocol {
+            SslProtocol::TLS1 => Ok(Self::TLS1),
+            SslProtocol::TLS11 => Ok(Self::TLS11),
+            SslProtocol::TLS12 => Ok(Self::TLS12),
+            SslProtocol::TLS13 => Ok(Self::TLS13),
+            SslProtocol::DTLS1 => Ok(Self::DTLS1),
+            _ => Err(Error::from_code(errSecParam)),
+        }
+    }
+}
+
+/// The TLS options of a Network.framework connection.
+pub struct ProtocolOptions {
+    options: sec_protocol_options_t,
+    // the `nw_protocol_options_t` the options belong to, if created here
+    tls: nw_protocol_options_t,
+}
+
+unsafe impl Send for ProtocolOptions {}
+
+impl fmt::Debug for ProtocolOptions {
+    #[cold]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_tuple("ProtocolOptions").field(&self.options).finish()
+    }
+}
+
+impl Default for ProtocolOptions {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProtocolOptions {
+    /// Creates new TLS protocol options.
+    ///
+    /// The `nw_protocol_options_t` for `nw_parameters` is available from
+    /// `as_nw_protocol_options`.
+    #[must_use]
+    pub fn new() -> Self {
+        unsafe {
+            let tls = nw_tls_create_options();
+            Self {
+                options: nw_tls_copy_sec_protocol_options(tls),
+                tls,
+            }
+        }
+    }
+
+    /// Wraps a raw `sec_protocol_options_t`, such as the one of the TLS
+    /// options passed to the configuration block of
+    /// `nw_parameters_create_secure_tcp`, taking an additional reference to it.
+    ///
+    /// # Safety
+    ///
+    /// `options` must be valid protocol options.
+    #[must_use]
+    pub unsafe fn wrap_under_get_rule(options: sec_protocol_options_t) -> Self {
+        Self {
+            options: sec_retain(options.cast()).cast(),
+            tls: ptr::null_mut(),
+        }
+    }
+
+    /// Returns the raw `sec_protocol_options_t`.
+    #[inline(always)]
+    #[must_use]
+    pub fn as_raw(&self) -> sec_protocol_options_t {
+        self.options
+    }
+
+    /// Returns the raw `nw_protocol_options_t` the options were created from
+    /// by `new`, or null for wrapped options.
+    #[inline(always)]
+    #[must_use]
+    pub fn as_nw_protocol_options(&self) -> nw_protocol_options_t {
+        self.tls
+    }
+
+    /// Sets the minimum protocol version.
+    pub fn set_protocol_version_min(&mut self, min_version: TlsProtocolVersion) {
+        unsafe { sec_protocol_options_set_min_tls_protocol_version(self.options, min_version.0) }
+    }
+
+    /// Sets the maximum protocol version.
+    pub fn set_protocol_version_max(&mut self, max_version: TlsProtocolVersion) {
+        unsafe { sec_protocol_options_set_max_tls_protocol_version(self.options, max_version.0) }
+    }
+
+    /// Sets the server name sent in the SNI extension.
+    pub fn set_peer_domain_name(&mut self, server_name: &str) -> Result<()> {
+        let server_name = CString::new(server_name).map_err(|_| Error::from_code(errSecParam))?;
+        unsafe { sec_protocol_options_set_tls_server_name(self.options, server_name.as_ptr()) }
+        Ok(())
+    }
+
+    /// Adds an ALPN protocol, in order of preference.
+    pub fn add_alpn_protocol(&mut self, protocol: &str) -> Result<()> {
+        let protocol = CString::new(protocol).map_err(|_| Error::from_code(errSecParam))?;
+        unsafe { sec_protocol_options_add_tls_application_protocol(self.options, protocol.as_ptr()) }
+        Ok(())
+    }
+
+    /// Sets the certificate to use for this side of the connection, followed
+    /// by the intermediate certificates to send along with it.
+    pub fn set_certificate(&mut self, identity: &SecIdentity, certs: &[SecCertificate]) -> Result<()> {
+        unsafe {
+            let identity = if certs.is_empty() {
+                sec_identity_create(identity.as_concrete_TypeRef())
+            } else {
+                let certs = CFArray::from_CFTypes(certs);
+                sec_identity_create_with_certificates(identity.as_concrete_TypeRef(), certs.as_concrete_TypeRef())
+            };
+            if identity.is_null() {
+                return Err(Error::from_code(errSecParam));
+            }
+            sec_protocol_options_set_local_identity(self.options, identity);
+            sec_release(identity.cast());
+        }
+        Ok(())
+    }
+
+    /// Adds a cipher suite, in order of preference.
+    ///
+    /// Once a suite is added, only the added suites are offered, including
+    /// for TLS 1.3.
+    pub fn append_cipher_suite(&mut self, suite: CipherSuite) -> Result<()> {
+        let suite = tls_ciphersuite(suite).ok_or_else(|| Error::from_code(errSecParam))?;
+        unsafe { sec_protocol_options_append_tls_ciphersuite(self.options, suite) }
+        Ok(())
+    }
+
+    /// Adds an external pre-shared key and the identity naming it.
+    pub fn add_pre_shared_key(&mut self, psk: &[u8], psk_identity: &[u8]) {
+        let psk = DispatchData::new(psk);
+        let psk_identity = DispatchData::new(psk_identity);
+        unsafe { sec_protocol_options_add_pre_shared_key(self.options, psk.as_raw(), psk_identity.as_raw()) }
+    }
+
+    /// Sets the identity hint a server sends for pre-shared keys.
+    pub fn set_pre_shared_key_identity_hint(&mut self, hint: &[u8]) {
+        let hint = DispatchData::new(hint);
+        unsafe { sec_protocol_options_set_tls_pre_shared_key_identity_hint(self.options, hint.as_raw()) }
+    }
+
+    /// Sets whether the peer must authenticate with a certificate.
+    ///
+    /// This defaults to `true` for clients and `false` for servers.
+    pub fn set_peer_authentication_required(&mut self, required: bool) {
+        unsafe { sec_protocol_options_set_peer_authentication_required(self.options, required) }
+    }
+
+    /// Sets whether session tickets are enabled.
+    pub fn set_session_tickets_enabled(&mut self, enabled: bool) {
+        unsafe { sec_protocol_options_set_tls_tickets_enabled(self.options, enabled) }
+    }
+
+    /// Sets whether sessions may be resumed.
+    pub fn set_resumption_enabled(&mut self, enabled: bool) {
+        unsafe { sec_protocol_options_set_tls_resumption_enabled(self.options, enabled) }
+    }
+
+    /// Sets whether application data may be sent before the handshake has
+    /// finished.
+    pub fn set_false_start_enabled(&mut self, enabled: bool) {
+        unsafe { sec_protocol_options_set_tls_false_start_enabled(self.options, enabled) }
+    }
+
+    /// Sets whether OCSP responses are requested from the server.
+    pub fn set_ocsp_enabled(&mut self, enabled: bool) {
+        unsafe { sec_protocol_options_set_tls_ocsp_enabled(self.options, enabled) }
+    }
+
+    /// Sets whether signed certificate timestamps are requested from the
+    /// server.
+    pub fn set_sct_enabled(&mut self, enabled: bool) {
+        unsafe { sec_protocol_options_set_tls_sct_enabled(self.options, enabled) }
+    }
+
+    /// Replaces the evaluation of the peer's certificate by `verify`, which
+    /// is called on `queue` and returns whether the peer is trusted.
+    ///
+    /// The trust is set up for the peer's certificates and the default
+    /// policies; `verify` may change it before evaluating it.
+    pub fn set_verify_block<F>(&mut self, queue: &DispatchQueue, verify: F)
+    where
+        F: Fn(&ProtocolMetadata, SecTrust) -> bool + Send + Sync + 'static,
+    {
+        let block = block::ConcreteBlock::new(
+            move |metadata: sec_protocol_metadata_t, trust: sec_trust_t, complete: sec_protocol_verify_complete_t| {
+                let trusted = unsafe {
+                    let metadata = ProtocolMetadata::wrap_under_get_rule(metadata);
+                    let trust = sec_trust_copy_ref(trust);
+                    !trust.is_null() && verify(&metadata, SecTrust::wrap_under_create_rule(trust))
+                };
+                let complete = unsafe { &*complete.cast::<block::Block<(bool,), ()>>() };
+                unsafe { complete.call((trusted,)) }
+            },
+        );
+        let block = block.copy();
+        let block: &block::Block<_, _> = &block;
+        unsafe { sec_protocol_options_set_verify_block(self.options, ptr::addr_of!(*block).cast(), queue.as_raw()) }
+    }
+}
+
+impl Drop for ProtocolOptions {
+    fn drop(&mut self) {
+        unsafe {
+            sec_release(self.options.cast());
+            if !self.tls.is_null() {
+                nw_release(self.tls);
+            }
+        }
+    }
+}
+
+/// The negotiated parameters of a Network.framework TLS connection.
+pub struct ProtocolMetadata(sec_protocol_metadata_t);
+
+impl fmt::Debug for ProtocolMetadata {
+    #[cold]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("ProtocolMetadata")
+            .field("protocol_version", &self.negotiated_protocol_version())
+            .field("cipher_suite", &self.negotiated_cipher_suite())
+            .field("alpn_protocol", &self.alpn_protocol())
+            .finish()
+    }
+}
+
+impl ProtocolMetadata {
+    /// Wraps a raw `sec_protocol_metadata_t`, taking an additional reference
+    /// to it.
+    ///
+    /// # Safety
+    ///
+    /// `metadata` must be valid protocol metadata.
+    #[must_use]
+    pub unsafe fn wrap_under_get_rule(metadata: sec_protocol_metadata_t) -> Self {
+        Self(sec_retain(metadata.cast()).cast())
+    }
+
+    /// Wraps a raw `sec_protocol_metadata_t` returned by a `copy` function,
+    /// such as `nw_tls_copy_sec_protocol_metadata`.
+    ///
+    /// # Safety
+    ///
+    /// `metadata` must be valid protocol metadata the caller owns a reference to.
+    #[must_use]
+    pub unsafe fn wrap_under_create_rule(metadata: sec_protocol_metadata_t) -> Self {
+        Self(metadata)
+    }
+
+    /// Returns the raw `sec_protocol_metadata_t`.
+    #[inline(always)]
+    #[must_use]
+    pub fn as_raw(&self) -> sec_protocol_metadata_t {
+        self.0
+    }
+
+    /// Returns the negotiated protocol version.
+    #[must_use]
+    pub fn negotiated_protocol_version(&self) -> TlsProtocolVersion {
+        unsafe { TlsProtocolVersion(sec_protocol_metadata_get_negotiated_tls_protocol_version(self.0)) }
+    }
+
+    /// Returns the negotiated cipher suite.
+    #[must_use]
+    pub fn negotiated_cipher_suite(&self) -> CipherSuite {
+        unsafe { cipher_suite(sec_protocol_metadata_get_negotiated_tls_ciphersuite(self.0)) }
+    }
+
+    /// Returns the protocol negotiated with ALPN, if any.
+    #[must_use]
+    pub fn alpn_protocol(&self) -> Option<String> {
+        unsafe { string(sec_protocol_metadata_get_negotiated_protocol(self.0)) }
+    }
+
+    /// Returns the server name sent by the client.
+    #[must_use]
+    pub fn server_name(&self) -> Option<String> {
+        unsafe { string(sec_protocol_metadata_get_server_name(self.0)) }
+    }
+
+    /// Returns whether the server accepted TLS 1.3 early data.
+    #[must_use]
+    pub fn early_data_accepted(&self) -> bool {
+        unsafe { sec_protocol_metadata_get_early_data_accepted(self.0) }
+    }
+
+    /// Returns the certificates presented by the peer, leaf first.
+    #[must_use]
+    pub fn peer_certificates(&self) -> Vec<SecCertificate> {
+        let certs = Rc::new(RefCell::new(Vec::new()));
+        let block_certs = Rc::clone(&certs);
+        let block = block::ConcreteBlock::new(move |certificate: sec_certificate_t| unsafe {
+            let certificate = sec_certificate_copy_ref(certificate);
+            if !certificate.is_null() {
+                block_certs.borrow_mut().push(SecCertificate::wrap_under_create_rule(certificate));
+            }
+        });
+        let block = block.copy();
+        let block: &block::Block<_, _> = &block;
+        unsafe {
+            sec_protocol_metadata_access_peer_certificate_chain(self.0, ptr::addr_of!(*block).cast());
+        }
+        certs.take()
+    }
+
+    /// Returns the public key of the peer's certificate.
+    #[must_use]
+    pub fn peer_public_key(&self) -> Option<Vec<u8>> {
+        unsafe { DispatchData::wrap_under_create_rule(sec_protocol_metadata_copy_peer_public_key(self.0)) }
+            .map(|key| key.to_vec())
+    }
+
+    /// Derives keying material from the session, as defined by RFC 5705 for
+    /// TLS 1.2 and RFC 8446 for TLS 1.3, with an empty context.
+    pub fn export_keying_material(&self, label: &str, len: usize) -> Result<Vec<u8>> {
+        let secret = unsafe {
+            DispatchData::wrap_under_create_rule(sec_protocol_metadata_create_secret(
+                self.0,
+                label.len(),
+                label.as_ptr().cast::<c_char>(),
+                len,
+            ))
+        };
+        secret.map(|secret| secret.to_vec()).ok_or_else(|| Error::from_code(errSecParam))
+    }
+}
+
+impl Clone for ProtocolMetadata {
+    #[inline]
+    fn clone(&self) -> Self {
+        unsafe { Self::wrap_under_get_rule(self.0) }
+    }
+}
+
+impl Drop for ProtocolMetadata {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { sec_release(self.0.cast()) }
+    }
+}
+
+unsafe fn string(ptr: *const c_char) -> Option<String> {
+    if ptr.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(ptr).to_string_lossy().into_owned())
+    }
+}
+
+// `SSLCipherSuite` is `u32` on x86_64 but `u16`, like `tls_ciphersuite_t`, on
+// Apple silicon and iOS, where these conversions do nothing.
+#[allow(clippy::useless_conversion)]
+fn tls_ciphersuite(suite: CipherSuite) -> Option<tls_ciphersuite_t> {
+    suite.to_raw().try_into().ok()
+}
+
+#[allow(clippy::useless_conversion)]
+fn cipher_suite(suite: tls_ciphersuite_t) -> CipherSuite {
+    CipherSuite::from_raw(suite.into())
+}
+
+/// Client side TLS settings for Network.framework connections, mirroring
+/// [`ClientBuilder`](crate::secure_transport::ClientBuilder).
+#[derive(Debug, Default, Clone)]
+pub struct ClientOptions {
+    identity: Option<SecIdentity>,
+    chain: Vec<SecCertificate>,
+    protocol_min: Option<SslProtocol>,
+    protocol_max: Option<SslProtocol>,
+    trust_policy: TrustPolicy,
+    danger_accept_invalid_certs: bool,
+    danger_accept_invalid_hostnames: bool,
+    whitelisted_ciphers: Vec<CipherSuite>,
+    alpn: Vec<String>,
+    psks: Vec<PreSharedKey>,
+}
+
+impl ClientOptions {
+    /// Creates new options with the system defaults.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Specifies the set of root certificates to trust when
+    /// verifying the server's certificate.
+    #[inline]
+    pub fn anchor_certificates(&mut self, certs: &[SecCertificate]) -> &mut Self {
+        self.trust_policy.anchor_certificates(certs);
+        self
+    }
+
+    /// Add the certificate the set of root certificates to trust
+    /// when verifying the server's certificate.
+    #[inline]
+    pub fn add_anchor_certificate(&mut self, cert: &SecCertificate) -> &mut Self {
+        self.trust_policy.add_anchor_certificate(cert);
+        self
+    }
+
+    /// Specifies whether to trust the built-in certificates in addition
+    /// to specified anchor certificates.
+    #[inline(always)]
+    pub fn trust_anchor_certificates_only(&mut self, only: bool) -> &mut Self {
+        self.trust_policy.system_roots(!only);
+        self
+    }
+
+    /// Specifies the policy used to verify the server's certificate, replacing
+    /// the anchors set previously.
+    ///
+    /// Stapled OCSP responses and SCTs are requested from the server if the
+    /// policy requires them.
+    #[inline]
+    pub fn trust_policy(&mut self, policy: &TrustPolicy) -> &mut Self {
+        self.trust_policy = policy.clone();
+        self
+    }
+
+    /// Specifies whether to trust invalid certificates.
+    ///
+    /// # Warning
+    ///
+    /// You should think very carefully before using this method. If invalid
+    /// certificates are trusted, *any* certificate for *any* site will be
+    /// trusted for use. This includes expired certificates. This introduces
+    /// significant vulnerabilities, and should only be used as a last resort.
+    #[inline(always)]
+    pub fn danger_accept_invalid_certs(&mut self, noverify: bool) -> &mut Self {
+        self.danger_accept_invalid_certs = noverify;
+        self
+    }
+
+    /// Specifies whether to verify that the server's hostname matches its certificate.
+    ///
+    /// # Warning
+    ///
+    /// You should think very carefully before using this method. If hostnames are not verified,
+    /// *any* valid certificate for *any* site will be trusted for use. This introduces significant
+    /// vulnerabilities, and should only be used as a last resort.
+    #[inline(always)]
+    pub fn danger_accept_invalid_hostnames(&mut self, danger_accept_invalid_hostnames: bool) -> &mut Self {
+        self.danger_accept_invalid_hostnames = danger_accept_invalid_hostnames;
+        self
+    }
+
+    /// Set a whitelist of enabled ciphers. Any ciphers not whitelisted will be disabled.
+    #[inline]
+    pub fn whitelist_ciphers(&mut self, whitelisted_ciphers: &[CipherSuite]) -> &mut Self {
+        self.whitelisted_ciphers = whitelisted_ciphers.to_owned();
+        self
+    }
+
+    /// Use the specified identity as a SSL/TLS client certificate.
+    #[inline]
+    pub fn identity(&mut self, identity: &SecIdentity, chain: &[SecCertificate]) -> &mut Self {
+        self.identity = Some(identity.clone());
+        self.chain = chain.to_owned();
+        self
+    }
+
+    /// Configure the minimum protocol that this client will support.
+    #[inline(always)]
+    pub fn protocol_min(&mut self, min: SslProtocol) -> &mut Self {
+        self.protocol_min = Some(min);
+        self
+    }
+
+    /// Configure the maximum protocol that this client will support.
+    #[inline(always)]
+    pub fn protocol_max(&mut self, max: SslProtocol) -> &mut Self {
+        self.protocol_max = Some(max);
+        self
+    }
+
+    /// Configures the set of protocols used for ALPN.
+    pub fn alpn_protocols(&mut self, protocols: &[&str]) -> &mut Self {
+        self.alpn = protocols.iter().map(|&s| s.to_owned()).collect();
+        self
+    }
+
+    /// Adds an external pre-shared key and the identity naming it.
+    ///
+    /// Unless ciphers are whitelisted, this enables the PSK cipher suites
+    /// and only those, so the connection uses TLS 1.2.
+    pub fn pre_shared_key(&mut self, psk: &[u8], psk_identity: &[u8]) -> &mut Self {
+        self.psks.push(PreSharedKey {
+            key: psk.to_owned(),
+            identity: psk_identity.to_owned(),
+        });
+        self
+    }
+
+    /// Creates protocol options for a connection to `domain`.
+    pub fn build(&self, domain: &str) -> Result<ProtocolOptions> {
+        let mut options = ProtocolOptions::new();
+        self.configure(&mut options, domain)?;
+        Ok(options)
+    }
+
+    /// Applies the settings to existing protocol options for a connection to
+    /// `domain`.
+    pub fn configure(&self, options: &mut ProtocolOptions, domain: &str) -> Result<()> {
+        options.set_peer_domain_name(domain)?;
+        if let Some(min) = self.protocol_min {
+            options.set_protocol_version_min(min.try_into()?);
+        }
+        if let Some(max) = self.protocol_max {
+            options.set_protocol_version_max(max.try_into()?);
+        }
+        for protocol in &self.alpn {
+            options.add_alpn_protocol(protocol)?;
+        }
+        for &suite in &self.whitelisted_ciphers {
+            options.append_cipher_suite(suite)?;
+        }
+        if self.whitelisted_ciphers.is_empty() && !self.psks.is_empty() {
+            for suite in psk_cipher_suites() {
+                options.append_cipher_suite(suite)?;
+            }
+        }
+        if let Some(identity) = &self.identity {
+            options.set_certificate(identity, &self.chain)?;
+        }
+        for psk in &self.psks {
+            options.add_pre_shared_key(&psk.key, &psk.identity);
+        }
+
+        if self.trust_policy.requires_ocsp_stapling() {
+            options.set_ocsp_enabled(true);
+        }
+        if self.trust_policy.requires_certificate_transparency() {
+            options.set_sct_enabled(true);
+        }
+
+        // the default evaluation already checks the system anchors and the domain
+        if self.trust_policy != TrustPolicy::default()
+            || self.danger_accept_invalid_certs
+            || self.danger_accept_invalid_hostnames
+        {
+            let policy = self.trust_policy.clone();
+            let danger_accept_invalid_certs = self.danger_accept_invalid_certs;
+            let domain = if self.danger_accept_invalid_hostnames {
+                None
+            } else {
+                Some(policy.hostname(domain).to_owned())
+            };
+            options.set_verify_block(&DispatchQueue::global(), move |_, mut trust| {
+                danger_accept_invalid_certs
+                    || policy.verify(&mut trust, SslProtocolSide::SERVER, domain.as_deref()).is_ok()
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Returns the cipher suites enabled for pre-shared keys: those with AEAD
+/// ciphers not requiring a certificate, forward secret ones first.
+pub(crate) fn psk_cipher_suites() -> Vec<CipherSuite> {
+    let mut suites = CipherSuite::known()
+        .filter(|suite| {
+            suite.info().map_or(false, |info| {
+                info.authentication == Authentication::Psk && info.key_exchange != KeyExchange::Rsa && info.is_aead()
+            })
+        })
+        .collect::<Vec<_>>();
+    suites.sort_by_key(|suite| !suite.info().map_or(false, CipherSuiteInfo::forward_secrecy));
+    suites
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn protocol_versions() {
+        assert_eq!(TlsProtocolVersion::TLS13, SslProtocol::TLS13.try_into().unwrap());
+        assert_eq!(TlsProtocolVersion::TLS1, SslProtocol::TLS1.try_into().unwrap());
+        assert!(TlsProtocolVersion::try_from(SslProtocol::SSL3).is_err());
+        assert_eq!(0x0303, TlsProtocolVersion::TLS12.to_raw());
+    }
+
+    #[test]
+    fn build_client_options() {
+        let mut client = ClientOptions::new();
+        client
+            .anchor_certificates(&[crate::test::certificate()])
+            .trust_anchor_certificates_only(true)
+            .protocol_min(SslProtocol::TLS12)
+            .protocol_max(SslProtocol::TLS13)
+            .alpn_protocols(&["h2", "http/1.1"])
+            .whitelist_ciphers(&[CipherSuite::TLS_AES_128_GCM_SHA256])
+            .pre_shared_key(b"secret", b"client");
+        let options = p!(client.build("foobar.com"));
+        assert!(!options.as_raw().is_null());
+        assert!(!options.as_nw_protocol_options().is_null());
+
+        client.protocol_min(SslProtocol::SSL3);
+        assert!(client.build("foobar.com").is_err());
+        assert!(ClientOptions::new().build("foo\0bar.com").is_err());
+    }
+
+    #[test]
+    fn psk_suites() {
+        assert_eq!(
+            vec![
+                CipherSuite::TLS_DHE_PSK_WITH_AES_128_GCM_SHA256,
+                CipherSuite::TLS_DHE_PSK_WITH_AES_256_GCM_SHA384,
+                CipherSuite::TLS_PSK_WITH_AES_128_GCM_SHA256,
+                CipherSuite::TLS_PSK_WITH_AES_256_GCM_SHA384,
+            ],
+            psk_cipher_suites()
+        );
+    }
+
+    #[test]
+    fn wrap_options() {
+        let options = ProtocolOptions::new();
+        let mut wrapped = unsafe { ProtocolOptions::wrap_under_get_rule(options.as_raw()) };
+        drop(options);
+        wrapped.set_session_tickets_enabled(false);
+        assert!(wrapped.as_nw_protocol_options().is_null());
+    }
+}
@@ -0,0 +1,316 @@
+//! Signed certificate timestamp inspection.
+//!
+//! Certificate Transparency logs issue signed certificate timestamps (SCTs)
+//! as a promise to include a certificate. These are the values passed to
+//! `SecTrust::set_signed_certificate_timestamps`. Parsing does not verify the
+//! log's signature; that happens during trust evaluation, or with
+//! [`CtLog::verify`](crate::ct_log::CtLog::verify).
+
+use security_framework_sys::base::errSecDecode;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::der::Certificate;
+use crate::{Error, Result};
+
+/// What a log signed when issuing an SCT.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LogEntry<'a> {
+    /// A DER encoded certificate, for SCTs delivered in the TLS extension or
+    /// a stapled OCSP response.
+    X509(&'a [u8]),
+    /// A precertificate, for SCTs embedded in the certificate itself.
+    Precert {
+        /// The SHA-256 hash of the issuer's `SubjectPublicKeyInfo`.
+        issuer_key_hash: [u8; 32],
+        /// The certificate's `TBSCertificate` without the SCT list extension.
+        tbs_certificate: Vec<u8>,
+    },
+}
+
+impl LogEntry<'static> {
+    /// Creates the entry for the SCTs embedded in the DER encoded `certificate`,
+    /// issued by `issuer`.
+    #[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub fn precert(certificate: &[u8], issuer: &[u8]) -> Result<Self> {
+        use crate::channel_binding::EndPointHash;
+
+        let issuer = Certificate::parse(issuer).ok_or_else(|| Error::from_code(errSecDecode))?;
+        let mut issuer_key_hash = [0; 32];
+        issuer_key_hash.copy_from_slice(&EndPointHash::Sha256.digest(issuer.subject_public_key_info));
+        Self::precert_with_issuer_key_hash(certificate, issuer_key_hash)
+    }
+
+    /// Creates the entry for the SCTs embedded in the DER encoded `certificate`,
+    /// given the SHA-256 hash of its issuer's `SubjectPublicKeyInfo`.
+    pub fn precert_with_issuer_key_hash(certificate: &[u8], issuer_key_hash: [u8; 32]) -> Result<Self> {
+        Ok(Self::Precert {
+            issuer_key_hash,
+            tbs_certificate: crate::der::precert_tbs_certificate(certificate).ok_or_else(|| Error::from_code(errSecDecode))?,
+        })
+    }
+}
+
+/// A single signed certificate timestamp, as defined by RFC 6962.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SignedCertificateTimestamp {
+    raw: Vec<u8>,
+    log_id: [u8; 32],
+    timestamp: u64,
+    extensions: Vec<u8>,
+    hash_algorithm: u8,
+    signature_algorithm: u8,
+    signature: Vec<u8>,
+}
+
+impl SignedCertificateTimestamp {
+    /// Parses a single serialized version 1 SCT.
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        Self::parse_inner(bytes).ok_or_else(|| Error::from_code(errSecDecode))
+    }
+
+    /// Parses a TLS encoded `SignedCertificateTimestampList`, as found in the
+    /// TLS extension, the stapled OCSP extension and the X.509 extension.
+    ///
+    /// SCTs with a version other than 1 are skipped, as RFC 6962 requires.
+    pub fn parse_list(bytes: &[u8]) -> Result<Vec<Self>> {
+        Self::parse_list_inner(bytes).ok_or_else(|| Error::from_code(errSecDecode))
+    }
+
+    /// Parses the SCTs embedded in a DER encoded certificate.
+    ///
+    /// Returns an empty list if the certificate has none.
+    pub fn from_certificate(certificate: &[u8]) -> Result<Vec<Self>> {
+        match Certificate::parse(certificate).ok_or_else(|| Error::from_code(errSecDecode))?.signed_certificate_timestamps {
+            Some(list) => Self::parse_list(list),
+            None => Ok(vec![]),
+        }
+    }
+
+    fn parse_list_inner(bytes: &[u8]) -> Option<Vec<Self>> {
+        let mut list = Cursor(bytes);
+        let mut entries = Cursor(list.read_vec16()?);
+        if !list.0.is_empty() {
+            return None;
+        }
+        let mut scts = vec![];
+        while !entries.0.is_empty() {
+            let entry = entries.read_vec16()?;
+            match entry.first() {
+                Some(0) => scts.push(Self::parse_inner(entry)?),
+                Some(_) => {}
+                None => return None,
+            }
+        }
+        Some(scts)
+    }
+
+    fn parse_inner(bytes: &[u8]) -> Option<Self> {
+        let mut cursor = Cursor(bytes);
+        if cursor.read_u8()? != 0 {
+            return None;
+        }
+        let mut log_id = [0; 32];
+        log_id.copy_from_slice(cursor.read(32)?);
+        let timestamp = cursor.read(8)?.iter().fold(0, |n, &b| (n << 8) | u64::from(b));
+        let extensions = cursor.read_vec16()?.to_vec();
+        let hash_algorithm = cursor.read_u8()?;
+        let signature_algorithm = cursor.read_u8()?;
+        let signature = cursor.read_vec16()?.to_vec();
+        if !cursor.0.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            raw: bytes.to_vec(),
+            log_id,
+            timestamp,
+            extensions,
+            hash_algorithm,
+            signature_algorithm,
+            signature,
+        })
+    }
+
+    /// The serialized SCT.
+    #[inline(always)]
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.raw
+    }
+
+    /// The SHA-256 hash of the issuing log's public key.
+    #[inline(always)]
+    #[must_use]
+    pub fn log_id(&self) -> &[u8; 32] {
+        &self.log_id
+    }
+
+    /// The time the log issued the SCT.
+    #[inline]
+    #[must_use]
+    pub fn timestamp(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.timestamp)
+    }
+
+    /// The time the log issued the SCT, in milliseconds since the Unix epoch.
+    #[inline(always)]
+    #[must_use]
+    pub fn timestamp_millis(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// The opaque `CtExtensions` field.
+    #[inline(always)]
+    #[must_use]
+    pub fn extensions(&self) -> &[u8] {
+        &self.extensions
+    }
+
+    /// The TLS `HashAlgorithm` of the signature, e.g. 4 for SHA-256.
+    #[inline(always)]
+    #[must_use]
+    pub fn hash_algorithm(&self) -> u8 {
+        self.hash_algorithm
+    }
+
+    /// The TLS `SignatureAlgorithm` of the signature, e.g. 3 for ECDSA.
+    #[inline(always)]
+    #[must_use]
+    pub fn signature_algorithm(&self) -> u8 {
+        self.signature_algorithm
+    }
+
+    /// The log's signature.
+    #[inline(always)]
+    #[must_use]
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    /// Returns the data covered by the log's signature, for `entry`.
+    #[must_use]
+    pub fn signed_data(&self, entry: &LogEntry<'_>) -> Vec<u8> {
+        // version v1 and signature type certificate_timestamp
+        let mut data = vec![0, 0];
+        data.extend_from_slice(&self.timestamp.to_be_bytes());
+        let (entry_type, key_hash, cert): (u16, &[u8], &[u8]) = match entry {
+            LogEntry::X509(cert) => (0, &[], cert),
+            LogEntry::Precert { issuer_key_hash, tbs_certificate } => (1, issuer_key_hash, tbs_certificate),
+        };
+        data.extend_from_slice(&entry_type.to_be_bytes());
+        data.extend_from_slice(key_hash);
+        data.extend_from_slice(&(cert.len() as u32).to_be_bytes()[1..]);
+        data.extend_from_slice(cert);
+        data.extend_from_slice(&(self.extensions.len() as u16).to_be_bytes());
+        data.extend_from_slice(&self.extensions);
+        data
+    }
+}
+
+struct Cursor<'a>(&'a [u8]);
+
+impl<'a> Cursor<'a> {
+    fn read(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Some(head)
+    }
+
+    fn read_u8(&mut self) -> Option<u8> {
+        self.read(1).map(|b| b[0])
+    }
+
+    fn read_vec16(&mut self) -> Option<&'a [u8]> {
+        let len = self.read(2)?;
+        self.read(usize::from(u16::from_be_bytes([len[0], len[1]])))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+
+    pub(crate) fn sct(log_id: u8, timestamp: u64) -> Vec<u8> {
+        let mut sct = vec![0];
+        sct.extend_from_slice(&[log_id; 32]);
+        sct.extend_from_slice(&timestamp.to_be_bytes());
+        sct.extend_from_slice(&[0, 0, 4, 3, 0, 4, 0xde, 0xad, 0xbe, 0xef]);
+        sct
+    }
+
+    pub(crate) fn list(scts: &[Vec<u8>]) -> Vec<u8> {
+        let mut entries = vec![];
+        for sct in scts {
+            entries.extend_from_slice(&(sct.len() as u16).to_be_bytes());
+            entries.extend_from_slice(sct);
+        }
+        let mut list = (entries.len() as u16).to_be_bytes().to_vec();
+        list.extend_from_slice(&entries);
+        list
+    }
+
+    #[test]
+    fn parse() {
+        let bytes = sct(7, 1_700_000_000_123);
+        let sct = SignedCertificateTimestamp::parse(&bytes).unwrap();
+        assert_eq!(&[7; 32], sct.log_id());
+        assert_eq!(1_700_000_000_123, sct.timestamp_millis());
+        assert_eq!(UNIX_EPOCH + Duration::from_millis(1_700_000_000_123), sct.timestamp());
+        assert!(sct.extensions().is_empty());
+        assert_eq!(4, sct.hash_algorithm());
+        assert_eq!(3, sct.signature_algorithm());
+        assert_eq!(&[0xde, 0xad, 0xbe, 0xef][..], sct.signature());
+        assert_eq!(&bytes[..], sct.as_bytes());
+
+        assert!(SignedCertificateTimestamp::parse(&bytes[..bytes.len() - 1]).is_err());
+        let mut v2 = bytes.clone();
+        v2[0] = 1;
+        assert!(SignedCertificateTimestamp::parse(&v2).is_err());
+    }
+
+    #[test]
+    fn parse_list() {
+        let mut future = sct(3, 0);
+        future[0] = 1;
+        let scts = SignedCertificateTimestamp::parse_list(&list(&[sct(1, 10), future, sct(2, 20)])).unwrap();
+        assert_eq!(2, scts.len());
+        assert_eq!(&[1; 32], scts[0].log_id());
+        assert_eq!(20, scts[1].timestamp_millis());
+
+        let mut trailing = list(&[sct(1, 10)]);
+        trailing.push(0);
+        assert!(SignedCertificateTimestamp::parse_list(&trailing).is_err());
+        assert!(SignedCertificateTimestamp::parse_list(&list(&[vec![]])).is_err());
+    }
+
+    #[test]
+    fn embedded() {
+        let scts = SignedCertificateTimestamp::from_certificate(include_bytes!("../test/ct/leaf.der")).unwrap();
+        assert_eq!(3, scts.len());
+        assert_eq!(scts[0].log_id(), scts[1].log_id());
+        assert_ne!(scts[0].log_id(), scts[2].log_id());
+        assert_eq!(1_767_225_603_000, scts[2].timestamp_millis());
+        assert!(SignedCertificateTimestamp::from_certificate(include_bytes!("../test/ct/root.der")).unwrap().is_empty());
+
+        let tbs = include_bytes!("../test/ct/precert_tbs.der").to_vec();
+        let entry = LogEntry::Precert {
+            issuer_key_hash: [7; 32],
+            tbs_certificate: tbs.clone(),
+        };
+        assert_eq!(entry, LogEntry::precert_with_issuer_key_hash(include_bytes!("../test/ct/leaf.der"), [7; 32]).unwrap());
+        let data = scts[0].signed_data(&entry);
+        assert_eq!(&[0, 0][..], &data[..2]);
+        assert_eq!(&1_767_225_601_000u64.to_be_bytes()[..], &data[2..10]);
+        assert_eq!(&[0, 1][..], &data[10..12]);
+        assert_eq!(&[7; 32][..], &data[12..44]);
+        assert_eq!(&(tbs.len() as u32).to_be_bytes()[1..], &data[44..47]);
+        assert_eq!(&tbs[..], &data[47..data.len() - 2]);
+        assert_eq!(&[0, 0][..], &data[data.len() - 2..]);
+
+        let data = scts[0].signed_data(&LogEntry::X509(&[1, 2, 3]));
+        assert_eq!(&[0, 0, 0, 0, 3, 1, 2, 3, 0, 0][..], &data[10..]);
+    }
+}
@@ -76,6 +76,7 @@
 use core_foundation::array::{CFArray, CFArrayRef};
 
 use core_foundation::base::{Boolean, TCFType};
+use core_foundation::data::CFData;
 #[cfg(feature = "alpn")]
 use core_foundation::string::CFString;
 use core_foundation_sys::base::{kCFAllocatorDefault, OSStatus};
@@ -90,22 +91,38 @@ use security_framework_sys::base::{
 use security_framework_sys::secure_transport::*;
 use std::any::Any;
 use std::cmp;
+use std::collections::VecDeque;
 use std::fmt;
 use std::io;
 use std::io::prelude::*;
+use std::io::IoSlice;
 use std::marker::PhantomData;
+use std::net::IpAddr;
 use std::panic::{self, AssertUnwindSafe};
 use std::ptr;
 use std::result;
 use std::slice;
+use std::sync::Arc;
 
 use crate::base::{Error, Result};
 use crate::certificate::SecCertificate;
+use crate::channel_binding::{self, ChannelBindingType};
 use crate::cipher_suite::CipherSuite;
 use crate::identity::SecIdentity;
 use crate::import_export::Pkcs12ImportOptions;
-use crate::policy::SecPolicy;
+use crate::item::{ItemClass, ItemSearchOptions, Reference, SearchResult};
+#[cfg(feature = "keylog")]
+use crate::key_log::KeyLog;
+#[cfg(all(target_os = "macos", feature = "OSX_10_11"))]
+use crate::os::macos::secure_transport::MidHandshakeSslStreamExt;
+#[cfg(target_os = "macos")]
+use crate::os::macos::secure_transport::SslContextExt;
+#[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use crate::protocol_options::{self, ClientOptions, ProtocolOptions};
+use crate::session_cache::SessionCache;
+use crate::tls_preset::TlsPreset;
 use crate::trust::SecTrust;
+use crate::trust_policy::TrustPolicy;
 use crate::{cvt, AsInner};
 use security_framework_sys::base::errSecParam;
 
@@ -133,6 +150,34 @@ impl SslConnectionType {
     pub const DATAGRAM: Self = Self(kSSLDatagramType);
 }
 
+/// The progress of a handshake driven with `step`.
+#[derive(Debug)]
+pub enum HandshakeStatus {
+    /// The underlying stream must become readable before stepping again.
+    WantRead,
+    /// The underlying stream must become writable before stepping again.
+    WantWrite,
+    /// The peer's certificate was received and should be evaluated with the
+    /// given trust before stepping again.
+    ///
+    /// This requires `break_on_server_auth` on the client side and
+    /// `break_on_client_auth` on the server side.
+    ServerAuth(SecTrust),
+    /// The server requested a client certificate, which can be set with
+    /// `SslContext::set_certificate` before stepping again.
+    ///
+    /// This requires `break_on_cert_requested`.
+    ClientCertRequested,
+    /// The client hello was received, with the server name the client
+    /// requested via SNI, if any.
+    ///
+    /// This requires `SslContextExt::set_break_on_client_hello`. The name is
+    /// only available with the `OSX_10_11` (or greater) feature.
+    ClientHello(Option<String>),
+    /// The handshake completed.
+    Done,
+}
+
 /// An error or intermediate state after a TLS handshake attempt.
 #[derive(Debug)]
 pub enum HandshakeError<S> {
@@ -151,6 +196,7 @@ impl<S> From<Error> for HandshakeError<S> {
 
 /// An error or intermediate state after a TLS handshake attempt.
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)] // boxing the builder would break matching on it
 pub enum ClientHandshakeError<S> {
     /// The handshake failed.
     Failure(Error),
@@ -235,6 +281,63 @@ impl<S> MidHandshakeSslStream<S> {
     pub fn handshake(self) -> result::Result<SslStream<S>, HandshakeError<S>> {
         self.stream.handshake()
     }
+
+    /// Advances the handshake as far as possible, returning what it is
+    /// waiting for.
+    ///
+    /// Unlike `handshake`, this does not consume the stream, which suits
+    /// event loops. Once `Done` is returned, `into_stream` returns the
+    /// established stream.
+    pub fn step(&mut self) -> Result<HandshakeStatus> {
+        #[cfg(feature = "tracing")]
+        let _span = self.stream.ctx.handshake_span().entered();
+        loop {
+            let ret = unsafe { SSLHandshake(self.stream.ctx.0) };
+            #[cfg(feature = "tracing")]
+            self.stream.ctx.trace_handshake(ret);
+            self.error = Error::from_code(ret);
+            let status = match ret {
+                errSecSuccess => HandshakeStatus::Done,
+                errSSLWouldBlock => {
+                    let conn = self.stream.connection_mut();
+                    conn.err = None;
+                    if conn.write_blocked {
+                        HandshakeStatus::WantWrite
+                    } else {
+                        HandshakeStatus::WantRead
+                    }
+                }
+                errSSLPeerAuthCompleted => match self.stream.ctx.peer_trust2()? {
+                    Some(trust) => HandshakeStatus::ServerAuth(trust),
+                    // nothing to evaluate, e.g. no optional client certificate
+                    None => continue,
+                },
+                errSSLClientCertRequested => HandshakeStatus::ClientCertRequested,
+                errSSLClientHelloReceived => {
+                    #[cfg(any(feature = "OSX_10_11", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+                    let name = self.stream.ctx.requested_peer_name()?;
+                    #[cfg(not(any(feature = "OSX_10_11", target_os = "ios", target_os = "tvos", target_os = "watchos")))]
+                    let name = None;
+                    HandshakeStatus::ClientHello(name)
+                }
+                err => {
+                    self.stream.check_panic();
+                    return Err(Error::from_code(err));
+                }
+            };
+            return Ok(status);
+        }
+    }
+
+    /// Returns the established stream once the handshake completed, or
+    /// `self` if it has not.
+    pub fn into_stream(self) -> result::Result<SslStream<S>, Self> {
+        if self.stream.ctx.state().ok() == Some(SessionState::CONNECTED) {
+            Ok(self.stream)
+        } else {
+            Err(self)
+        }
+    }
 }
 
 /// An SSL stream midway through the handshake process.
@@ -242,9 +345,12 @@ impl<S> MidHandshakeSslStream<S> {
 pub struct MidHandshakeClientBuilder<S> {
     stream: MidHandshakeSslStream<S>,
     domain: Option<String>,
-    certs: Vec<SecCertificate>,
-    trust_certs_only: bool,
+    trust_policy: TrustPolicy,
     danger_accept_invalid_certs: bool,
+    client_cert_resolver: Option<ClientCertResolver>,
+    server_authenticated: bool,
+    #[cfg(feature = "keylog")]
+    key_log: Option<KeyLog>,
 }
 
 impl<S> MidHandshakeClientBuilder<S> {
@@ -268,68 +374,112 @@ impl<S> MidHandshakeClientBuilder<S> {
         self.stream.error()
     }
 
-    /// Restarts the handshake process.
-    pub fn handshake(self) -> result::Result<SslStream<S>, ClientHandshakeError<S>> {
-        let MidHandshakeClientBuilder {
-            stream,
-            domain,
-            certs,
-            trust_certs_only,
-            danger_accept_invalid_certs,
-        } = self;
-
-        let mut result = stream.handshake();
+    /// Advances the handshake as far as possible.
+    ///
+    /// The server's certificate is verified and a client certificate selected
+    /// as part of stepping, so only `WantRead`, `WantWrite` and `Done` are
+    /// returned. Once `Done` is returned, `handshake` returns the stream.
+    pub fn step(&mut self) -> Result<HandshakeStatus> {
         loop {
-            let stream = match result {
-                Ok(stream) => return Ok(stream),
-                Err(HandshakeError::Interrupted(stream)) => stream,
-                Err(HandshakeError::Failure(err)) => {
-                    return Err(ClientHandshakeError::Failure(err))
+            match self.stream.step()? {
+                HandshakeStatus::ServerAuth(trust) => {
+                    self.server_authenticated = true;
+                    if !self.danger_accept_invalid_certs {
+                        self.verify_server(trust)?;
+                    }
                 }
-            };
-
-            if stream.would_block() {
-                let ret = MidHandshakeClientBuilder {
-                    stream,
-                    domain,
-                    certs,
-                    trust_certs_only,
-                    danger_accept_invalid_certs,
-                };
-                return Err(ClientHandshakeError::Interrupted(ret));
+                HandshakeStatus::ClientCertRequested => self.resolve_client_cert()?,
+                HandshakeStatus::Done => {
+                    // resumed sessions skip the certificate exchange
+                    self.stream.stream.session_resumed = Some(!self.server_authenticated);
+                    #[cfg(feature = "keylog")]
+                    {
+                        if let Some(ref key_log) = self.key_log {
+                            // logging is best effort and never fails the handshake
+                            let _ = self.stream.context().log_secrets(key_log);
+                        }
+                    }
+                    return Ok(HandshakeStatus::Done);
+                }
+                status => return Ok(status),
             }
+        }
+    }
 
-            if stream.server_auth_completed() {
-                if danger_accept_invalid_certs {
-                    result = stream.handshake();
-                    continue;
-                }
-                let mut trust = match stream.context().peer_trust2()? {
-                    Some(trust) => trust,
-                    None => {
-                        result = stream.handshake();
-                        continue;
-                    }
-                };
-                trust.set_anchor_certificates(&certs)?;
-                trust.set_trust_anchor_certificates_only(self.trust_certs_only)?;
-                let policy = SecPolicy::create_ssl(SslProtocolSide::SERVER, domain.as_deref());
-                trust.set_policy(&policy)?;
-                trust.evaluate_with_error().map_err(|error| {
-                    #[cfg(feature = "log")]
-                    log::warn!("SecTrustEvaluateWithError: {}", error.to_string());
-                    Error::from_code(error.code() as _)
-                })?;
-                result = stream.handshake();
-                continue;
+    /// Restarts the handshake process.
+    #[allow(clippy::result_large_err)]
+    pub fn handshake(mut self) -> result::Result<SslStream<S>, ClientHandshakeError<S>> {
+        match self.step()? {
+            HandshakeStatus::Done => Ok(self.stream.stream),
+            HandshakeStatus::WantRead | HandshakeStatus::WantWrite => {
+                Err(ClientHandshakeError::Interrupted(self))
             }
+            _ => Err(ClientHandshakeError::Failure(*self.stream.error())),
+        }
+    }
 
-            let err = Error::from_code(stream.error().code());
-            return Err(ClientHandshakeError::Failure(err));
+    fn verify_server(&mut self, mut trust: SecTrust) -> Result<()> {
+        self.trust_policy.verify(&mut trust, SslProtocolSide::SERVER, self.domain.as_deref())?;
+        self.stream.stream.peer_certificates = Some(trust_chain(&trust));
+        Ok(())
+    }
+
+    fn resolve_client_cert(&mut self) -> Result<()> {
+        if let Some(ClientCertResolver(ref resolver)) = self.client_cert_resolver {
+            let names = self.stream.context().distinguished_names()?.unwrap_or_default();
+            if let Some((identity, chain)) = resolver(&names) {
+                self.stream.context_mut().set_certificate(&identity, &chain)?;
+            }
         }
+        Ok(())
+    }
+}
+
+type ClientCertResolverFn = dyn Fn(&[Vec<u8>]) -> Option<(SecIdentity, Vec<SecCertificate>)> + Send + Sync;
+
+/// Selects a client certificate given the names of the acceptable authorities.
+#[derive(Clone)]
+struct ClientCertResolver(Arc<ClientCertResolverFn>);
+
+impl fmt::Debug for ClientCertResolver {
+    #[cold]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("ClientCertResolver")
     }
 }
 
+/// Searches the keychain for an identity issued by, or chaining up to, one of
+/// the given DER encoded certificate authority names.
+///
+/// This can be passed to `ClientBuilder::client_cert_resolver` as is. No
+/// identity is returned if `issuers` is empty, since offering an arbitrary
+/// certificate would reveal it to servers that did not ask for it. The
+/// returned chain is empty, so the server must know any intermediates.
+#[must_use]
+pub fn keychain_client_identity(issuers: &[Vec<u8>]) -> Option<(SecIdentity, Vec<SecCertificate>)> {
+    if issuers.is_empty() {
+        return None;
+    }
+    let results = ItemSearchOptions::new()
+        .class(ItemClass::identity())
+        .issuers(issuers)
+        .load_refs(true)
+        .search()
+        .ok()?;
+    results.into_iter().find_map(|result| match result {
+        SearchResult::Ref(Reference::Identity(identity)) => Some((identity, vec![])),
+        _ => None,
+    })
+}
+
+/// Returns the certificates of an evaluated trust, leaf first.
+#[allow(deprecated)]
+fn trust_chain(trust: &SecTrust) -> Vec<SecCertificate> {
+    (0..trust.certificate_count())
+        .filter_map(|i| trust.certificate_at_index(i))
+        .collect()
+}
+
 /// Specifies the state of a TLS session.
 #[derive(Debug, PartialEq, Eq)]
 pub struct SessionState(SSLSessionState);
@@ -383,11 +533,45 @@ impl SslClientCertificateState {
     pub const REJECTED: Self = Self(kSSLClientCertRejected);
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for SslClientCertificateState {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(match *self {
+            Self::NONE => "none",
+            Self::REQUESTED => "requested",
+            Self::SENT => "sent",
+            Self::REJECTED => "rejected",
+            _ => "unknown",
+        })
+    }
+}
+
 /// Specifies protocol versions.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct SslProtocol(SSLProtocol);
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for SslProtocol {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
 impl SslProtocol {
+    #[cfg(any(feature = "serde", feature = "tracing"))]
+    fn name(self) -> &'static str {
+        match self {
+            Self::SSL2 => "SSLv2",
+            Self::SSL3 | Self::SSL3_ONLY => "SSLv3",
+            Self::TLS1 | Self::TLS1_ONLY => "TLSv1",
+            Self::TLS11 => "TLSv1.1",
+            Self::TLS12 => "TLSv1.2",
+            Self::TLS13 => "TLSv1.3",
+            Self::DTLS1 => "DTLSv1",
+            _ => "unknown",
+        }
+    }
+
     /// No protocol has been or should be negotiated or specified; use the default.
     pub const UNKNOWN: Self = Self(kSSLProtocolUnknown);
 
@@ -523,6 +707,40 @@ impl SslContext {
         }
     }
 
+    // Secure Transport has no getter for the side of a context, but only
+    // client contexts report a peer domain name, even when none was set.
+    fn is_client_side(&self) -> bool {
+        let mut len = 0;
+        unsafe { SSLGetPeerDomainNameLength(self.0, &mut len) == errSecSuccess }
+    }
+
+    /// Returns the server name requested by the client via SNI.
+    ///
+    /// This is only meaningful on the server side, once the client hello has
+    /// been received. See `SslContextExt::set_break_on_client_hello`.
+    #[cfg(any(feature = "OSX_10_11", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub fn requested_peer_name(&self) -> Result<Option<String>> {
+        unsafe {
+            let mut len = 0;
+            cvt(SSLCopyRequestedPeerNameLength(self.0, &mut len))?;
+            if len == 0 {
+                return Ok(None);
+            }
+            let mut buf = vec![0; len];
+            cvt(SSLCopyRequestedPeerName(
+                self.0,
+                buf.as_mut_ptr().cast(),
+                &mut len,
+            ))?;
+            buf.truncate(len);
+            // the length may or may not include a trailing NUL
+            while buf.last() == Some(&0) {
+                buf.pop();
+            }
+            Ok(String::from_utf8(buf).ok())
+        }
+    }
+
     /// Sets the certificate to be used by this side of the SSL session.
     ///
     /// This must be called before the handshake for server-side connections,
@@ -639,6 +857,24 @@ impl SslContext {
         Ok(SslClientCertificateState(state))
     }
 
+    /// Returns the DER encoded distinguished names of the certificate
+    /// authorities the server accepts client certificates from.
+    ///
+    /// This can be used in conjunction with `set_break_on_cert_requested` to
+    /// select a client certificate.
+    pub fn distinguished_names(&self) -> Result<Option<Vec<Vec<u8>>>> {
+        unsafe {
+            let mut names = ptr::null();
+            cvt(SSLCopyDistinguishedNames(self.0, &mut names))?;
+            if names.is_null() {
+                return Ok(None);
+            }
+
+            let names = CFArray::<CFData>::wrap_under_create_rule(names);
+            Ok(Some(names.iter().map(|name| name.bytes().to_vec()).collect()))
+        }
+    }
+
     /// Returns the `SecTrust` object corresponding to the peer.
     ///
     /// This can be used in conjunction with `set_break_on_server_auth` to
@@ -796,6 +1032,37 @@ impl SslContext {
         }
     }
 
+    /// Writes the master secret of the established session to `key_log`.
+    ///
+    /// Secure Transport only exposes the secret through private functions,
+    /// which are looked up at runtime. Returns `errSecUnimplemented` if they
+    /// are unavailable, or if the session uses TLS 1.3, which has no master
+    /// secret.
+    #[cfg(feature = "keylog")]
+    pub fn log_secrets(&self, key_log: &KeyLog) -> Result<()> {
+        dlsym! { fn SSLInternalClientRandom(SSLContextRef, *mut c_void, *mut usize) -> OSStatus }
+        dlsym! { fn SSLInternalMasterSecret(SSLContextRef, *mut c_void, *mut usize) -> OSStatus }
+
+        if self.negotiated_protocol_version()? == SslProtocol::TLS13 {
+            return Err(Error::from_code(errSecUnimplemented));
+        }
+
+        let (client_random, master_secret) = match (SSLInternalClientRandom.get(), SSLInternalMasterSecret.get()) {
+            (Some(client_random), Some(master_secret)) => (client_random, master_secret),
+            _ => return Err(Error::from_code(errSecUnimplemented)),
+        };
+        let mut random = [0u8; 32];
+        let mut random_len = random.len();
+        let mut secret = [0u8; 48];
+        let mut secret_len = secret.len();
+        unsafe {
+            cvt(client_random(self.0, random.as_mut_ptr().cast(), &mut random_len))?;
+            cvt(master_secret(self.0, secret.as_mut_ptr().cast(), &mut secret_len))?;
+        }
+        key_log.log("CLIENT_RANDOM", &random[..random_len], &secret[..secret_len]);
+        Ok(())
+    }
+
     /// Sets whether a protocol is enabled or not.
     ///
     /// # Note
@@ -868,6 +1135,7 @@ impl SslContext {
                 stream,
                 err: None,
                 panic: None,
+                write_blocked: false,
             };
             let stream = Box::into_raw(Box::new(stream));
             let ret = SSLSetConnection(self.0, stream.cast());
@@ -878,11 +1146,46 @@ impl SslContext {
 
             Ok(SslStream {
                 ctx: self,
+                peer_certificates: None,
+                server_certificate: None,
+                session_resumed: None,
+                state: StreamState::Open,
+                close_notify_received: false,
+                require_close_notify: false,
+                write_buf: Vec::new(),
+                write_buf_capacity: 0,
+                max_record_size: MAX_RECORD_SIZE,
                 _m: PhantomData,
             })
         }
     }
 
+    #[cfg(feature = "tracing")]
+    fn handshake_span(&self) -> tracing::Span {
+        tracing::debug_span!("tls_handshake", context = ?self.0)
+    }
+
+    /// Records the outcome of a call to `SSLHandshake`.
+    #[cfg(feature = "tracing")]
+    fn trace_handshake(&self, ret: OSStatus) {
+        match ret {
+            errSecSuccess => {
+                let protocol = self.negotiated_protocol_version().map(SslProtocol::name);
+                let cipher = self.negotiated_cipher().ok();
+                tracing::debug!(
+                    protocol = protocol.unwrap_or("unknown"),
+                    cipher = cipher.and_then(|cipher| cipher.iana_name()).unwrap_or("unknown"),
+                    "handshake completed"
+                );
+            }
+            errSSLWouldBlock => tracing::trace!("handshake would block"),
+            errSSLPeerAuthCompleted => tracing::debug!("peer certificate received"),
+            errSSLClientCertRequested => tracing::debug!("client certificate requested"),
+            errSSLClientHelloReceived => tracing::debug!("ClientHello received"),
+            err => tracing::warn!(status = err, error = %Error::from_code(err), "handshake failed"),
+        }
+    }
+
     /// Performs the SSL/TLS handshake.
     pub fn handshake<S>(self, stream: S) -> result::Result<SslStream<S>, HandshakeError<S>>
     where
@@ -898,6 +1201,8 @@ struct Connection<S> {
     stream: S,
     err: Option<io::Error>,
     panic: Option<Box<dyn Any + Send>>,
+    /// Whether the last I/O error, e.g. `WouldBlock`, came from a write.
+    write_blocked: bool,
 }
 
 // the logic here is based off of libcurl's
@@ -934,6 +1239,9 @@ where
             Ok(Ok(len)) => start += len,
             Ok(Err(e)) => {
                 ret = translate_err(&e);
+                #[cfg(feature = "tracing")]
+                tracing::trace!(kind = ?e.kind(), error = %e, status = ret, "transport read failed");
+                conn.write_blocked = false;
                 conn.err = Some(e);
                 break;
             }
@@ -971,6 +1279,9 @@ where
             Ok(Ok(len)) => start += len,
             Ok(Err(e)) => {
                 ret = translate_err(&e);
+                #[cfg(feature = "tracing")]
+                tracing::trace!(kind = ?e.kind(), error = %e, status = ret, "transport write failed");
+                conn.write_blocked = true;
                 conn.err = Some(e);
                 break;
             }
@@ -986,9 +1297,86 @@ where
     ret
 }
 
+/// A snapshot of the parameters of an established connection.
+///
+/// With the `serde` feature, this can be serialized, e.g. for logging.
+/// Protocol versions and client certificate states serialize as strings like
+/// `"TLSv1.2"` and `"sent"`, cipher suites as their IANA name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ConnectionInfo {
+    /// The negotiated protocol version.
+    pub protocol_version: SslProtocol,
+    /// The negotiated cipher suite.
+    pub cipher_suite: CipherSuite,
+    /// The IANA name of the negotiated cipher suite, if it has one.
+    pub cipher_suite_name: Option<&'static str>,
+    /// The protocol selected via ALPN, if any.
+    pub alpn_protocol: Option<String>,
+    /// The server name sent via SNI, if any.
+    pub server_name: Option<String>,
+    /// The DER encoded certificate chain of the peer, leaf first.
+    pub peer_certificates: Vec<Vec<u8>>,
+    /// The state of client certificate authentication.
+    pub client_certificate_state: SslClientCertificateState,
+    /// Whether an earlier session was resumed, if known.
+    pub session_resumed: Option<bool>,
+    /// Whether TLS false start was enabled on the context, if known.
+    ///
+    /// This is the configured setting, not whether false start took place;
+    /// Secure Transport only uses it if the negotiated cipher suite allows
+    /// it, and does not report when it did.
+    pub false_start_enabled: Option<bool>,
+}
+
+/// The largest amount of plaintext a TLS record carries.
+const MAX_RECORD_SIZE: usize = 16384;
+
+/// How far an `SslStream` has gone towards ending its session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StreamState {
+    Open,
+    /// Our `close_notify` alert was sent.
+    Closed,
+    /// `into_parts` has taken the connection, which `Drop` must not free
+    /// again.
+    Detached,
+}
+
 /// A type implementing SSL/TLS encryption over an underlying stream.
+///
+/// # Errors
+///
+/// Reads and writes report errors as follows:
+///
+/// * Errors of the underlying stream, including `WouldBlock`, are returned
+///   as they are.
+/// * Reads return `Ok(0)` once the peer's `close_notify` alert was received.
+/// * Reads also return `Ok(0)` if the connection ends without a `close_notify`
+///   alert, because the underlying stream reached end of file or reported
+///   `ConnectionReset`. As this is how a truncation attack looks,
+///   `set_require_close_notify` turns these into errors: `UnexpectedEof` at
+///   end of file, the underlying stream's error, or `ConnectionAborted` if
+///   Secure Transport aborted the connection itself.
+/// * All other failures are returned as errors of kind `Other`, wrapping the
+///   `base::Error` with the Secure Transport status.
+///
+/// # Writes
+///
+/// Each write is sent as its own TLS record unless a write buffer is set
+/// with `set_write_buffer_size`, and `write_vectored` gathers its buffers
+/// into as few records as `set_max_record_size` allows.
 pub struct SslStream<S> {
     ctx: SslContext,
+    peer_certificates: Option<Vec<SecCertificate>>,
+    server_certificate: Option<SecCertificate>,
+    session_resumed: Option<bool>,
+    state: StreamState,
+    close_notify_received: bool,
+    require_close_notify: bool,
+    write_buf: Vec<u8>,
+    write_buf_capacity: usize,
+    max_record_size: usize,
     _m: PhantomData<S>,
 }
 
@@ -997,13 +1385,29 @@ impl<S: fmt::Debug> fmt::Debug for SslStream<S> {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt.debug_struct("SslStream")
             .field("context", &self.ctx)
+            .field("peer_certificates", &self.peer_certificates)
+            .field("server_certificate", &self.server_certificate)
+            .field("session_resumed", &self.session_resumed)
+            .field("state", &self.state)
+            .field("close_notify_received", &self.close_notify_received)
+            .field("require_close_notify", &self.require_close_notify)
+            .field("buffered_write_size", &self.write_buf.len())
+            .field("write_buffer_size", &self.write_buf_capacity)
+            .field("max_record_size", &self.max_record_size)
             .field("stream", self.get_ref())
-            .finish()
+            .finish_non_exhaustive()
     }
 }
 
 impl<S> Drop for SslStream<S> {
     fn drop(&mut self) {
+        if self.state == StreamState::Detached {
+            return;
+        }
+        // like `BufWriter`, send buffered writes on a best-effort basis
+        if !self.write_buf.is_empty() && !std::thread::panicking() {
+            let _ = self.flush_write_buf();
+        }
         unsafe {
             let mut conn = ptr::null();
             let ret = SSLGetConnection(self.ctx.0, &mut conn);
@@ -1013,9 +1417,46 @@ impl<S> Drop for SslStream<S> {
     }
 }
 
+/// The error returned by `SslStream::into_parts` and `SslStream::into_inner`
+/// when buffered writes could not be sent.
+#[derive(Debug)]
+pub struct IntoInnerError<S> {
+    stream: SslStream<S>,
+    error: io::Error,
+}
+
+impl<S> IntoInnerError<S> {
+    /// Returns the error which occurred while sending the buffered writes.
+    #[inline(always)]
+    #[must_use]
+    pub fn error(&self) -> &io::Error {
+        &self.error
+    }
+
+    /// Returns the stream, with the writes that could not be sent still
+    /// buffered.
+    #[inline(always)]
+    #[must_use]
+    pub fn into_inner(self) -> SslStream<S> {
+        self.stream
+    }
+}
+
+impl<S> From<IntoInnerError<S>> for io::Error {
+    #[inline(always)]
+    fn from(err: IntoInnerError<S>) -> Self {
+        err.error
+    }
+}
+
 impl<S> SslStream<S> {
     fn handshake(mut self) -> result::Result<Self, HandshakeError<S>> {
-        match unsafe { SSLHandshake(self.ctx.0) } {
+        #[cfg(feature = "tracing")]
+        let _span = self.ctx.handshake_span().entered();
+        let ret = unsafe { SSLHandshake(self.ctx.0) };
+        #[cfg(feature = "tracing")]
+        self.ctx.trace_handshake(ret);
+        match ret {
             errSecSuccess => Ok(self),
             reason @ errSSLPeerAuthCompleted
             | reason @ errSSLClientCertRequested
@@ -1059,11 +1500,118 @@ impl<S> SslStream<S> {
         &mut self.ctx
     }
 
-    /// Shuts down the connection.
+    /// Returns the peer's certificate chain, leaf first, as verified during
+    /// the handshake.
+    ///
+    /// This is only set by `ClientBuilder` and by `ServerBuilder` with client
+    /// authentication enabled, and is `None` if the peer's certificate was
+    /// not verified, for example because an optional client certificate was
+    /// not presented.
+    #[inline(always)]
+    #[must_use]
+    pub fn peer_certificates(&self) -> Option<&[SecCertificate]> {
+        self.peer_certificates.as_deref()
+    }
+
+    /// Returns whether the handshake resumed an earlier session.
+    ///
+    /// This is only known for streams created by `ClientBuilder`, which
+    /// notices the server not sending its certificate again, and is `None`
+    /// otherwise.
+    #[inline(always)]
+    #[must_use]
+    pub fn session_resumed(&self) -> Option<bool> {
+        self.session_resumed
+    }
+
+    /// Returns channel binding data for the connection, as defined by
+    /// RFC 5929.
+    ///
+    /// `tls-server-end-point` hashes the server's certificate. Clients take
+    /// it from the peer's certificate chain. Servers use the certificate of
+    /// the identity they presented, which is only known for streams created
+    /// by `ServerBuilder`; other server streams return an error and can use
+    /// `channel_binding::tls_server_end_point` with the certificate directly.
+    /// `tls-unique` is not supported, as Secure Transport does not expose the
+    /// Finished messages.
+    pub fn channel_binding(&self, kind: ChannelBindingType) -> Result<Vec<u8>> {
+        match kind {
+            ChannelBindingType::TlsServerEndPoint => {
+                let certificate = match self.server_certificate {
+                    Some(ref certificate) => Some(certificate.clone()),
+                    // on the server side the peer's certificate is the client's
+                    None if self.context().is_client_side() => {
+                        self.context().peer_trust2()?.and_then(|trust| trust_chain(&trust).into_iter().next())
+                    }
+                    None => None,
+                };
+                match certificate {
+                    Some(certificate) => channel_binding::tls_server_end_point(&certificate.to_der()),
+                    None => Err(Error::from_code(errSecParam)),
+                }
+            }
+            ChannelBindingType::TlsUnique => Err(Error::from_code(errSecUnimplemented)),
+        }
+    }
+
+    /// Returns a snapshot of the parameters of the connection.
+    ///
+    /// The peer's certificate chain is the one verified during the
+    /// handshake if known, and the unverified chain the peer sent otherwise.
+    pub fn connection_info(&self) -> Result<ConnectionInfo> {
+        let ctx = self.context();
+        let cipher_suite = ctx.negotiated_cipher()?;
+
+        let peer_certificates = match self.peer_certificates {
+            Some(ref chain) => chain.iter().map(SecCertificate::to_der).collect(),
+            None => match ctx.peer_trust2().ok().flatten() {
+                Some(trust) => trust_chain(&trust).iter().map(SecCertificate::to_der).collect(),
+                None => vec![],
+            },
+        };
+
+        #[cfg(any(feature = "OSX_10_11", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+        let requested_name = ctx.requested_peer_name().ok().flatten();
+        #[cfg(not(any(feature = "OSX_10_11", target_os = "ios", target_os = "tvos", target_os = "watchos")))]
+        let requested_name = None;
+        // clients know the name they sent, servers the one they received
+        let server_name = match ctx.peer_domain_name() {
+            Ok(name) if !name.is_empty() => Some(name),
+            _ => requested_name,
+        };
+
+        #[cfg(feature = "alpn")]
+        let alpn_protocol = ctx.alpn_protocols().ok().and_then(|protocols| protocols.into_iter().next());
+        #[cfg(not(feature = "alpn"))]
+        let alpn_protocol = None;
+
+        #[cfg(feature = "OSX_10_9")]
+        let false_start_enabled = ctx.false_start().ok();
+        #[cfg(not(feature = "OSX_10_9"))]
+        let false_start_enabled = None;
+
+        Ok(ConnectionInfo {
+            protocol_version: ctx.negotiated_protocol_version()?,
+            cipher_suite,
+            cipher_suite_name: cipher_suite.iana_name(),
+            alpn_protocol,
+            server_name,
+            peer_certificates,
+            client_certificate_state: ctx.client_certificate_state()?,
+            session_resumed: self.session_resumed,
+            false_start_enabled,
+        })
+    }
+
+    /// Shuts down the connection, after sending any buffered writes.
     pub fn close(&mut self) -> result::Result<(), io::Error> {
+        self.flush_write_buf()?;
         unsafe {
             let ret = SSLClose(self.ctx.0);
+            #[cfg(feature = "tracing")]
+            tracing::debug!(status = ret, close_notify_received = self.close_notify_received, "closing connection");
             if ret == errSecSuccess {
+                self.state = StreamState::Closed;
                 Ok(())
             } else {
                 Err(self.get_error(ret))
@@ -1071,6 +1619,175 @@ impl<S> SslStream<S> {
         }
     }
 
+    /// Sends a `close_notify` alert to the peer, and returns whether the peer's
+    /// `close_notify` alert was received before.
+    ///
+    /// Secure Transport stops reading once it sent its own `close_notify`, so
+    /// to wait for the peer's alert, read until `Ok(0)` before shutting down.
+    /// The underlying stream is left open.
+    pub fn shutdown(&mut self) -> io::Result<bool> {
+        self.close()?;
+        Ok(self.close_notify_received)
+    }
+
+    /// Returns whether the peer's `close_notify` alert was received.
+    #[inline(always)]
+    #[must_use]
+    pub fn close_notify_received(&self) -> bool {
+        self.close_notify_received
+    }
+
+    /// If enabled, reads fail instead of returning `Ok(0)` when the
+    /// connection ends without a `close_notify` alert from the peer.
+    ///
+    /// Protocols which don't delimit their messages themselves, such as
+    /// HTTP/1.0 without `Content-Length`, should enable this to detect
+    /// truncation attacks. Disabled by default, since many peers don't send
+    /// the alert.
+    #[inline]
+    pub fn set_require_close_notify(&mut self, require: bool) {
+        self.require_close_notify = require;
+    }
+
+    /// Buffers up to `size` bytes of writes, so that many small writes are
+    /// sent as one record instead of one record each.
+    ///
+    /// Buffered data is only sent once the buffer would overflow, or by
+    /// `flush`, `close` and `shutdown`. Dropping the stream also tries to
+    /// send it, but ignores errors. Writes of at least `size` bytes bypass
+    /// the buffer. A size of 0, the default, disables buffering. Any data
+    /// buffered before is sent first.
+    pub fn set_write_buffer_size(&mut self, size: usize) -> io::Result<()> {
+        self.flush_write_buf()?;
+        self.write_buf_capacity = size;
+        self.write_buf = Vec::with_capacity(size);
+        Ok(())
+    }
+
+    /// Returns the capacity of the write buffer, 0 if writes are unbuffered.
+    #[inline(always)]
+    #[must_use]
+    pub fn write_buffer_size(&self) -> usize {
+        self.write_buf_capacity
+    }
+
+    /// Returns the number of bytes written but not yet sent.
+    #[inline(always)]
+    #[must_use]
+    pub fn buffered_write_size(&self) -> usize {
+        self.write_buf.len()
+    }
+
+    /// Limits the plaintext carried by each record written to `size` bytes,
+    /// between 1 and 16384.
+    ///
+    /// Smaller records let the peer decrypt data sooner, at the cost of
+    /// overhead per record. Defaults to 16384, the maximum TLS allows.
+    pub fn set_max_record_size(&mut self, size: usize) -> Result<()> {
+        if size == 0 || size > MAX_RECORD_SIZE {
+            return Err(Error::from_code(errSecParam));
+        }
+        self.max_record_size = size;
+        Ok(())
+    }
+
+    /// Returns the maximum amount of plaintext in each record written.
+    #[inline(always)]
+    #[must_use]
+    pub fn max_record_size(&self) -> usize {
+        self.max_record_size
+    }
+
+    /// Ends the TLS session and returns the underlying stream.
+    ///
+    /// Plaintext that was decrypted but not read yet is discarded, see
+    /// `into_parts` to keep it. Fails like `into_parts` if buffered writes
+    /// cannot be sent.
+    #[inline]
+    pub fn into_inner(self) -> result::Result<S, IntoInnerError<S>> {
+        self.into_parts().map(|(stream, _)| stream)
+    }
+
+    /// Ends the TLS session and returns the underlying stream, along with the
+    /// plaintext that was decrypted but not read yet.
+    ///
+    /// This lets protocols like STARTTLS hand the connection off or continue
+    /// without TLS. To end the session cleanly, `close` it first. Writes
+    /// buffered by `set_write_buffer_size` are sent first; if that fails, the
+    /// error holds the stream, with the data that could not be sent still
+    /// buffered. Secure Transport reads whole records from the stream, so the
+    /// stream is positioned right after the last record read unless a record
+    /// was only partially received.
+    pub fn into_parts(mut self) -> result::Result<(S, Vec<u8>), IntoInnerError<S>> {
+        if let Err(error) = self.flush_write_buf() {
+            return Err(IntoInnerError { stream: self, error });
+        }
+        let plaintext = self.read_buffered();
+        let conn = unsafe { Box::from_raw(self.connection_mut() as *mut Connection<S>) };
+        // Secure Transport refuses to change the connection of an active
+        // session, so `Drop` is told not to free it again instead
+        self.state = StreamState::Detached;
+        Ok((conn.stream, plaintext))
+    }
+
+    /// Reads the plaintext Secure Transport has buffered, without reading
+    /// from the stream.
+    fn read_buffered(&mut self) -> Vec<u8> {
+        let mut buf = vec![0; self.context().buffered_read_size().unwrap_or(0)];
+        let mut len = 0;
+        while len < buf.len() {
+            let mut nread = 0;
+            unsafe {
+                SSLRead(self.ctx.0, buf[len..].as_mut_ptr().cast(), buf.len() - len, &mut nread);
+            }
+            if nread == 0 {
+                break;
+            }
+            len += nread;
+        }
+        buf.truncate(len);
+        buf
+    }
+
+    /// Sends up to one record of `buf`.
+    fn write_record(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = cmp::min(buf.len(), self.max_record_size);
+        unsafe {
+            let mut nwritten = 0;
+            let ret = SSLWrite(
+                self.ctx.0,
+                buf.as_ptr().cast(),
+                len,
+                &mut nwritten,
+            );
+            // just to be safe, base success off of nwritten rather than ret
+            // for the same reason as in read
+            if nwritten > 0 {
+                Ok(nwritten)
+            } else {
+                Err(self.get_error(ret))
+            }
+        }
+    }
+
+    /// Sends the write buffer, keeping what could not be sent on error.
+    fn flush_write_buf(&mut self) -> io::Result<()> {
+        let mut buf = std::mem::take(&mut self.write_buf);
+        let mut sent = 0;
+        let result = loop {
+            if sent == buf.len() {
+                break Ok(());
+            }
+            match self.write_record(&buf[sent..]) {
+                Ok(n) => sent += n,
+                Err(err) => break Err(err),
+            }
+        };
+        buf.drain(..sent);
+        self.write_buf = buf;
+        result
+    }
+
     fn connection(&self) -> &Connection<S> {
         unsafe {
             let mut conn = ptr::null();
@@ -1099,15 +1816,35 @@ impl<S> SslStream<S> {
         }
     }
 
+    #[cold]
+    fn truncation_error(&mut self, ret: OSStatus) -> io::Error {
+        if let Some(err) = self.connection_mut().err.take() {
+            return err;
+        }
+        let kind = if ret == errSSLClosedNoNotify {
+            io::ErrorKind::UnexpectedEof
+        } else {
+            io::ErrorKind::ConnectionAborted
+        };
+        io::Error::new(kind, Error::from_code(ret))
+    }
+
     #[cold]
     fn get_error(&mut self, ret: OSStatus) -> io::Error {
         self.check_panic();
 
-        if let Some(err) = self.connection_mut().err.take() {
+        let err = if let Some(err) = self.connection_mut().err.take() {
             err
         } else {
             io::Error::new(io::ErrorKind::Other, Error::from_code(ret))
+        };
+        #[cfg(feature = "tracing")]
+        {
+            if err.kind() != io::ErrorKind::WouldBlock {
+                tracing::debug!(status = ret, kind = ?err.kind(), error = %err, "I/O failed");
+            }
         }
+        err
     }
 }
 
@@ -1142,7 +1879,27 @@ impl<S: Read + Write> Read for SslStream<S> {
             }
 
             match ret {
-                errSSLClosedGraceful | errSSLClosedAbort | errSSLClosedNoNotify => Ok(0),
+                errSSLClosedGraceful => {
+                    // after our own close_notify, reads fail this way as well
+                    if self.state != StreamState::Closed {
+                        #[cfg(feature = "tracing")]
+                        tracing::debug!("close_notify received");
+                        self.close_notify_received = true;
+                    }
+                    self.connection_mut().err = None;
+                    Ok(0)
+                }
+                errSSLClosedAbort | errSSLClosedNoNotify if self.require_close_notify => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(status = ret, "connection closed without close_notify");
+                    Err(self.truncation_error(ret))
+                }
+                errSSLClosedAbort | errSSLClosedNoNotify => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(status = ret, "connection closed without close_notify");
+                    self.connection_mut().err = None;
+                    Ok(0)
+                }
                 // this error isn't fatal
                 errSSLPeerAuthCompleted => self.read(buf),
                 _ => Err(self.get_error(ret)),
@@ -1157,38 +1914,205 @@ impl<S: Read + Write> Write for SslStream<S> {
         if buf.is_empty() {
             return Ok(0);
         }
-        unsafe {
-            let mut nwritten = 0;
-            let ret = SSLWrite(
-                self.ctx.0,
-                buf.as_ptr().cast(),
-                buf.len(),
-                &mut nwritten,
-            );
-            // just to be safe, base success off of nwritten rather than ret
-            // for the same reason as in read
-            if nwritten > 0 {
-                Ok(nwritten)
-            } else {
-                Err(self.get_error(ret))
-            }
+        if self.write_buf.len() + buf.len() > self.write_buf_capacity {
+            self.flush_write_buf()?;
         }
-    }
+        if buf.len() >= self.write_buf_capacity {
+            return self.write_record(buf);
+        }
+        self.write_buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        if self.write_buf_capacity > 0 {
+            let mut written = 0;
+            for buf in bufs {
+                match self.write(buf) {
+                    Ok(n) if n == buf.len() => written += n,
+                    Ok(n) => return Ok(written + n),
+                    Err(err) if written == 0 => return Err(err),
+                    Err(_) => break,
+                }
+            }
+            return Ok(written);
+        }
+
+        let mut bufs = bufs.iter().filter(|buf| !buf.is_empty()).peekable();
+        let first = match bufs.next() {
+            Some(first) => first,
+            None => return Ok(0),
+        };
+        if bufs.peek().is_none() || first.len() >= self.max_record_size {
+            return self.write_record(first);
+        }
+        // gather the buffers into one record, reusing the allocation of the
+        // write buffer, which is always empty while writes are unbuffered
+        let mut record = std::mem::take(&mut self.write_buf);
+        record.reserve(self.max_record_size);
+        record.extend_from_slice(first);
+        for buf in bufs {
+            let len = cmp::min(buf.len(), self.max_record_size - record.len());
+            record.extend_from_slice(&buf[..len]);
+            if record.len() == self.max_record_size {
+                break;
+            }
+        }
+        let result = self.write_record(&record);
+        record.clear();
+        self.write_buf = record;
+        result
+    }
 
     fn flush(&mut self) -> io::Result<()> {
+        self.flush_write_buf()?;
         self.connection_mut().stream.flush()
     }
 }
 
+/// An in-memory transport for running a session without an underlying stream.
+///
+/// Ciphertext received from the peer is fed in with `push_incoming`, and
+/// ciphertext to send to the peer is drained with `take_outgoing`. Reads
+/// return `WouldBlock` while no incoming data is available, so handshakes and
+/// reads over a `MemoryBio` behave as over a non-blocking socket:
+///
+/// ```no_run
+/// use security_framework::secure_transport::{
+///     HandshakeError, HandshakeStatus, MemoryBio, SslConnectionType, SslContext, SslProtocolSide,
+/// };
+///
+/// # fn send(_: &[u8]) {}
+/// # fn receive() -> Vec<u8> { vec![] }
+/// let mut ctx = SslContext::new(SslProtocolSide::CLIENT, SslConnectionType::STREAM).unwrap();
+/// ctx.set_peer_domain_name("example.com").unwrap();
+/// let mut stream = match ctx.handshake(MemoryBio::new()) {
+///     Err(HandshakeError::Interrupted(stream)) => stream,
+///     _ => panic!("handshake did not wait for the server"),
+/// };
+/// loop {
+///     send(&stream.get_mut().take_outgoing());
+///     stream.get_mut().push_incoming(&receive());
+///     if let HandshakeStatus::Done = stream.step().unwrap() {
+///         break;
+///     }
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct MemoryBio {
+    incoming: VecDeque<u8>,
+    outgoing: Vec<u8>,
+    eof: bool,
+}
+
+impl MemoryBio {
+    /// Creates an empty transport.
+    #[inline(always)]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends ciphertext received from the peer.
+    #[inline]
+    pub fn push_incoming(&mut self, data: &[u8]) {
+        self.incoming.extend(data);
+    }
+
+    /// Marks the end of the data received from the peer.
+    ///
+    /// Once the incoming data is consumed, the session sees the connection
+    /// as closed instead of waiting for more.
+    #[inline(always)]
+    pub fn close_incoming(&mut self) {
+        self.eof = true;
+    }
+
+    /// Returns the number of received bytes not yet consumed by the session.
+    #[inline(always)]
+    #[must_use]
+    pub fn incoming_len(&self) -> usize {
+        self.incoming.len()
+    }
+
+    /// Drains the ciphertext to send to the peer.
+    #[inline]
+    #[must_use]
+    pub fn take_outgoing(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.outgoing)
+    }
+
+    /// Returns the number of bytes waiting to be sent to the peer.
+    #[inline(always)]
+    #[must_use]
+    pub fn outgoing_len(&self) -> usize {
+        self.outgoing.len()
+    }
+}
+
+impl Read for MemoryBio {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.incoming.is_empty() && !buf.is_empty() && !self.eof {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        let len = cmp::min(buf.len(), self.incoming.len());
+        for (dst, src) in buf.iter_mut().zip(self.incoming.drain(..len)) {
+            *dst = src;
+        }
+        Ok(len)
+    }
+}
+
+impl Write for MemoryBio {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.outgoing.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    #[inline(always)]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// An external pre-shared key and the identity naming it.
+#[derive(Clone)]
+pub(crate) struct PreSharedKey {
+    #[cfg_attr(not(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos")), allow(dead_code))]
+    pub(crate) key: Vec<u8>,
+    pub(crate) identity: Vec<u8>,
+}
+
+impl fmt::Debug for PreSharedKey {
+    #[cold]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("PreSharedKey")
+            .field("identity", &String::from_utf8_lossy(&self.identity))
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns the error for pre-shared keys, which Secure Transport does not
+/// implement.
+#[cold]
+fn psk_unsupported() -> Error {
+    #[cfg(feature = "tracing")]
+    tracing::warn!("Secure Transport does not support pre-shared keys, use protocol_options instead");
+    #[cfg(all(feature = "log", not(feature = "tracing")))]
+    log::warn!("Secure Transport does not support pre-shared keys, use protocol_options instead");
+    Error::from_code(errSecUnimplemented)
+}
+
 /// A builder type to simplify the creation of client side `SslStream`s.
 #[derive(Debug)]
 pub struct ClientBuilder {
     identity: Option<SecIdentity>,
-    certs: Vec<SecCertificate>,
     chain: Vec<SecCertificate>,
+    preset: Option<TlsPreset>,
     protocol_min: Option<SslProtocol>,
     protocol_max: Option<SslProtocol>,
-    trust_certs_only: bool,
+    trust_policy: TrustPolicy,
     use_sni: bool,
     danger_accept_invalid_certs: bool,
     danger_accept_invalid_hostnames: bool,
@@ -1198,6 +2122,11 @@ pub struct ClientBuilder {
     alpn: Option<Vec<String>>,
     #[cfg(feature = "session-tickets")]
     enable_session_tickets: bool,
+    client_cert_resolver: Option<ClientCertResolver>,
+    session_cache: Option<(SessionCache, Option<u16>)>,
+    psks: Vec<PreSharedKey>,
+    #[cfg(feature = "keylog")]
+    key_log: Option<KeyLog>,
 }
 
 impl Default for ClientBuilder {
@@ -1214,11 +2143,11 @@ impl ClientBuilder {
     pub fn new() -> Self {
         Self {
             identity: None,
-            certs: Vec::new(),
             chain: Vec::new(),
+            preset: None,
             protocol_min: None,
             protocol_max: None,
-            trust_certs_only: false,
+            trust_policy: TrustPolicy::new(),
             use_sni: true,
             danger_accept_invalid_certs: false,
             danger_accept_invalid_hostnames: false,
@@ -1228,6 +2157,11 @@ impl ClientBuilder {
             alpn: None,
             #[cfg(feature = "session-tickets")]
             enable_session_tickets: false,
+            client_cert_resolver: None,
+            session_cache: None,
+            psks: Vec::new(),
+            #[cfg(feature = "keylog")]
+            key_log: None,
         }
     }
 
@@ -1235,7 +2169,7 @@ impl ClientBuilder {
     /// verifying the server's certificate.
     #[inline]
     pub fn anchor_certificates(&mut self, certs: &[SecCertificate]) -> &mut Self {
-        self.certs = certs.to_owned();
+        self.trust_policy.anchor_certificates(certs);
         self
     }
 
@@ -1243,7 +2177,7 @@ impl ClientBuilder {
     /// when verifying the server's certificate.
     #[inline]
     pub fn add_anchor_certificate(&mut self, certs: &SecCertificate) -> &mut Self {
-        self.certs.push(certs.to_owned());
+        self.trust_policy.add_anchor_certificate(certs);
         self
     }
 
@@ -1251,7 +2185,17 @@ impl ClientBuilder {
     /// to specified anchor certificates.
     #[inline(always)]
     pub fn trust_anchor_certificates_only(&mut self, only: bool) -> &mut Self {
-        self.trust_certs_only = only;
+        self.trust_policy.system_roots(!only);
+        self
+    }
+
+    /// Specifies the policy used to verify the server's certificate, replacing
+    /// the anchors set previously.
+    ///
+    /// The `danger_accept_invalid_*` settings still take precedence.
+    #[inline]
+    pub fn trust_policy(&mut self, policy: &TrustPolicy) -> &mut Self {
+        self.trust_policy = policy.clone();
         self
     }
 
@@ -1292,6 +2236,16 @@ impl ClientBuilder {
         self
     }
 
+    /// Configures protocol versions and cipher suites from a preset.
+    ///
+    /// Settings made with `protocol_min`, `protocol_max`, `whitelist_ciphers`
+    /// and `blacklist_ciphers` are applied on top of the preset.
+    #[inline]
+    pub fn preset(&mut self, preset: TlsPreset) -> &mut Self {
+        self.preset = Some(preset);
+        self
+    }
+
     /// Set a whitelist of enabled ciphers. Any ciphers not whitelisted will be disabled.
     pub fn whitelist_ciphers(&mut self, whitelisted_ciphers: &[CipherSuite]) -> &mut Self {
         self.whitelisted_ciphers = whitelisted_ciphers.to_owned();
@@ -1311,6 +2265,30 @@ impl ClientBuilder {
         self
     }
 
+    /// Resumes sessions using peer IDs handed out by `cache`.
+    ///
+    /// `port` is the server's port, which is part of the peer ID if the cache
+    /// is configured to include it. The peer ID replaces the one derived
+    /// from the domain alone when session tickets are enabled.
+    pub fn session_cache(&mut self, cache: &SessionCache, port: Option<u16>) -> &mut Self {
+        self.session_cache = Some((cache.clone(), port));
+        self
+    }
+
+    /// Selects the client certificate once the server requests one.
+    ///
+    /// The resolver is passed the DER encoded distinguished names of the
+    /// certificate authorities the server accepts, which may be empty. If it
+    /// returns `None`, the identity set with `identity`, if any, is used.
+    /// `keychain_client_identity` is a resolver searching the keychain.
+    pub fn client_cert_resolver<F>(&mut self, resolver: F) -> &mut Self
+    where
+        F: Fn(&[Vec<u8>]) -> Option<(SecIdentity, Vec<SecCertificate>)> + Send + Sync + 'static,
+    {
+        self.client_cert_resolver = Some(ClientCertResolver(Arc::new(resolver)));
+        self
+    }
+
     /// Configure the minimum protocol that this client will support.
     #[inline(always)]
     pub fn protocol_min(&mut self, min: SslProtocol) -> &mut Self {
@@ -1342,9 +2320,98 @@ impl ClientBuilder {
         self
     }
 
+    /// Adds an external pre-shared key and the identity naming it, to
+    /// authenticate with instead of certificates.
+    ///
+    /// Secure Transport does not implement pre-shared keys, so `handshake`
+    /// fails with `errSecUnimplemented` once one is added. Connect with the
+    /// options returned by `protocol_options` instead, which enable the PSK
+    /// cipher suites unless ciphers are whitelisted.
+    pub fn pre_shared_key(&mut self, psk: &[u8], psk_identity: &[u8]) -> &mut Self {
+        self.psks.push(PreSharedKey {
+            key: psk.to_owned(),
+            identity: psk_identity.to_owned(),
+        });
+        self
+    }
+
+    /// Writes the secrets of established connections to `key_log`, so
+    /// captured traffic can be decrypted.
+    ///
+    /// Only TLS 1.2 and earlier connections are logged. Network.framework
+    /// does not expose secrets, so `protocol_options` returns
+    /// `errSecUnimplemented` while a key log is set.
+    #[cfg(feature = "keylog")]
+    pub fn key_log(&mut self, key_log: &KeyLog) -> &mut Self {
+        self.key_log = Some(key_log.clone());
+        self
+    }
+
+    /// Creates Network.framework TLS options for a connection to `domain`
+    /// with the settings of the builder.
+    ///
+    /// Returns `errSecUnimplemented` if the builder uses a setting
+    /// Network.framework has no equivalent for: disabled SNI, a client
+    /// certificate resolver, a session cache, a key log or a blacklist of
+    /// ciphers without a whitelist or preset. Requires macOS 10.15 or iOS 13.
+    #[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub fn protocol_options(&self, domain: &str) -> Result<ProtocolOptions> {
+        if !self.use_sni || self.client_cert_resolver.is_some() || self.session_cache.is_some() {
+            return Err(Error::from_code(errSecUnimplemented));
+        }
+        #[cfg(feature = "keylog")]
+        {
+            if self.key_log.is_some() {
+                return Err(Error::from_code(errSecUnimplemented));
+            }
+        }
+        let mut options = ClientOptions::new();
+        options
+            .trust_policy(&self.trust_policy)
+            .danger_accept_invalid_certs(self.danger_accept_invalid_certs)
+            .danger_accept_invalid_hostnames(self.danger_accept_invalid_hostnames);
+        if let Some(ref identity) = self.identity {
+            options.identity(identity, &self.chain);
+        }
+        if let Some(min) = self.protocol_min.or_else(|| self.preset.map(TlsPreset::protocol_min)) {
+            options.protocol_min(min);
+        }
+        if let Some(max) = self.protocol_max {
+            options.protocol_max(max);
+        }
+        let mut ciphers = match self.preset {
+            Some(preset) if self.whitelisted_ciphers.is_empty() => {
+                let mut ciphers = preset.cipher_suites().to_owned();
+                if !self.psks.is_empty() {
+                    ciphers.extend(protocol_options::psk_cipher_suites());
+                }
+                ciphers
+            }
+            _ => self.whitelisted_ciphers.clone(),
+        };
+        if !self.blacklisted_ciphers.is_empty() {
+            if ciphers.is_empty() {
+                return Err(Error::from_code(errSecUnimplemented));
+            }
+            ciphers.retain(|cipher| !self.blacklisted_ciphers.contains(cipher));
+        }
+        options.whitelist_ciphers(&ciphers);
+        #[cfg(feature = "alpn")]
+        {
+            if let Some(ref alpn) = self.alpn {
+                options.alpn_protocols(&alpn.iter().map(|s| &**s).collect::<Vec<_>>());
+            }
+        }
+        for psk in &self.psks {
+            options.pre_shared_key(&psk.key, &psk.identity);
+        }
+        options.build(domain)
+    }
+
     /// Initiates a new SSL/TLS session over a stream connected to the specified domain.
     ///
     /// If both SNI and hostname verification are disabled, the value of `domain` will be ignored.
+    #[allow(clippy::result_large_err)]
     pub fn handshake<S>(
         &self,
         domain: &str,
@@ -1360,17 +2427,19 @@ impl ClientBuilder {
             error: Error::from(errSecSuccess),
         };
 
-        let certs = self.certs.clone();
         let stream = MidHandshakeClientBuilder {
             stream,
             domain: if self.danger_accept_invalid_hostnames {
                 None
             } else {
-                Some(domain.to_string())
+                Some(self.trust_policy.hostname(domain).to_string())
             },
-            certs,
-            trust_certs_only: self.trust_certs_only,
+            trust_policy: self.trust_policy.clone(),
             danger_accept_invalid_certs: self.danger_accept_invalid_certs,
+            client_cert_resolver: self.client_cert_resolver.clone(),
+            server_authenticated: false,
+            #[cfg(feature = "keylog")]
+            key_log: self.key_log.clone(),
         };
         stream.handshake()
     }
@@ -1379,6 +2448,9 @@ impl ClientBuilder {
     where
         S: Read + Write,
     {
+        if !self.psks.is_empty() {
+            return Err(psk_unsupported());
+        }
         let mut ctx = SslContext::new(SslProtocolSide::CLIENT, SslConnectionType::STREAM)?;
 
         if self.use_sni {
@@ -1402,7 +2474,17 @@ impl ClientBuilder {
                 ctx.set_session_tickets_enabled(true)?;
             }
         }
+        if let Some((ref cache, port)) = self.session_cache {
+            #[cfg(feature = "alpn")]
+            let alpn = self.alpn.iter().flatten().map(|s| &**s).collect::<Vec<_>>();
+            #[cfg(not(feature = "alpn"))]
+            let alpn = Vec::new();
+            ctx.set_peer_id(&cache.peer_id(domain, port, &alpn))?;
+        }
         ctx.set_break_on_server_auth(true)?;
+        if self.client_cert_resolver.is_some() {
+            ctx.set_break_on_cert_requested(true)?;
+        }
         self.configure_protocols(&mut ctx)?;
         self.configure_ciphers(&mut ctx)?;
 
@@ -1410,6 +2492,9 @@ impl ClientBuilder {
     }
 
     fn configure_protocols(&self, ctx: &mut SslContext) -> Result<()> {
+        if let Some(preset) = self.preset {
+            preset.configure(ctx)?;
+        }
         if let Some(min) = self.protocol_min {
             ctx.set_protocol_version_min(min)?;
         }
@@ -1435,11 +2520,58 @@ impl ClientBuilder {
     }
 }
 
+#[cfg(all(target_os = "macos", feature = "OSX_10_11"))]
+type SniResolver = dyn Fn(&str) -> Option<(SecIdentity, Vec<SecCertificate>)> + Send + Sync;
+
+type ClientCertVerifier = dyn Fn(&[SecCertificate]) -> bool + Send + Sync;
+
+/// Specifies whether a server requires clients to present a certificate.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ClientAuthMode {
+    /// The handshake fails if the client does not present a valid certificate.
+    Required,
+    /// A certificate is requested, but clients may connect without one. A
+    /// certificate that is presented must still be valid.
+    Optional,
+}
+
 /// A builder type to simplify the creation of server-side `SslStream`s.
-#[derive(Debug)]
 pub struct ServerBuilder {
     identity: SecIdentity,
     certs: Vec<SecCertificate>,
+    client_auth: Option<ClientAuthMode>,
+    client_trust_policy: TrustPolicy,
+    client_cert_verifier: Option<Arc<ClientCertVerifier>>,
+    session_cache: Option<SessionCache>,
+    preset: Option<TlsPreset>,
+    #[cfg(all(target_os = "macos", feature = "OSX_10_11"))]
+    sni_resolver: Option<Arc<SniResolver>>,
+    psks: Vec<PreSharedKey>,
+    psk_identity_hint: Option<Vec<u8>>,
+    #[cfg(feature = "keylog")]
+    key_log: Option<KeyLog>,
+}
+
+impl fmt::Debug for ServerBuilder {
+    #[cold]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut builder = fmt.debug_struct("ServerBuilder");
+        builder
+            .field("identity", &self.identity)
+            .field("certs", &self.certs)
+            .field("client_auth", &self.client_auth)
+            .field("client_trust_policy", &self.client_trust_policy)
+            .field("client_cert_verifier", &self.client_cert_verifier.is_some())
+            .field("session_cache", &self.session_cache)
+            .field("preset", &self.preset)
+            .field("psks", &self.psks)
+            .field("psk_identity_hint", &self.psk_identity_hint);
+        #[cfg(all(target_os = "macos", feature = "OSX_10_11"))]
+        builder.field("sni_resolver", &self.sni_resolver.is_some());
+        #[cfg(feature = "keylog")]
+        builder.field("key_log", &self.key_log);
+        builder.finish()
+    }
 }
 
 impl ServerBuilder {
@@ -1450,9 +2582,172 @@ impl ServerBuilder {
         Self {
             identity: identity.clone(),
             certs: certs.to_owned(),
+            client_auth: None,
+            client_trust_policy: TrustPolicy::new(),
+            client_cert_verifier: None,
+            session_cache: None,
+            preset: None,
+            #[cfg(all(target_os = "macos", feature = "OSX_10_11"))]
+            sni_resolver: None,
+            psks: Vec::new(),
+            psk_identity_hint: None,
+            #[cfg(feature = "keylog")]
+            key_log: None,
         }
     }
 
+    /// Requests a certificate from clients, verified against `anchors`.
+    ///
+    /// If `anchors` is empty, client certificates are verified against the
+    /// system trust store. Otherwise only certificates chaining to one of the
+    /// anchors are accepted, and on macOS their names are sent to clients as
+    /// the acceptable certificate authorities.
+    pub fn client_auth(&mut self, mode: ClientAuthMode, anchors: &[SecCertificate]) -> &mut Self {
+        self.client_auth = Some(mode);
+        self.client_trust_policy = TrustPolicy::new();
+        if !anchors.is_empty() {
+            self.client_trust_policy.anchor_certificates(anchors).system_roots(false);
+        }
+        self
+    }
+
+    /// Specifies the policy used to verify client certificates, replacing the
+    /// anchors passed to `client_auth`.
+    ///
+    /// On macOS the names of the policy's anchors are sent to clients as the
+    /// acceptable certificate authorities. It only has an effect if
+    /// `client_auth` is set.
+    pub fn client_trust_policy(&mut self, policy: &TrustPolicy) -> &mut Self {
+        self.client_trust_policy = policy.clone();
+        self
+    }
+
+    /// Adds a check run on client certificate chains that passed trust
+    /// evaluation.
+    ///
+    /// The verifier is passed the chain, leaf first, and rejects the client by
+    /// returning `false`. It only has an effect if `client_auth` is set.
+    pub fn with_client_cert_verifier<F>(&mut self, verifier: F) -> &mut Self
+    where
+        F: Fn(&[SecCertificate]) -> bool + Send + Sync + 'static,
+    {
+        self.client_cert_verifier = Some(Arc::new(verifier));
+        self
+    }
+
+    /// Selects the identity and certificate chain based on the server name
+    /// the client requests via SNI.
+    ///
+    /// The resolver is called once the client hello has been received, with the
+    /// requested name. If it returns `None`, or the client did not send a name,
+    /// the identity passed to `new` is used. This allows one listener to serve
+    /// multiple hostnames.
+    ///
+    /// Requires the `OSX_10_11` (or greater) feature.
+    #[cfg(all(target_os = "macos", feature = "OSX_10_11"))]
+    pub fn with_sni_resolver<F>(&mut self, resolver: F) -> &mut Self
+    where
+        F: Fn(&str) -> Option<(SecIdentity, Vec<SecCertificate>)> + Send + Sync + 'static,
+    {
+        self.sni_resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    /// Resumes sessions of clients using peer IDs handed out by `cache`.
+    ///
+    /// Secure Transport only resumes sessions on the server side if the
+    /// client is identified, so this only has an effect on handshakes started
+    /// with `handshake_with_peer`. Clients are told apart by IP address
+    /// alone, since their port changes with every connection.
+    pub fn session_cache(&mut self, cache: &SessionCache) -> &mut Self {
+        self.session_cache = Some(cache.clone());
+        self
+    }
+
+    /// Configures protocol versions and cipher suites from a preset.
+    #[inline]
+    pub fn preset(&mut self, preset: TlsPreset) -> &mut Self {
+        self.preset = Some(preset);
+        self
+    }
+
+    /// Adds an external pre-shared key and the identity clients name it by.
+    ///
+    /// Secure Transport does not implement pre-shared keys, so handshakes
+    /// fail with `errSecUnimplemented` once one is added. Accept connections
+    /// with the options returned by `protocol_options` instead.
+    pub fn pre_shared_key(&mut self, psk: &[u8], psk_identity: &[u8]) -> &mut Self {
+        self.psks.push(PreSharedKey {
+            key: psk.to_owned(),
+            identity: psk_identity.to_owned(),
+        });
+        self
+    }
+
+    /// Sets the hint sent to clients to help them pick a pre-shared key.
+    pub fn psk_identity_hint(&mut self, hint: &[u8]) -> &mut Self {
+        self.psk_identity_hint = Some(hint.to_owned());
+        self
+    }
+
+    /// Writes the secrets of established connections to `key_log`, so
+    /// captured traffic can be decrypted.
+    ///
+    /// Only TLS 1.2 and earlier connections are logged. Network.framework
+    /// does not expose secrets, so `protocol_options` returns
+    /// `errSecUnimplemented` while a key log is set.
+    #[cfg(feature = "keylog")]
+    pub fn key_log(&mut self, key_log: &KeyLog) -> &mut Self {
+        self.key_log = Some(key_log.clone());
+        self
+    }
+
+    /// Creates Network.framework TLS options with the settings of the
+    /// builder, for a listener.
+    ///
+    /// Pre-shared keys enable the PSK cipher suites, in addition to those of
+    /// the preset if one is set. Returns `errSecUnimplemented` if the builder uses
+    /// client authentication, an SNI resolver, a session cache or a key log.
+    /// Requires macOS 10.15 or iOS 13.
+    #[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub fn protocol_options(&self) -> Result<ProtocolOptions> {
+        #[cfg(all(target_os = "macos", feature = "OSX_10_11"))]
+        {
+            if self.sni_resolver.is_some() {
+                return Err(Error::from_code(errSecUnimplemented));
+            }
+        }
+        if self.client_auth.is_some() || self.client_cert_verifier.is_some() || self.session_cache.is_some() {
+            return Err(Error::from_code(errSecUnimplemented));
+        }
+        #[cfg(feature = "keylog")]
+        {
+            if self.key_log.is_some() {
+                return Err(Error::from_code(errSecUnimplemented));
+            }
+        }
+        let mut options = ProtocolOptions::new();
+        options.set_certificate(&self.identity, &self.certs)?;
+        let mut ciphers = Vec::new();
+        if let Some(preset) = self.preset {
+            options.set_protocol_version_min(preset.protocol_min().try_into()?);
+            ciphers.extend_from_slice(preset.cipher_suites());
+        }
+        if !self.psks.is_empty() {
+            ciphers.extend(protocol_options::psk_cipher_suites());
+        }
+        for suite in ciphers {
+            options.append_cipher_suite(suite)?;
+        }
+        for psk in &self.psks {
+            options.add_pre_shared_key(&psk.key, &psk.identity);
+        }
+        if let Some(ref hint) = self.psk_identity_hint {
+            options.set_pre_shared_key_identity_hint(hint);
+        }
+        Ok(options)
+    }
+
     /// Creates a new `ServerBuilder` which will use the identity
     /// from the given PKCS #12 data.
     ///
@@ -1479,9 +2774,41 @@ impl ServerBuilder {
     }
 
     /// Create a SSL context for lower-level stream initialization.
+    ///
+    /// If an SNI resolver is configured, the context breaks on the client
+    /// hello, and the caller is responsible for selecting the certificate.
+    /// Likewise, if client authentication is configured, the context breaks
+    /// once the client's certificate was received, and the caller is
+    /// responsible for verifying it.
     pub fn new_ssl_context(&self) -> Result<SslContext> {
+        if !self.psks.is_empty() || self.psk_identity_hint.is_some() {
+            return Err(psk_unsupported());
+        }
         let mut ctx = SslContext::new(SslProtocolSide::SERVER, SslConnectionType::STREAM)?;
         ctx.set_certificate(&self.identity, &self.certs)?;
+        if let Some(preset) = self.preset {
+            preset.configure(&mut ctx)?;
+        }
+        if let Some(mode) = self.client_auth {
+            ctx.set_client_side_authenticate(match mode {
+                ClientAuthMode::Required => SslAuthenticate::ALWAYS,
+                ClientAuthMode::Optional => SslAuthenticate::TRY,
+            })?;
+            ctx.set_break_on_client_auth(true)?;
+            #[cfg(target_os = "macos")]
+            {
+                let anchors = self.client_trust_policy.anchors();
+                if !anchors.is_empty() {
+                    ctx.set_certificate_authorities(anchors)?;
+                }
+            }
+        }
+        #[cfg(all(target_os = "macos", feature = "OSX_10_11"))]
+        {
+            if self.sni_resolver.is_some() {
+                ctx.set_break_on_client_hello(true)?;
+            }
+        }
         Ok(ctx)
     }
 
@@ -1490,12 +2817,93 @@ impl ServerBuilder {
     where
         S: Read + Write,
     {
-        match self.new_ssl_context()?.handshake(stream) {
-            Ok(stream) => Ok(stream),
-            Err(HandshakeError::Interrupted(stream)) => Err(*stream.error()),
-            Err(HandshakeError::Failure(err)) => Err(err),
+        self.drive_handshake(self.new_ssl_context()?.handshake(stream))
+    }
+
+    /// Initiates a new SSL/TLS session over a stream connected to `peer`,
+    /// allowing its session to be resumed if a session cache is configured.
+    pub fn handshake_with_peer<S>(&self, stream: S, peer: IpAddr) -> Result<SslStream<S>>
+    where
+        S: Read + Write,
+    {
+        let mut ctx = self.new_ssl_context()?;
+        if let Some(ref cache) = self.session_cache {
+            ctx.set_peer_id(&cache.peer_id(&peer.to_string(), None, &[]))?;
+        }
+        self.drive_handshake(ctx.handshake(stream))
+    }
+
+    fn drive_handshake<S>(&self, mut result: result::Result<SslStream<S>, HandshakeError<S>>) -> Result<SslStream<S>> {
+        #[allow(unused_mut)]
+        let mut identity = self.identity.clone();
+        loop {
+            let mut stream = match result {
+                Ok(mut stream) => {
+                    stream.server_certificate = identity.certificate().ok();
+                    #[cfg(feature = "keylog")]
+                    {
+                        if let Some(ref key_log) = self.key_log {
+                            let _ = stream.context().log_secrets(key_log);
+                        }
+                    }
+                    return Ok(stream);
+                }
+                Err(HandshakeError::Interrupted(stream)) => stream,
+                Err(HandshakeError::Failure(err)) => return Err(err),
+            };
+
+            #[cfg(all(target_os = "macos", feature = "OSX_10_11"))]
+            {
+                if stream.client_hello_received() {
+                    if let Some(resolved) = self.resolve_sni(stream.context_mut())? {
+                        identity = resolved;
+                    }
+                    result = stream.handshake();
+                    continue;
+                }
+            }
+
+            if self.client_auth.is_some() && stream.server_auth_completed() {
+                stream.stream.peer_certificates = self.verify_client(stream.context())?;
+                result = stream.handshake();
+                continue;
+            }
+
+            return Err(*stream.error());
         }
     }
+
+    fn verify_client(&self, ctx: &SslContext) -> Result<Option<Vec<SecCertificate>>> {
+        let mut trust = match ctx.peer_trust2()? {
+            Some(trust) => trust,
+            None if self.client_auth == Some(ClientAuthMode::Optional) => return Ok(None),
+            None => return Err(Error::from_code(errSSLBadCert)),
+        };
+        self.client_trust_policy.evaluate(&mut trust, SslProtocolSide::CLIENT, None)?;
+
+        let chain = trust_chain(&trust);
+        if let Some(ref verifier) = self.client_cert_verifier {
+            if !verifier(&chain) {
+                return Err(Error::from_code(errSSLBadCert));
+            }
+        }
+        Ok(Some(chain))
+    }
+
+    #[cfg(all(target_os = "macos", feature = "OSX_10_11"))]
+    fn resolve_sni(&self, ctx: &mut SslContext) -> Result<Option<SecIdentity>> {
+        let resolver = match self.sni_resolver {
+            Some(ref resolver) => resolver,
+            None => return Ok(None),
+        };
+        if let Some(name) = ctx.requested_peer_name()? {
+            if let Some((identity, certs)) = resolver(&name) {
+                ctx.set_certificate(&identity, &certs)?;
+                return Ok(Some(identity));
+            }
+        }
+        Ok(None)
+    }
 }
 
 #[cfg(test)]
@@ -1611,6 +3019,28 @@ mod test {
         }
     }
 
+    #[test]
+    #[cfg(feature = "session-tickets")]
+    fn client_session_cache() {
+        let cache = SessionCache::new();
+        for resumed in [false, true] {
+            let stream = p!(TcpStream::connect("google.com:443"));
+            let stream = p!(ClientBuilder::new()
+                .enable_session_tickets(true)
+                .session_cache(&cache, Some(443))
+                .handshake("google.com", stream));
+            assert_eq!(Some(resumed), stream.session_resumed());
+        }
+
+        cache.clear();
+        let stream = p!(TcpStream::connect("google.com:443"));
+        let stream = p!(ClientBuilder::new()
+            .enable_session_tickets(true)
+            .session_cache(&cache, Some(443))
+            .handshake("google.com", stream));
+        assert_eq!(Some(false), stream.session_resumed());
+    }
+
     #[test]
     #[cfg(feature = "alpn")]
     fn client_alpn_accept() {
@@ -1665,6 +3095,24 @@ mod test {
             .unwrap();
     }
 
+    #[test]
+    fn client_verify_hostname() {
+        let mut policy = TrustPolicy::new();
+        policy.verify_hostname("google.com");
+        let stream = p!(TcpStream::connect("google.com:443"));
+        ClientBuilder::new()
+            .trust_policy(&policy)
+            .handshake("foobar.com", stream)
+            .unwrap();
+
+        policy.system_roots(false);
+        let stream = p!(TcpStream::connect("google.com:443"));
+        assert!(ClientBuilder::new()
+            .trust_policy(&policy)
+            .handshake("google.com", stream)
+            .is_err());
+    }
+
     #[test]
     fn connect_no_verify_ssl() {
         let stream = p!(TcpStream::connect("expired.badssl.com:443"));
@@ -1741,6 +3189,47 @@ mod test {
         assert_eq!(num - 1, p!(stream.context().enabled_ciphers()).len());
     }
 
+    #[test]
+    fn test_builder_preset() {
+        let ctx = p!(SslContext::new(
+            SslProtocolSide::CLIENT,
+            SslConnectionType::STREAM
+        ));
+        let supported = p!(ctx.supported_ciphers());
+        for preset in [TlsPreset::Intermediate, TlsPreset::Old] {
+            assert!(preset.configurable_cipher_suites().any(|suite| supported.contains(&suite)));
+        }
+
+        let stream = p!(TcpStream::connect("google.com:443"));
+        let stream = p!(ClientBuilder::new()
+            .preset(TlsPreset::Intermediate)
+            .ctx_into_stream("google.com", stream));
+
+        let ctx = stream.context();
+        assert_eq!(SslProtocol::TLS12, p!(ctx.protocol_version_min()));
+        let enabled = p!(ctx.enabled_ciphers());
+        assert!(!enabled.is_empty());
+        assert!(enabled.iter().all(|suite| TlsPreset::Intermediate.cipher_suites().contains(suite)));
+    }
+
+    #[test]
+    fn test_builder_psk() {
+        let mut builder = ClientBuilder::new();
+        builder.pre_shared_key(b"secret", b"device");
+        let err = builder
+            .ctx_into_stream("foobar.com", std::io::Cursor::new(Vec::new()))
+            .unwrap_err();
+        assert_eq!(errSecUnimplemented, err.code());
+        assert!(!format!("{builder:?}").contains("secret"));
+
+        #[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+        {
+            p!(builder.protocol_options("foobar.com"));
+            builder.use_sni(false);
+            assert!(builder.protocol_options("foobar.com").is_err());
+        }
+    }
+
     #[test]
     fn idle_context_peer_trust() {
         let ctx = p!(SslContext::new(
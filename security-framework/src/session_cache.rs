@@ -0,0 +1,199 @@
+//! Control over TLS session resumption.
+//!
+//! Secure Transport keeps sessions in a process-wide cache and resumes one
+//! when a new connection uses the same peer ID as an earlier connection.
+//! `SessionCache` decides which connections share a peer ID, and bounds the
+//! number of peers tracked and the lifetime of their sessions by handing out
+//! a fresh peer ID once an entry was evicted or has expired. Secure Transport
+//! applies its own expiry on top of this, so sessions may still not be
+//! resumed for as long as configured here.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Hands out peer IDs for session resumption.
+///
+/// Clones share their entries, so one cache can be used by many builders.
+#[derive(Debug, Clone)]
+pub struct SessionCache {
+    capacity: usize,
+    expiry: Duration,
+    include_port: bool,
+    include_alpn: bool,
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    entries: HashMap<String, Entry>,
+    generation: u64,
+    tick: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    generation: u64,
+    created: Instant,
+    last_used: u64,
+}
+
+impl Default for SessionCache {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SessionCache {
+    /// Creates a cache tracking up to 256 peers, whose sessions are reused
+    /// for up to 10 minutes, with peer IDs derived from host, port and ALPN
+    /// protocols.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            capacity: 256,
+            expiry: Duration::from_secs(10 * 60),
+            include_port: true,
+            include_alpn: true,
+            state: Arc::default(),
+        }
+    }
+
+    /// Sets the number of peers tracked. The least recently used peer is
+    /// evicted once it is exceeded.
+    #[inline]
+    pub fn capacity(&mut self, capacity: usize) -> &mut Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Sets how long after the first full handshake with a peer its session
+    /// may be resumed.
+    #[inline]
+    pub fn expiry(&mut self, expiry: Duration) -> &mut Self {
+        self.expiry = expiry;
+        self
+    }
+
+    /// If enabled, connections to different ports of the same host do not
+    /// share sessions.
+    ///
+    /// Enabled by default.
+    #[inline]
+    pub fn include_port(&mut self, include: bool) -> &mut Self {
+        self.include_port = include;
+        self
+    }
+
+    /// If enabled, connections offering different ALPN protocols do not
+    /// share sessions.
+    ///
+    /// Enabled by default.
+    #[inline]
+    pub fn include_alpn(&mut self, include: bool) -> &mut Self {
+        self.include_alpn = include;
+        self
+    }
+
+    /// Returns the peer ID to pass to `SslContext::set_peer_id` for a
+    /// connection to `host`.
+    ///
+    /// The host is always part of the ID, so a session is never resumed
+    /// with a different host than the one it was verified for.
+    #[must_use]
+    pub fn peer_id(&self, host: &str, port: Option<u16>, alpn: &[&str]) -> Vec<u8> {
+        self.peer_id_at(host, port, alpn, Instant::now())
+    }
+
+    /// Forgets all peers, so no existing session is resumed.
+    pub fn clear(&self) {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner).entries.clear();
+    }
+
+    fn peer_id_at(&self, host: &str, port: Option<u16>, alpn: &[&str], now: Instant) -> Vec<u8> {
+        let mut key = host.to_owned();
+        if let (true, Some(port)) = (self.include_port, port) {
+            key = format!("{key}:{port}");
+        }
+        if self.include_alpn && !alpn.is_empty() {
+            key = format!("{key} {}", alpn.join(","));
+        }
+
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.tick += 1;
+        let tick = state.tick;
+        let expiry = self.expiry;
+        let generation = match state.entries.get_mut(&key) {
+            Some(entry) if now.saturating_duration_since(entry.created) < expiry => {
+                entry.last_used = tick;
+                entry.generation
+            }
+            _ => {
+                state.generation += 1;
+                let generation = state.generation;
+                state.entries.insert(key.clone(), Entry {
+                    generation,
+                    created: now,
+                    last_used: tick,
+                });
+                while state.entries.len() > self.capacity.max(1) {
+                    let oldest = state
+                        .entries
+                        .iter()
+                        .min_by_key(|(_, entry)| entry.last_used)
+                        .map(|(key, _)| key.clone());
+                    match oldest {
+                        Some(oldest) => state.entries.remove(&oldest),
+                        None => break,
+                    };
+                }
+                generation
+            }
+        };
+
+        format!("{key}#{generation}").into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn derives_peer_ids() {
+        let mut cache = SessionCache::new();
+        let id = cache.peer_id("example.com", Some(443), &["h2"]);
+        assert_eq!(id, cache.clone().peer_id("example.com", Some(443), &["h2"]));
+        assert_ne!(id, cache.peer_id("example.com", Some(8443), &["h2"]));
+        assert_ne!(id, cache.peer_id("example.com", Some(443), &["http/1.1"]));
+        assert_ne!(id, cache.peer_id("example.org", Some(443), &["h2"]));
+
+        cache.include_port(false).include_alpn(false);
+        let id = cache.peer_id("example.net", Some(443), &["h2"]);
+        assert_eq!(id, cache.peer_id("example.net", Some(8443), &["http/1.1"]));
+
+        cache.clear();
+        assert_ne!(id, cache.peer_id("example.net", Some(443), &["h2"]));
+    }
+
+    #[test]
+    fn expires_and_evicts() {
+        let mut cache = SessionCache::new();
+        cache.capacity(2).expiry(Duration::from_secs(60));
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        let a = cache.peer_id_at("a", None, &[], at(0));
+        assert_eq!(a, cache.peer_id_at("a", None, &[], at(59)));
+        let expired = cache.peer_id_at("a", None, &[], at(60));
+        assert_ne!(a, expired);
+
+        let b = cache.peer_id_at("b", None, &[], at(61));
+        assert_eq!(expired, cache.peer_id_at("a", None, &[], at(62)));
+        // b is now the least recently used peer
+        cache.peer_id_at("c", None, &[], at(63));
+        assert_eq!(expired, cache.peer_id_at("a", None, &[], at(64)));
+        assert_ne!(b, cache.peer_id_at("b", None, &[], at(65)));
+    }
+}
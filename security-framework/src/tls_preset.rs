@@ -0,0 +1,169 @@
+//! Vetted TLS configurations.
+//!
+//! The presets follow the "modern", "intermediate" and "old" configurations
+//! of Mozilla's Server Side TLS guidelines, restricted to the cipher suites
+//! Secure Transport implements.
+
+use crate::cipher_suite::{CipherSuite, TlsVersion};
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use crate::secure_transport::{SslContext, SslProtocol};
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use crate::Result;
+
+/// A named set of protocol versions and cipher suites.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TlsPreset {
+    /// TLS 1.3 only, for peers known to be recent. Secure Transport does
+    /// not negotiate TLS 1.3 on all systems, where this preset fails every
+    /// handshake.
+    Modern,
+    /// TLS 1.2 and later with forward secret AEAD cipher suites. The
+    /// recommended choice for general purpose connections.
+    Intermediate,
+    /// TLS 1.0 and later, including CBC and 3DES cipher suites, for peers
+    /// that only support legacy configurations.
+    Old,
+}
+
+static MODERN: &[CipherSuite] = &[
+    CipherSuite::TLS_AES_128_GCM_SHA256,
+    CipherSuite::TLS_AES_256_GCM_SHA384,
+    CipherSuite::TLS_CHACHA20_POLY1305_SHA256,
+];
+
+static INTERMEDIATE: &[CipherSuite] = &[
+    CipherSuite::TLS_AES_128_GCM_SHA256,
+    CipherSuite::TLS_AES_256_GCM_SHA384,
+    CipherSuite::TLS_CHACHA20_POLY1305_SHA256,
+    CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256,
+    CipherSuite::TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256,
+    CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384,
+    CipherSuite::TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
+    CipherSuite::TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256,
+    CipherSuite::TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256,
+    CipherSuite::TLS_DHE_RSA_WITH_AES_128_GCM_SHA256,
+    CipherSuite::TLS_DHE_RSA_WITH_AES_256_GCM_SHA384,
+];
+
+static OLD: &[CipherSuite] = &[
+    CipherSuite::TLS_AES_128_GCM_SHA256,
+    CipherSuite::TLS_AES_256_GCM_SHA384,
+    CipherSuite::TLS_CHACHA20_POLY1305_SHA256,
+    CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256,
+    CipherSuite::TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256,
+    CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384,
+    CipherSuite::TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
+    CipherSuite::TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256,
+    CipherSuite::TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256,
+    CipherSuite::TLS_DHE_RSA_WITH_AES_128_GCM_SHA256,
+    CipherSuite::TLS_DHE_RSA_WITH_AES_256_GCM_SHA384,
+    CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA256,
+    CipherSuite::TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA256,
+    CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA,
+    CipherSuite::TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA,
+    CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA384,
+    CipherSuite::TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA384,
+    CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA,
+    CipherSuite::TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA,
+    CipherSuite::TLS_DHE_RSA_WITH_AES_128_CBC_SHA256,
+    CipherSuite::TLS_DHE_RSA_WITH_AES_256_CBC_SHA256,
+    CipherSuite::TLS_RSA_WITH_AES_128_GCM_SHA256,
+    CipherSuite::TLS_RSA_WITH_AES_256_GCM_SHA384,
+    CipherSuite::TLS_RSA_WITH_AES_128_CBC_SHA256,
+    CipherSuite::TLS_RSA_WITH_AES_256_CBC_SHA256,
+    CipherSuite::TLS_RSA_WITH_AES_128_CBC_SHA,
+    CipherSuite::TLS_RSA_WITH_AES_256_CBC_SHA,
+    CipherSuite::TLS_RSA_WITH_3DES_EDE_CBC_SHA,
+];
+
+impl TlsPreset {
+    /// Returns the cipher suites of the preset, most preferred first.
+    ///
+    /// TLS 1.3 suites are listed for completeness; Secure Transport always
+    /// enables them when TLS 1.3 is negotiated.
+    #[must_use]
+    pub fn cipher_suites(self) -> &'static [CipherSuite] {
+        match self {
+            Self::Modern => MODERN,
+            Self::Intermediate => INTERMEDIATE,
+            Self::Old => OLD,
+        }
+    }
+
+    /// Returns the cipher suites of the preset that can be passed to
+    /// `SslContext::set_enabled_ciphers`, i.e. those used before TLS 1.3.
+    pub fn configurable_cipher_suites(self) -> impl Iterator<Item = CipherSuite> {
+        self.cipher_suites().iter().copied().filter(|suite| !is_tls13(*suite))
+    }
+
+    /// Returns the minimum protocol version of the preset.
+    ///
+    /// Presets leave the maximum version to the system.
+    #[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    #[must_use]
+    pub fn protocol_min(self) -> SslProtocol {
+        match self {
+            Self::Modern => SslProtocol::TLS13,
+            Self::Intermediate => SslProtocol::TLS12,
+            Self::Old => SslProtocol::TLS1,
+        }
+    }
+
+    /// Applies the preset to a context.
+    ///
+    /// Cipher suites not supported by the context are skipped. If none
+    /// remain, as for `Modern`, the enabled ciphers are left unchanged.
+    #[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub fn configure(self, ctx: &mut SslContext) -> Result<()> {
+        ctx.set_protocol_version_min(self.protocol_min())?;
+        let supported = ctx.supported_ciphers()?;
+        let ciphers = self
+            .configurable_cipher_suites()
+            .filter(|suite| supported.contains(suite))
+            .collect::<Vec<_>>();
+        if !ciphers.is_empty() {
+            ctx.set_enabled_ciphers(&ciphers)?;
+        }
+        Ok(())
+    }
+}
+
+fn is_tls13(suite: CipherSuite) -> bool {
+    suite.info().map_or(false, |info| info.min_version == TlsVersion::Tls13)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cipher_suite::Security;
+
+    const PRESETS: [TlsPreset; 3] = [TlsPreset::Modern, TlsPreset::Intermediate, TlsPreset::Old];
+
+    #[test]
+    fn known_suites() {
+        for preset in PRESETS {
+            let suites = preset.cipher_suites();
+            for (i, suite) in suites.iter().enumerate() {
+                assert!(suite.iana_name().is_some(), "{preset:?} {suite:?}");
+                assert!(!suites[..i].contains(suite), "{preset:?} {suite:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn presets_nest() {
+        // each preset extends the more restrictive one, keeping its order
+        assert!(INTERMEDIATE.starts_with(MODERN));
+        assert!(OLD.starts_with(INTERMEDIATE));
+        assert_eq!(0, TlsPreset::Modern.configurable_cipher_suites().count());
+        assert_eq!(INTERMEDIATE.len() - MODERN.len(), TlsPreset::Intermediate.configurable_cipher_suites().count());
+    }
+
+    #[test]
+    fn intermediate_is_secure() {
+        for suite in TlsPreset::Intermediate.cipher_suites() {
+            let info = suite.info().unwrap();
+            assert!(info.security >= Security::Secure, "{suite:?}");
+        }
+    }
+}
@@ -9,12 +9,31 @@ use core_foundation::data::CFData;
 use core_foundation::date::CFDate;
 use core_foundation_sys::base::{Boolean, CFIndex};
 
+#[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use core_foundation::{base::CFType, boolean::CFBoolean, dictionary::CFDictionary, number::CFNumber, string::CFString};
+#[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use core_foundation_sys::string::CFStringRef;
+
+#[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use security_framework_sys::base::errSecParam;
 use security_framework_sys::trust::*;
+use std::fmt;
 use std::ptr;
+#[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+#[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use std::{
+    future::Future, pin::Pin, sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
 
+#[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use crate::base::Error;
 use crate::base::Result;
 use crate::certificate::SecCertificate;
 use crate::cvt;
+#[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use crate::dispatch::DispatchQueue;
 use crate::key::SecKey;
 use crate::policy::SecPolicy;
 use core_foundation::error::{CFError, CFErrorRef};
@@ -55,6 +74,133 @@ impl TrustResult {
     }
 }
 
+/// Details of a completed trust evaluation, as returned by `SecTrust::copy_result`.
+///
+/// Security.framework only reports whether revocation and Certificate
+/// Transparency checks succeeded as a whole, not which of the attached OCSP
+/// responses or SCTs it used for them.
+#[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrustResultDetails {
+    result: Option<TrustResult>,
+    evaluation_date: Option<SystemTime>,
+    revocation_checked: bool,
+    revocation_valid_until: Option<SystemTime>,
+    extended_validation: bool,
+    organization_name: Option<String>,
+    certificate_transparency: bool,
+}
+
+#[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+impl TrustResultDetails {
+    /// The result of the evaluation.
+    #[inline(always)]
+    #[must_use]
+    pub fn result(&self) -> Option<TrustResult> {
+        self.result
+    }
+
+    /// The time at which the evaluation took place.
+    #[inline(always)]
+    #[must_use]
+    pub fn evaluation_date(&self) -> Option<SystemTime> {
+        self.evaluation_date
+    }
+
+    /// Returns `true` if the revocation status of every certificate in the chain was established.
+    ///
+    /// When network fetches are disabled, this can only come from the responses
+    /// attached with `SecTrust::set_trust_ocsp_response`, so it tells whether the
+    /// stapled responses were accepted, but not which of them were used.
+    /// Parse them with [`OcspResponse`](crate::ocsp::OcspResponse) to inspect
+    /// their validity period.
+    #[inline(always)]
+    #[must_use]
+    pub fn revocation_checked(&self) -> bool {
+        self.revocation_checked
+    }
+
+    /// The time until which the established revocation status remains valid.
+    ///
+    /// For a stapled response this follows its `nextUpdate`, so a server can
+    /// use it to decide when to fetch a fresh response.
+    #[inline(always)]
+    #[must_use]
+    pub fn revocation_valid_until(&self) -> Option<SystemTime> {
+        self.revocation_valid_until
+    }
+
+    /// Returns `true` if the leaf is an Extended Validation certificate.
+    #[inline(always)]
+    #[must_use]
+    pub fn extended_validation(&self) -> bool {
+        self.extended_validation
+    }
+
+    /// The organization name of an Extended Validation certificate.
+    #[inline(always)]
+    #[must_use]
+    pub fn organization_name(&self) -> Option<&str> {
+        self.organization_name.as_deref()
+    }
+
+    /// Returns `true` if the chain met the Certificate Transparency requirements.
+    ///
+    /// The SCTs may come from the certificate itself, a stapled OCSP response,
+    /// or those attached with `SecTrust::set_signed_certificate_timestamps`.
+    /// Which of them satisfied the requirements is not reported.
+    #[inline(always)]
+    #[must_use]
+    pub fn certificate_transparency(&self) -> bool {
+        self.certificate_transparency
+    }
+
+    fn from_dictionary(dict: &CFDictionary<CFString, CFType>) -> Self {
+        let find = |key: CFStringRef| dict.find(unsafe { CFString::wrap_under_get_rule(key) });
+        let flag = |key: CFStringRef| {
+            find(key)
+                .and_then(|value| value.downcast::<CFBoolean>())
+                .map_or(false, bool::from)
+        };
+        let date = |key: CFStringRef| {
+            find(key)
+                .and_then(|value| value.downcast::<CFDate>())
+                .and_then(|date| system_time(&date))
+        };
+
+        unsafe {
+            Self {
+                result: find(kSecTrustResultValue)
+                    .and_then(|value| value.downcast::<CFNumber>())
+                    .and_then(|value| value.to_i64())
+                    .map(|value| TrustResult(value as SecTrustResultType)),
+                evaluation_date: date(kSecTrustEvaluationDate),
+                revocation_checked: flag(kSecTrustRevocationChecked),
+                revocation_valid_until: date(kSecTrustRevocationValidUntilDate),
+                extended_validation: flag(kSecTrustExtendedValidation),
+                organization_name: find(kSecTrustOrganizationName)
+                    .and_then(|value| value.downcast::<CFString>())
+                    .map(|value| value.to_string()),
+                #[cfg(any(feature = "OSX_10_11", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+                certificate_transparency: flag(kSecTrustCertificateTransparency),
+                #[cfg(not(any(feature = "OSX_10_11", target_os = "ios", target_os = "tvos", target_os = "watchos")))]
+                certificate_transparency: false,
+            }
+        }
+    }
+}
+
+#[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+fn system_time(date: &CFDate) -> Option<SystemTime> {
+    // CFAbsoluteTime counts seconds from 2001-01-01
+    let secs = date.abs_time() + 978_307_200.0;
+    if secs >= 0.0 {
+        UNIX_EPOCH.checked_add(Duration::from_secs_f64(secs))
+    } else {
+        UNIX_EPOCH.checked_sub(Duration::from_secs_f64(-secs))
+    }
+}
+
 declare_TCFType! {
     /// A type representing a trust evaluation for a certificate.
     SecTrust, SecTrustRef
@@ -64,6 +210,13 @@ impl_TCFType!(SecTrust, SecTrustRef, SecTrustGetTypeID);
 unsafe impl Sync for SecTrust {}
 unsafe impl Send for SecTrust {}
 
+impl fmt::Debug for SecTrust {
+    #[cold]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("SecTrust").finish()
+    }
+}
+
 #[cfg(target_os = "macos")]
 bitflags::bitflags! {
     /// The option flags used to configure the evaluation of a `SecTrust`.
@@ -155,6 +308,13 @@ impl SecTrust {
         unsafe { cvt(SecTrustSetPolicies(self.0, policy.as_CFTypeRef())) }
     }
 
+    /// Sets the policies used to evaluate trust, all of which must be satisfied.
+    pub fn set_policies(&mut self, policies: &[SecPolicy]) -> Result<()> {
+        let policies = CFArray::from_CFTypes(policies);
+
+        unsafe { cvt(SecTrustSetPolicies(self.0, policies.as_CFTypeRef())) }
+    }
+
     /// Sets option flags for customizing evaluation of a trust object.
     #[cfg(target_os = "macos")]
     #[inline]
@@ -214,6 +374,25 @@ impl SecTrust {
         unsafe { cvt(SecTrustSetSignedCertificateTimestamps(self.0, scts.as_concrete_TypeRef())) }
     }
 
+    /// Returns the details of the last evaluation, such as whether revocation
+    /// was checked using the attached OCSP responses.
+    ///
+    /// Only the overall outcome of the revocation and Certificate Transparency
+    /// checks is available, not the individual responses or SCTs they used.
+    ///
+    /// Note: evaluate must first be called on the `SecTrust`.
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    pub fn copy_result(&self) -> Result<TrustResultDetails> {
+        unsafe {
+            let result = SecTrustCopyResult(self.0);
+            if result.is_null() {
+                return Err(Error::from_code(errSecParam));
+            }
+            let result = CFDictionary::wrap_under_create_rule(result);
+            Ok(TrustResultDetails::from_dictionary(&result))
+        }
+    }
+
     /// Returns the public key for a leaf certificate after it has been evaluated.
     #[inline]
     pub fn copy_public_key(&mut self) -> Result<SecKey> {
@@ -290,16 +469,127 @@ impl SecTrust {
     }
 }
 
-#[cfg(not(any(feature = "OSX_10_14", target_os = "ios", target_os = "tvos", target_os = "watchos")))]
+#[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+impl SecTrust {
+    /// Evaluates trust without blocking the calling thread.
+    ///
+    /// The evaluation, including any network fetches of intermediate certificates
+    /// or revocation information, is started on `queue`, and the returned future
+    /// resolves once it has finished. Requires macOS 10.15 or iOS 13.
+    pub fn evaluate_async(&self, queue: &DispatchQueue) -> TrustEvaluation {
+        let state = Arc::new(Mutex::new(TrustEvaluationState::default()));
+        let callback_state = Arc::clone(&state);
+        self.evaluate_async_with_callback(queue, move |result| {
+            let mut state = callback_state.lock().unwrap_or_else(|e| e.into_inner());
+            state.result = Some(result.map_err(SendableError));
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        });
+        TrustEvaluation { state }
+    }
+
+    /// Evaluates trust without blocking the calling thread, calling `callback`
+    /// with the outcome on `queue` once the evaluation has finished.
+    ///
+    /// Requires macOS 10.15 or iOS 13.
+    pub fn evaluate_async_with_callback<F>(&self, queue: &DispatchQueue, callback: F)
+    where
+        F: FnOnce(Result<(), CFError>) + Send + 'static,
+    {
+        let trust = self.clone();
+        let raw_queue = queue.clone();
+        // SecTrustEvaluateAsyncWithError must be called from the queue it reports results on
+        queue.exec_async(move || {
+            // the block may run on another thread of the queue, so the callback is shared through a Mutex
+            let callback = Arc::new(Mutex::new(Some(callback)));
+            let block_callback = Arc::clone(&callback);
+            let block = block::ConcreteBlock::new(move |_: SecTrustRef, trusted: bool, error: CFErrorRef| {
+                let result = if trusted {
+                    Ok(())
+                } else if error.is_null() {
+                    Err(cferror_from_osstatus(security_framework_sys::base::errSecNotTrusted))
+                } else {
+                    Err(unsafe { CFError::wrap_under_get_rule(error) })
+                };
+                let callback = block_callback.lock().unwrap_or_else(|e| e.into_inner()).take();
+                if let Some(callback) = callback {
+                    callback(result);
+                }
+            });
+            let block = block.copy();
+            let block: &block::Block<_, _> = &block;
+            let status = unsafe {
+                SecTrustEvaluateAsyncWithError(trust.as_concrete_TypeRef(), raw_queue.as_raw(), ptr::addr_of!(*block).cast())
+            };
+            if status != security_framework_sys::base::errSecSuccess {
+                let callback = callback.lock().unwrap_or_else(|e| e.into_inner()).take();
+                if let Some(callback) = callback {
+                    callback(Err(cferror_from_osstatus(status)));
+                }
+            }
+        });
+    }
+}
+
+/// A future resolving to the outcome of an asynchronous trust evaluation.
+///
+/// Created by [`SecTrust::evaluate_async`].
+#[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+#[must_use = "futures do nothing unless polled"]
+pub struct TrustEvaluation {
+    state: Arc<Mutex<TrustEvaluationState>>,
+}
+
+#[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+#[derive(Default)]
+struct TrustEvaluationState {
+    result: Option<Result<(), SendableError>>,
+    waker: Option<Waker>,
+}
+
+// CFError is immutable once created, so it can be handed to the thread polling the future.
+#[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+struct SendableError(CFError);
+
+#[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+unsafe impl Send for SendableError {}
+
+#[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+impl fmt::Debug for TrustEvaluation {
+    #[cold]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let finished = self.state.lock().map(|s| s.result.is_some()).unwrap_or(false);
+        fmt.debug_struct("TrustEvaluation").field("finished", &finished).finish()
+    }
+}
+
+#[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+impl Future for TrustEvaluation {
+    type Output = Result<(), CFError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(result) = state.result.take() {
+            Poll::Ready(result.map_err(|e| e.0))
+        } else {
+            state.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
 extern "C" {
     fn CFErrorCreate(allocator: core_foundation_sys::base::CFAllocatorRef, domain: core_foundation_sys::string::CFStringRef, code: CFIndex, userInfo: core_foundation_sys::dictionary::CFDictionaryRef) -> CFErrorRef;
 }
 
-#[cfg(not(any(feature = "OSX_10_14", target_os = "ios", target_os = "tvos", target_os = "watchos")))]
 fn cferror_from_osstatus(code: core_foundation_sys::base::OSStatus) -> CFError {
     unsafe {
         let error = CFErrorCreate(ptr::null_mut(), core_foundation_sys::error::kCFErrorDomainOSStatus, code as _, ptr::null_mut());
-        assert!(!error.is_null());
+        if error.is_null() {
+            // only fails when out of memory; this may run inside a block called from C, so don't unwind
+            std::process::abort();
+        }
         CFError::wrap_under_create_rule(error)
     }
 }
@@ -393,4 +683,72 @@ mod test {
         trust.set_policy(&ssl_policy).unwrap();
         assert!(trust.evaluate_with_error().is_err());
     }
+
+    #[test]
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    fn copy_result() {
+        let cert = certificate();
+        let ssl_policy = SecPolicy::create_ssl(SslProtocolSide::CLIENT, Some("certifi.io"));
+        let mut trust = SecTrust::create_with_certificates(&[cert], &[ssl_policy]).unwrap();
+        trust.set_network_fetch_allowed(false).unwrap();
+        trust
+            .set_trust_ocsp_response([&include_bytes!("../test/ocsp/good.der")[..]].iter())
+            .unwrap();
+        assert!(trust.evaluate_with_error().is_err());
+
+        let details = trust.copy_result().unwrap();
+        assert!(!details.result().unwrap().success());
+        assert!(details.evaluation_date().is_some());
+        assert!(!details.revocation_checked());
+        assert!(!details.extended_validation());
+    }
+
+    #[test]
+    #[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    fn evaluate_async_with_callback() {
+        use crate::dispatch::DispatchQueue;
+        use std::sync::mpsc;
+
+        let cert = certificate();
+        let ssl_policy = SecPolicy::create_ssl(SslProtocolSide::CLIENT, Some("certifi.io"));
+        let trust = SecTrust::create_with_certificates(&[cert], &[ssl_policy]).unwrap();
+        let (tx, rx) = mpsc::channel();
+        trust.evaluate_async_with_callback(&DispatchQueue::serial("trust"), move |result| {
+            tx.send(result.is_err()).unwrap();
+        });
+        assert!(rx.recv().unwrap());
+    }
+
+    #[test]
+    #[cfg(any(feature = "OSX_10_15", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    fn evaluate_async_future() {
+        use crate::dispatch::DispatchQueue;
+        use std::future::Future;
+        use std::sync::Arc;
+        use std::task::{Context, Poll, Wake};
+        use std::thread::{self, Thread};
+
+        struct ThreadWaker(Thread);
+
+        impl Wake for ThreadWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        let cert = certificate();
+        let ssl_policy = SecPolicy::create_ssl(SslProtocolSide::CLIENT, Some("certifi.io"));
+        let trust = SecTrust::create_with_certificates(&[cert], &[ssl_policy]).unwrap();
+        let mut evaluation = Box::pin(trust.evaluate_async(&DispatchQueue::global()));
+
+        let waker = Arc::new(ThreadWaker(thread::current())).into();
+        let mut cx = Context::from_waker(&waker);
+        let result = loop {
+            match evaluation.as_mut().poll(&mut cx) {
+                Poll::Ready(result) => break result,
+                Poll::Pending => thread::park(),
+            }
+        };
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,385 @@
+//! Certificate trust policies for TLS peers.
+//!
+//! A `TrustPolicy` describes how a peer's certificate chain is evaluated: which
+//! roots are trusted, whether revocation, stapled OCSP responses and Certificate
+//! Transparency are required, and how much clock skew is tolerated. The same
+//! policy is used by `ClientBuilder` to verify servers and by `ServerBuilder` to
+//! verify client certificates.
+
+#[cfg(any(feature = "OSX_10_14", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use core_foundation::date::CFDate;
+#[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use security_framework_sys::base::{errSecIncompleteCertRevocationCheck, errSecNotTrusted};
+#[cfg(any(feature = "OSX_10_14", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use security_framework_sys::base::{errSecCertificateExpired, errSecCertificateNotValidYet};
+#[cfg(any(feature = "OSX_10_12", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use std::sync::Arc;
+#[cfg(any(feature = "OSX_10_14", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use std::time::Duration;
+#[cfg(any(feature = "OSX_10_12", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use std::time::SystemTime;
+
+use crate::certificate::SecCertificate;
+#[cfg(any(feature = "OSX_10_12", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use crate::ct_log::CtLogList;
+#[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+use crate::policy::RevocationPolicy;
+use crate::policy::SecPolicy;
+use crate::secure_transport::SslProtocolSide;
+use crate::trust::SecTrust;
+use crate::{Error, Result};
+
+/// How a peer's certificate chain is evaluated.
+///
+/// The default policy trusts the system roots and applies the system's
+/// revocation defaults, like a plain `SecTrust` evaluation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrustPolicy {
+    anchors: Vec<SecCertificate>,
+    system_roots: bool,
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    revocation: Option<RevocationPolicy>,
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    require_ocsp_stapling: bool,
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    require_certificate_transparency: bool,
+    #[cfg(any(feature = "OSX_10_12", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    sct_requirement: Option<(Arc<CtLogList>, usize)>,
+    #[cfg(any(feature = "OSX_10_14", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    expiry_skew: Duration,
+    hostname: Option<String>,
+}
+
+impl Default for TrustPolicy {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TrustPolicy {
+    /// Creates a policy trusting the system roots.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            anchors: Vec::new(),
+            system_roots: true,
+            #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+            revocation: None,
+            #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+            require_ocsp_stapling: false,
+            #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+            require_certificate_transparency: false,
+            #[cfg(any(feature = "OSX_10_12", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+            sct_requirement: None,
+            #[cfg(any(feature = "OSX_10_14", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+            expiry_skew: Duration::ZERO,
+            hostname: None,
+        }
+    }
+
+    /// Specifies the set of root certificates to trust.
+    #[inline]
+    pub fn anchor_certificates(&mut self, certs: &[SecCertificate]) -> &mut Self {
+        self.anchors = certs.to_owned();
+        self
+    }
+
+    /// Adds a certificate to the set of root certificates to trust.
+    #[inline]
+    pub fn add_anchor_certificate(&mut self, cert: &SecCertificate) -> &mut Self {
+        self.anchors.push(cert.clone());
+        self
+    }
+
+    /// Specifies whether the built-in system roots are trusted in addition to
+    /// the anchor certificates.
+    ///
+    /// Defaults to `true`. Turning this off without specifying anchors rejects
+    /// every chain.
+    #[inline(always)]
+    pub fn system_roots(&mut self, trust: bool) -> &mut Self {
+        self.system_roots = trust;
+        self
+    }
+
+    /// Checks the revocation status of every certificate in the chain using
+    /// the given methods.
+    ///
+    /// Without this, the system's default revocation checking applies.
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    #[inline(always)]
+    pub fn revocation(&mut self, flags: RevocationPolicy) -> &mut Self {
+        self.revocation = Some(flags);
+        self
+    }
+
+    /// Requires the revocation status of the chain to be established by OCSP
+    /// responses attached to the trust, typically stapled by the peer, rather
+    /// than fetched from the network.
+    ///
+    /// Secure Transport does not attach stapled responses, so with
+    /// `ClientBuilder::handshake` this only succeeds for responses added with
+    /// `SecTrust::set_trust_ocsp_response`. Connections configured through
+    /// `ClientBuilder::protocol_options` request stapling from the server.
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    #[inline(always)]
+    pub fn require_ocsp_stapling(&mut self, require: bool) -> &mut Self {
+        self.require_ocsp_stapling = require;
+        self
+    }
+
+    /// Requires the chain to meet the system's Certificate Transparency
+    /// requirements.
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    #[inline(always)]
+    pub fn require_certificate_transparency(&mut self, require: bool) -> &mut Self {
+        self.require_certificate_transparency = require;
+        self
+    }
+
+    /// Requires the leaf certificate to embed valid signed certificate
+    /// timestamps from at least `count` distinct logs in `logs`.
+    ///
+    /// Unlike `require_certificate_transparency`, which defers to the system's
+    /// policy, this verifies each SCT against the given log list, so the check
+    /// does not depend on the system's view of which logs are trusted. A count
+    /// of 0 removes the requirement.
+    #[cfg(any(feature = "OSX_10_12", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    #[inline]
+    pub fn require_scts(&mut self, logs: &CtLogList, count: usize) -> &mut Self {
+        self.sct_requirement = if count == 0 { None } else { Some((Arc::new(logs.clone()), count)) };
+        self
+    }
+
+    /// Accepts certificates that expired, or become valid, within `skew` of
+    /// the current time, to tolerate clocks that are off.
+    ///
+    /// Requires macOS 10.14 or iOS, where evaluation reports why a chain was
+    /// rejected.
+    #[cfg(any(feature = "OSX_10_14", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    #[inline(always)]
+    pub fn allowed_expiry_skew(&mut self, skew: Duration) -> &mut Self {
+        self.expiry_skew = skew;
+        self
+    }
+
+    /// Verifies the peer's certificate against `hostname` rather than the
+    /// name it was connected to.
+    ///
+    /// This is useful when connecting by IP address or through a proxy to a
+    /// host whose certificate names a different domain.
+    #[inline]
+    pub fn verify_hostname(&mut self, hostname: &str) -> &mut Self {
+        self.hostname = Some(hostname.to_owned());
+        self
+    }
+
+    /// Returns the anchor certificates.
+    #[inline(always)]
+    #[must_use]
+    pub fn anchors(&self) -> &[SecCertificate] {
+        &self.anchors
+    }
+
+    /// Returns `true` if OCSP stapling is required.
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    #[inline(always)]
+    #[must_use]
+    pub fn requires_ocsp_stapling(&self) -> bool {
+        self.require_ocsp_stapling
+    }
+
+    /// Returns `true` if Certificate Transparency is required.
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    #[inline(always)]
+    #[must_use]
+    pub fn requires_certificate_transparency(&self) -> bool {
+        self.require_certificate_transparency
+    }
+
+    /// Returns the name a peer connected to as `domain` is verified against.
+    #[inline]
+    #[must_use]
+    pub(crate) fn hostname<'a>(&'a self, domain: &'a str) -> &'a str {
+        self.hostname.as_deref().unwrap_or(domain)
+    }
+
+    /// Evaluates the chain in `trust` under this policy.
+    ///
+    /// `peer` is the side whose certificate is evaluated, so a client passes
+    /// `SslProtocolSide::SERVER`. The chain must be valid for `hostname`, or
+    /// the name set with `verify_hostname`, if either is given.
+    pub fn evaluate(&self, trust: &mut SecTrust, peer: SslProtocolSide, hostname: Option<&str>) -> Result<()> {
+        self.verify(trust, peer, self.hostname.as_deref().or(hostname))
+    }
+
+    /// Evaluates the chain against exactly `hostname`, ignoring the name set
+    /// with `verify_hostname`.
+    pub(crate) fn verify(&self, trust: &mut SecTrust, peer: SslProtocolSide, hostname: Option<&str>) -> Result<()> {
+        let result = self.verify_chain(trust, peer, hostname);
+        #[cfg(feature = "tracing")]
+        match result {
+            Ok(()) => tracing::debug!(?peer, ?hostname, "certificate chain trusted"),
+            Err(err) => tracing::warn!(?peer, ?hostname, status = err.code(), error = %err, "certificate chain rejected"),
+        }
+        result
+    }
+
+    fn verify_chain(&self, trust: &mut SecTrust, peer: SslProtocolSide, hostname: Option<&str>) -> Result<()> {
+        trust.set_anchor_certificates(&self.anchors)?;
+        trust.set_trust_anchor_certificates_only(!self.system_roots)?;
+        #[allow(unused_mut)]
+        let mut policies = vec![SecPolicy::create_ssl(peer, hostname)];
+        #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+        {
+            if let Some(flags) = self.revocation_flags() {
+                policies.push(SecPolicy::create_revocation(flags)?);
+            }
+        }
+        trust.set_policies(&policies)?;
+
+        #[allow(unused_mut)]
+        let mut result = evaluate(trust);
+        #[cfg(any(feature = "OSX_10_14", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+        if self.expiry_skew > Duration::ZERO {
+            let skew = match result {
+                Err(err) if err.code() == errSecCertificateExpired => Some(-self.expiry_skew.as_secs_f64()),
+                Err(err) if err.code() == errSecCertificateNotValidYet => Some(self.expiry_skew.as_secs_f64()),
+                _ => None,
+            };
+            if let Some(skew) = skew {
+                trust.set_trust_verify_date(&CFDate::new(CFDate::now().abs_time() + skew))?;
+                result = evaluate(trust);
+            }
+        }
+        result?;
+
+        #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+        {
+            if self.require_ocsp_stapling || self.require_certificate_transparency {
+                let details = trust.copy_result()?;
+                if self.require_ocsp_stapling && !details.revocation_checked() {
+                    return Err(Error::from_code(errSecIncompleteCertRevocationCheck));
+                }
+                if self.require_certificate_transparency && !details.certificate_transparency() {
+                    return Err(Error::from_code(errSecNotTrusted));
+                }
+            }
+        }
+        #[cfg(any(feature = "OSX_10_12", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+        {
+            if let Some((logs, count)) = &self.sct_requirement {
+                #[allow(deprecated)]
+                let chain = (trust.certificate_at_index(0), trust.certificate_at_index(1));
+                let valid = match chain {
+                    (Some(leaf), Some(issuer)) => logs.count_embedded(&leaf.to_der(), &issuer.to_der(), SystemTime::now())?,
+                    _ => 0,
+                };
+                if valid < *count {
+                    return Err(Error::from_code(errSecNotTrusted));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Stapling only counts responses attached to the trust, so network
+    /// access is disabled for revocation checks when it is required.
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    fn revocation_flags(&self) -> Option<RevocationPolicy> {
+        if self.require_ocsp_stapling {
+            let flags = self.revocation.unwrap_or(RevocationPolicy::OCSP_METHOD);
+            Some(flags | RevocationPolicy::NETWORK_ACCESS_DISABLED)
+        } else {
+            self.revocation
+        }
+    }
+}
+
+fn evaluate(trust: &SecTrust) -> Result<()> {
+    trust.evaluate_with_error().map_err(|error| {
+        // with tracing, `TrustPolicy::verify` reports the rejection instead
+        #[cfg(all(feature = "log", not(feature = "tracing")))]
+        log::warn!("SecTrustEvaluateWithError: {error}");
+        Error::from_code(error.code() as _)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::certificate;
+
+    fn trust() -> SecTrust {
+        let policy = SecPolicy::create_ssl(SslProtocolSide::SERVER, None);
+        p!(SecTrust::create_with_certificates(&[certificate()], &[policy]))
+    }
+
+    #[test]
+    #[cfg(any(feature = "OSX_10_14", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    fn expiry_skew() {
+        // the test certificate expired in August 2025
+        let mut policy = TrustPolicy::new();
+        policy.add_anchor_certificate(&certificate()).system_roots(false);
+        let err = policy.evaluate(&mut trust(), SslProtocolSide::SERVER, None).unwrap_err();
+        assert_eq!(errSecCertificateExpired, err.code());
+
+        policy.allowed_expiry_skew(Duration::from_secs(20 * 365 * 24 * 60 * 60));
+        p!(policy.evaluate(&mut trust(), SslProtocolSide::SERVER, None));
+        assert!(policy.evaluate(&mut trust(), SslProtocolSide::SERVER, Some("certifi.io")).is_err());
+        policy.verify_hostname("foobar.com");
+        assert_eq!("foobar.com", policy.hostname("certifi.io"));
+    }
+
+    #[test]
+    fn system_roots_only() {
+        let mut policy = TrustPolicy::new();
+        #[cfg(any(feature = "OSX_10_14", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+        policy.allowed_expiry_skew(Duration::from_secs(20 * 365 * 24 * 60 * 60));
+        assert!(policy.evaluate(&mut trust(), SslProtocolSide::SERVER, None).is_err());
+        policy.system_roots(false);
+        assert!(policy.evaluate(&mut trust(), SslProtocolSide::SERVER, None).is_err());
+        assert_ne!(TrustPolicy::new(), policy);
+    }
+
+    #[test]
+    #[cfg(any(feature = "OSX_10_9", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    fn require_ocsp_stapling() {
+        let mut policy = TrustPolicy::new();
+        policy
+            .add_anchor_certificate(&certificate())
+            .system_roots(false)
+            .require_ocsp_stapling(true);
+        #[cfg(any(feature = "OSX_10_14", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+        policy.allowed_expiry_skew(Duration::from_secs(20 * 365 * 24 * 60 * 60));
+        assert!(policy.requires_ocsp_stapling());
+        assert_eq!(
+            Some(RevocationPolicy::OCSP_METHOD | RevocationPolicy::NETWORK_ACCESS_DISABLED),
+            policy.revocation_flags()
+        );
+        assert!(policy.evaluate(&mut trust(), SslProtocolSide::SERVER, None).is_err());
+    }
+
+    #[test]
+    #[cfg(any(feature = "OSX_10_14", target_os = "ios", target_os = "tvos", target_os = "watchos"))]
+    fn require_scts() {
+        let logs = p!(CtLogList::from_json(include_bytes!("../test/ct/log_list.json")));
+        let mut policy = TrustPolicy::new();
+        policy
+            .add_anchor_certificate(&certificate())
+            .system_roots(false)
+            .allowed_expiry_skew(Duration::from_secs(20 * 365 * 24 * 60 * 60));
+        p!(policy.evaluate(&mut trust(), SslProtocolSide::SERVER, None));
+
+        // the test certificate has no embedded SCTs
+        policy.require_scts(&logs, 1);
+        assert_ne!(TrustPolicy::new().sct_requirement, policy.sct_requirement);
+        let err = policy.evaluate(&mut trust(), SslProtocolSide::SERVER, None).unwrap_err();
+        assert_eq!(errSecNotTrusted, err.code());
+
+        policy.require_scts(&logs, 0);
+        p!(policy.evaluate(&mut trust(), SslProtocolSide::SERVER, None));
+    }
+}
@@ -1,12 +1,12 @@
-//! Querying trust settings.
+//! Querying and modifying trust settings.
 
 use core_foundation::array::{CFArray, CFArrayRef};
-use core_foundation::base::{CFIndex, TCFType};
+use core_foundation::base::{CFIndex, CFType, TCFType};
 use core_foundation::dictionary::CFDictionary;
 use core_foundation::number::CFNumber;
 use core_foundation::string::CFString;
 
-use core_foundation_sys::base::CFTypeRef;
+use core_foundation_sys::base::{CFTypeRef, OSStatus};
 use security_framework_sys::base::errSecNoTrustSettings;
 use security_framework_sys::base::errSecSuccess;
 use security_framework_sys::trust_settings::*;
@@ -17,6 +17,8 @@ use crate::base::Error;
 use crate::base::Result;
 use crate::certificate::SecCertificate;
 use crate::cvt;
+use crate::os::macos::access::SecTrustedApplication;
+use crate::policy::SecPolicy;
 
 /// Which set of trust settings to query
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -75,6 +77,219 @@ impl TrustSettingsForCertificate {
             _ => Self::Invalid,
         }
     }
+
+    fn to_value(self) -> SecTrustSettingsResult {
+        match self {
+            Self::Invalid => kSecTrustSettingsResultInvalid,
+            Self::TrustRoot => kSecTrustSettingsResultTrustRoot,
+            Self::TrustAsRoot => kSecTrustSettingsResultTrustAsRoot,
+            Self::Deny => kSecTrustSettingsResultDeny,
+            Self::Unspecified => kSecTrustSettingsResultUnspecified,
+        }
+    }
+}
+
+bitflags::bitflags! {
+    /// The key usages a trust settings entry applies to.
+    pub struct TrustSettingsKeyUsage: SecTrustSettingsKeyUsage {
+        /// Signing data.
+        const SIGNATURE = kSecTrustSettingsKeyUseSignature;
+        /// Encrypting and decrypting data.
+        const ENCRYPT_DATA = kSecTrustSettingsKeyUseEnDecryptData;
+        /// Encrypting and decrypting keys.
+        const ENCRYPT_KEY = kSecTrustSettingsKeyUseEnDecryptKey;
+        /// Signing certificates.
+        const SIGN_CERTIFICATE = kSecTrustSettingsKeyUseSignCert;
+        /// Signing CRLs and OCSP responses.
+        const SIGN_REVOCATION = kSecTrustSettingsKeyUseSignRevocation;
+        /// Key exchange.
+        const KEY_EXCHANGE = kSecTrustSettingsKeyUseKeyExchange;
+        /// Any usage.
+        const ANY = kSecTrustSettingsKeyUseAny;
+    }
+}
+
+/// A single usage constraint within the trust settings of a certificate.
+///
+/// An entry applies when all of its constraints (policy, application, policy
+/// string and key usage) match; unset constraints match anything. The entry
+/// with no constraints and no result means "always trust".
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrustSettingsEntry {
+    policy: Option<SecPolicy>,
+    application: Option<SecTrustedApplication>,
+    policy_string: Option<String>,
+    allowed_error: Option<OSStatus>,
+    result: Option<TrustSettingsForCertificate>,
+    key_usage: Option<TrustSettingsKeyUsage>,
+}
+
+impl TrustSettingsEntry {
+    /// Creates an entry with no constraints.
+    #[inline(always)]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restricts the entry to evaluations using the given policy, `kSecTrustSettingsPolicy`.
+    #[inline]
+    pub fn set_policy(&mut self, policy: SecPolicy) -> &mut Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Restricts the entry to the given application, `kSecTrustSettingsApplication`.
+    #[inline]
+    pub fn set_application(&mut self, application: SecTrustedApplication) -> &mut Self {
+        self.application = Some(application);
+        self
+    }
+
+    /// Restricts the entry to a policy specific string, `kSecTrustSettingsPolicyString`.
+    ///
+    /// This is the hostname for SSL policies and the email address for S/MIME.
+    #[inline]
+    pub fn set_policy_string(&mut self, policy_string: &str) -> &mut Self {
+        self.policy_string = Some(policy_string.to_owned());
+        self
+    }
+
+    /// Ignores the given error during evaluation, `kSecTrustSettingsAllowedError`.
+    #[inline]
+    pub fn set_allowed_error(&mut self, allowed_error: OSStatus) -> &mut Self {
+        self.allowed_error = Some(allowed_error);
+        self
+    }
+
+    /// Sets the outcome when the entry applies, `kSecTrustSettingsResult`.
+    ///
+    /// If unset, the system treats the entry as `TrustRoot`.
+    #[inline]
+    pub fn set_result(&mut self, result: TrustSettingsForCertificate) -> &mut Self {
+        self.result = Some(result);
+        self
+    }
+
+    /// Restricts the entry to the given key usages, `kSecTrustSettingsKeyUsage`.
+    #[inline]
+    pub fn set_key_usage(&mut self, key_usage: TrustSettingsKeyUsage) -> &mut Self {
+        self.key_usage = Some(key_usage);
+        self
+    }
+
+    /// Returns the policy constraint.
+    #[inline(always)]
+    #[must_use]
+    pub fn policy(&self) -> Option<&SecPolicy> {
+        self.policy.as_ref()
+    }
+
+    /// Returns the application constraint.
+    #[inline(always)]
+    #[must_use]
+    pub fn application(&self) -> Option<&SecTrustedApplication> {
+        self.application.as_ref()
+    }
+
+    /// Returns the policy string constraint.
+    #[inline(always)]
+    #[must_use]
+    pub fn policy_string(&self) -> Option<&str> {
+        self.policy_string.as_deref()
+    }
+
+    /// Returns the error ignored by this entry.
+    #[inline(always)]
+    #[must_use]
+    pub fn allowed_error(&self) -> Option<OSStatus> {
+        self.allowed_error
+    }
+
+    /// Returns the outcome when the entry applies.
+    #[inline(always)]
+    #[must_use]
+    pub fn result(&self) -> Option<TrustSettingsForCertificate> {
+        self.result
+    }
+
+    /// Returns the key usage constraint.
+    #[inline(always)]
+    #[must_use]
+    pub fn key_usage(&self) -> Option<TrustSettingsKeyUsage> {
+        self.key_usage
+    }
+
+    /// Converts the entry to a usage constraints dictionary, as taken by
+    /// `SecTrustSettingsSetTrustSettings`.
+    #[must_use]
+    pub fn to_dictionary(&self) -> CFDictionary<CFString, CFType> {
+        let mut params = vec![];
+        if let Some(ref policy) = self.policy {
+            params.push((
+                CFString::from_static_string("kSecTrustSettingsPolicy"),
+                policy.as_CFType(),
+            ));
+        }
+        if let Some(ref application) = self.application {
+            params.push((
+                CFString::from_static_string("kSecTrustSettingsApplication"),
+                application.as_CFType(),
+            ));
+        }
+        if let Some(ref policy_string) = self.policy_string {
+            params.push((
+                CFString::from_static_string("kSecTrustSettingsPolicyString"),
+                CFString::new(policy_string).into_CFType(),
+            ));
+        }
+        if let Some(allowed_error) = self.allowed_error {
+            params.push((
+                CFString::from_static_string("kSecTrustSettingsAllowedError"),
+                CFNumber::from(allowed_error).into_CFType(),
+            ));
+        }
+        if let Some(result) = self.result {
+            params.push((
+                CFString::from_static_string("kSecTrustSettingsResult"),
+                CFNumber::from(i64::from(result.to_value())).into_CFType(),
+            ));
+        }
+        if let Some(key_usage) = self.key_usage {
+            params.push((
+                CFString::from_static_string("kSecTrustSettingsKeyUsage"),
+                CFNumber::from(i64::from(key_usage.bits())).into_CFType(),
+            ));
+        }
+        CFDictionary::from_CFType_pairs(&params)
+    }
+
+    /// Reads an entry from a usage constraints dictionary, as returned by
+    /// `SecTrustSettingsCopyTrustSettings`.
+    ///
+    /// Unknown keys, and values of unexpected types, are ignored.
+    #[must_use]
+    pub fn from_dictionary(dict: &CFDictionary<CFString, CFType>) -> Self {
+        let find = |key: &'static str| dict.find(CFString::from_static_string(key));
+        let number = |key: &'static str| {
+            find(key)
+                .and_then(|value| value.downcast::<CFNumber>())
+                .and_then(|value| value.to_i64())
+        };
+
+        Self {
+            policy: find("kSecTrustSettingsPolicy").and_then(|value| value.downcast::<SecPolicy>()),
+            application: find("kSecTrustSettingsApplication")
+                .and_then(|value| value.downcast::<SecTrustedApplication>()),
+            policy_string: find("kSecTrustSettingsPolicyString")
+                .and_then(|value| value.downcast::<CFString>())
+                .map(|value| value.to_string()),
+            allowed_error: number("kSecTrustSettingsAllowedError").map(|value| value as OSStatus),
+            result: number("kSecTrustSettingsResult").map(TrustSettingsForCertificate::new),
+            key_usage: number("kSecTrustSettingsKeyUsage")
+                .map(|value| TrustSettingsKeyUsage::from_bits_truncate(value as SecTrustSettingsKeyUsage)),
+        }
+    }
 }
 
 /// Allows access to the certificates and their trust settings in a given domain.
@@ -140,6 +355,56 @@ impl TrustSettings {
         })
     }
 
+    /// Replaces the trust settings of the given certificate with the given usage constraints.
+    ///
+    /// An empty slice means "always trust this certificate", as with
+    /// `set_trust_settings_always`.
+    ///
+    /// Modifying the user domain prompts for confirmation, and fails with
+    /// `errSecInternalComponent` when not running in a GUI environment.
+    pub fn set_trust_settings(&self, cert: &SecCertificate, entries: &[TrustSettingsEntry]) -> Result<()> {
+        let entries = entries.iter().map(TrustSettingsEntry::to_dictionary).collect::<Vec<_>>();
+        let entries = CFArray::from_CFTypes(&entries);
+        cvt(unsafe {
+            SecTrustSettingsSetTrustSettings(
+                cert.as_CFTypeRef() as *mut _,
+                self.domain.into(),
+                entries.as_CFTypeRef(),
+            )
+        })
+    }
+
+    /// Removes all trust settings of the given certificate in this domain.
+    ///
+    /// If the certificate has no trust settings in this domain,
+    /// `errSecItemNotFound` is returned.
+    pub fn remove_trust_settings(&self, cert: &SecCertificate) -> Result<()> {
+        cvt(unsafe {
+            SecTrustSettingsRemoveTrustSettings(cert.as_CFTypeRef() as *mut _, self.domain.into())
+        })
+    }
+
+    /// Returns the usage constraints making up the trust settings of the given certificate.
+    ///
+    /// If the certificate has no trust settings in this domain,
+    /// `errSecItemNotFound` is returned.
+    pub fn trust_settings_for_certificate(&self, cert: &SecCertificate) -> Result<Vec<TrustSettingsEntry>> {
+        let trust_settings = unsafe {
+            let mut array_ptr: CFArrayRef = ptr::null_mut();
+            cvt(SecTrustSettingsCopyTrustSettings(
+                cert.as_CFTypeRef() as *mut _,
+                self.domain.into(),
+                &mut array_ptr,
+            ))?;
+            CFArray::<CFDictionary<CFString, CFType>>::wrap_under_create_rule(array_ptr)
+        };
+
+        Ok(trust_settings
+            .iter()
+            .map(|settings| TrustSettingsEntry::from_dictionary(&settings))
+            .collect())
+    }
+
     /// Returns the aggregate trust setting for the given certificate.
     ///
     /// This tells you whether the certificate should be trusted as a TLS
@@ -291,6 +556,34 @@ mod test {
         //  with a resulting kSecTrustSettingsResult of kSecTrustSettingsResultTrustRoot"."
     }
 
+    #[test]
+    fn entry_round_trip() {
+        use crate::secure_transport::SslProtocolSide;
+        use security_framework_sys::base::errSecHostNameMismatch;
+
+        let mut entry = TrustSettingsEntry::new();
+        entry
+            .set_policy(SecPolicy::create_ssl(SslProtocolSide::SERVER, None))
+            .set_policy_string("example.com")
+            .set_allowed_error(errSecHostNameMismatch)
+            .set_result(TrustSettingsForCertificate::Deny)
+            .set_key_usage(TrustSettingsKeyUsage::SIGNATURE | TrustSettingsKeyUsage::KEY_EXCHANGE);
+
+        let dict = entry.to_dictionary();
+        assert_eq!(5, dict.len());
+        assert_eq!(entry, TrustSettingsEntry::from_dictionary(&dict));
+
+        let empty = TrustSettingsEntry::new();
+        assert_eq!(0, empty.to_dictionary().len());
+        assert_eq!(empty, TrustSettingsEntry::from_dictionary(&empty.to_dictionary()));
+    }
+
+    #[test]
+    fn test_unknown_cert_has_no_entries() {
+        let ts = TrustSettings::new(Domain::System);
+        assert!(ts.trust_settings_for_certificate(&certificate()).is_err());
+    }
+
     #[test]
     fn test_unknown_cert_is_not_trusted() {
         let ts = TrustSettings::new(Domain::System);
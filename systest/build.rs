@@ -38,6 +38,9 @@ fn main() {
         .header("Security/SecRandom.h")
         .header("Security/SecureTransport.h")
         .header("Security/SecTrust.h")
+        .header("Security/SecProtocolOptions.h")
+        .header("Security/SecProtocolMetadata.h")
+        .header("CommonCrypto/CommonDigest.h")
         .flag("-Wno-deprecated-declarations")
         .type_name(|name, _, _| name.to_string())
         .skip_signededness(|s| s.ends_with("Ref") || s.ends_with("Func"))